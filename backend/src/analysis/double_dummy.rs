@@ -0,0 +1,691 @@
+//! Double-dummy solver: how many tricks each side takes with best play and every card
+//! in view.
+//!
+//! It is an alpha-beta search over single cards, answering "can North-South take n of
+//! the remaining tricks" rather than searching for the exact count, which cuts off much
+//! earlier. What keeps it fast enough for all twenty declarer/strain pairs of a deal:
+//!
+//! - cards in one hand with nothing left between them are equivalent, so only the top
+//!   one of each sequence is tried;
+//! - the side on lead cashing its top winners gives a cheap bound at each trick;
+//! - a transposition table, shared by every opening leader in a strain, keeps bounds
+//!   for positions at the start of a trick. Alongside each result the search tracks
+//!   which ranks actually decided a trick, so an entry only records the suit lengths
+//!   and who holds the cards that mattered, and matches every position that agrees on
+//!   those however the lower cards lie.
+use std::{
+    collections::HashMap,
+    hash::{BuildHasherDefault, Hasher},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    card::Suit,
+    contract::{Seat, Strain},
+    deal::Deal,
+};
+
+/// Tricks declarer takes double dummy, for every strain and declarer.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DoubleDummyTable {
+    /// Indexed by strain (clubs, diamonds, hearts, spades, no trump) and then by
+    /// declarer (North, East, South, West).
+    pub tricks: [[u8; 4]; 5],
+}
+
+impl DoubleDummyTable {
+    pub fn tricks(&self, strain: Strain, declarer: Seat) -> u8 {
+        self.tricks[strain_index(strain)][declarer.index()]
+    }
+}
+
+fn strain_index(strain: Strain) -> usize {
+    Strain::ALL
+        .iter()
+        .position(|s| *s == strain)
+        .expect("every strain is listed")
+}
+
+/// Solves a deal for every strain and declarer. CPU bound: async callers should run it
+/// on a blocking thread.
+pub fn solve_deal(deal: &Deal) -> DoubleDummyTable {
+    // Strains share nothing, so each is solved on its own thread.
+    let solved: Vec<[u8; 4]> = std::thread::scope(|scope| {
+        let handles: Vec<_> = Strain::ALL
+            .into_iter()
+            .map(|strain| scope.spawn(move || solve_strain(deal, strain)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("double-dummy solver panicked"))
+            .collect()
+    });
+    let mut tricks = [[0; 4]; 5];
+    for (row, strain) in tricks.iter_mut().zip(solved) {
+        *row = strain;
+    }
+    DoubleDummyTable { tricks }
+}
+
+fn solve_strain(deal: &Deal, strain: Strain) -> [u8; 4] {
+    let mut solver = Solver::new(deal, strain);
+    let mut tricks = [0; 4];
+    let mut north_south = None;
+    for declarer in [Seat::North, Seat::South, Seat::East, Seat::West] {
+        let made = solver.declarer_tricks(declarer, north_south);
+        north_south = Some(if declarer.is_north_south() {
+            made
+        } else {
+            13 - made
+        });
+        tricks[declarer.index()] = made;
+    }
+    tricks
+}
+
+/// Tricks `declarer` takes in `strain` with best play by both sides.
+pub fn declarer_tricks(deal: &Deal, strain: Strain, declarer: Seat) -> u8 {
+    Solver::new(deal, strain).declarer_tricks(declarer, None)
+}
+
+/// A card as a suit index (in `Suit::ALL` order) and a rank from 2 to 14.
+#[derive(Debug, Copy, Clone, Default)]
+struct Played {
+    seat: usize,
+    suit: usize,
+    rank: u8,
+}
+
+/// Rank bitmasks, one per suit, of the cards whose ranks decided a search result.
+type Ranks = [u16; 4];
+
+/// Bounds on the tricks North-South take from every position matching a pattern: the
+/// same suit lengths in each hand (the table key) and the same holders of the top
+/// `depth` cards still out in each suit.
+#[derive(Debug, Copy, Clone)]
+struct Entry {
+    depth: [u8; 4],
+    /// Holders of those cards, two bits each, the highest card in the lowest bits.
+    holders: [u32; 4],
+    lower: u8,
+    upper: u8,
+}
+
+/// The cards still out at the start of a trick.
+struct Layout {
+    lengths: u64,
+    /// Rank bitmask of every card still held, per suit.
+    live: [u16; 4],
+    /// Holders of the live cards in each suit, as in `Entry::holders`.
+    holders: [u32; 4],
+}
+
+impl Entry {
+    fn matches(&self, layout: &Layout) -> bool {
+        (0..4).all(|suit| {
+            let mask = (1u32 << (2 * self.depth[suit])) - 1;
+            layout.holders[suit] & mask == self.holders[suit]
+        })
+    }
+
+    fn ranks(&self, layout: &Layout) -> Ranks {
+        std::array::from_fn(|suit| top_cards(layout.live[suit], self.depth[suit] as u32))
+    }
+}
+
+/// The `count` highest ranks of a rank bitmask.
+fn top_cards(mask: u16, count: u32) -> u16 {
+    let mut top = 0;
+    let mut rest = mask;
+    for _ in 0..count.min(mask.count_ones()) {
+        let highest = 1 << (15 - rest.leading_zeros());
+        top |= highest;
+        rest &= !highest;
+    }
+    top
+}
+
+type Table = HashMap<(u64, u8), Vec<Entry>, BuildHasherDefault<PositionHasher>>;
+
+struct Solver {
+    /// `hands[seat][suit]` is a rank bitmask, bit `n` for rank `n`.
+    hands: [[u16; 4]; 4],
+    trump: Option<usize>,
+    table: Table,
+    /// How often each card, by seat, suit and rank, has caused a cutoff, weighted
+    /// towards early tricks. Leads are tried in that order.
+    history: [[[u32; 15]; 4]; 4],
+}
+
+impl Solver {
+    fn new(deal: &Deal, strain: Strain) -> Self {
+        let mut hands = [[0; 4]; 4];
+        for seat in Seat::ALL {
+            for suit in Suit::ALL {
+                hands[seat.index()][suit.index()] = deal.hand(seat).suit_mask(suit);
+            }
+        }
+        Solver {
+            hands,
+            trump: strain.trump().map(|suit| suit.index()),
+            table: Table::default(),
+            history: [[[0; 15]; 4]; 4],
+        }
+    }
+
+    /// Tricks for `declarer`, searching outwards from `guess` (North-South tricks), which
+    /// is usually right or one off when it comes from the other member of the partnership.
+    fn declarer_tricks(&mut self, declarer: Seat, guess: Option<u8>) -> u8 {
+        let leader = declarer.next().index();
+        let left = self.hands[0].iter().map(|s| s.count_ones()).sum::<u32>() as u8;
+        let mut north_south = guess.unwrap_or(left / 2).min(left);
+        if self.north_south_make(leader, north_south as i8, left).0 {
+            while north_south < left && self.north_south_make(leader, north_south as i8 + 1, left).0
+            {
+                north_south += 1;
+            }
+        } else {
+            north_south -= 1;
+            while north_south > 0 && !self.north_south_make(leader, north_south as i8, left).0 {
+                north_south -= 1;
+            }
+        }
+        if declarer.is_north_south() {
+            north_south
+        } else {
+            left - north_south
+        }
+    }
+
+    /// Whether North-South can take `need` of the `left` remaining tricks, with `leader`
+    /// to lead to the next trick, and the ranks that decided it.
+    fn north_south_make(&mut self, leader: usize, need: i8, left: u8) -> (bool, Ranks) {
+        if need <= 0 {
+            return (true, [0; 4]);
+        }
+        if need as u8 > left {
+            return (false, [0; 4]);
+        }
+        let layout = self.layout();
+        let key = (layout.lengths, leader as u8);
+        if let Some(entries) = self.table.get(&key) {
+            for entry in entries.iter().filter(|entry| entry.matches(&layout)) {
+                if entry.lower as i8 >= need {
+                    return (true, entry.ranks(&layout));
+                }
+                if (entry.upper as i8) < need {
+                    return (false, entry.ranks(&layout));
+                }
+            }
+        }
+        let north_south = leader.is_multiple_of(2);
+        let (quick, quick_ranks) = self.quick_tricks(leader, &layout);
+        let quick = quick.min(left) as i8;
+        if north_south && quick >= need {
+            return (true, quick_ranks);
+        }
+        if !north_south && left as i8 - quick < need {
+            return (false, quick_ranks);
+        }
+        if let Some((holder, trumps, trump_ranks)) = self.top_trumps(&layout) {
+            if holder.is_multiple_of(2) && trumps as i8 >= need {
+                return (true, trump_ranks);
+            }
+            if !holder.is_multiple_of(2) && left as i8 - (trumps as i8) < need {
+                return (false, trump_ranks);
+            }
+        }
+        let mut trick = [Played::default(); 4];
+        let (made, ranks) = self.play(leader, &mut trick, 0, need, left);
+        self.store(key, &layout, ranks, made, need as u8, left);
+        (made, ranks)
+    }
+
+    fn store(
+        &mut self,
+        key: (u64, u8),
+        layout: &Layout,
+        ranks: Ranks,
+        made: bool,
+        need: u8,
+        left: u8,
+    ) {
+        let mut depth = [0; 4];
+        let mut holders = [0; 4];
+        for suit in 0..4 {
+            let relevant = ranks[suit] & layout.live[suit];
+            if relevant != 0 {
+                depth[suit] = (layout.live[suit] >> relevant.trailing_zeros()).count_ones() as u8;
+                holders[suit] = layout.holders[suit] & ((1u32 << (2 * depth[suit])) - 1);
+            }
+        }
+        let entries = self.table.entry(key).or_default();
+        let index = match entries
+            .iter()
+            .position(|entry| entry.depth == depth && entry.holders == holders)
+        {
+            Some(index) => index,
+            None => {
+                entries.push(Entry {
+                    depth,
+                    holders,
+                    lower: 0,
+                    upper: left,
+                });
+                entries.len() - 1
+            }
+        };
+        let entry = &mut entries[index];
+        if made {
+            entry.lower = entry.lower.max(need);
+        } else {
+            entry.upper = entry.upper.min(need - 1);
+        }
+    }
+
+    fn play(
+        &mut self,
+        leader: usize,
+        trick: &mut [Played; 4],
+        played: usize,
+        need: i8,
+        left: u8,
+    ) -> (bool, Ranks) {
+        if played == 4 {
+            let best = self.winning_card(trick);
+            let won = if best.seat.is_multiple_of(2) { 1 } else { 0 };
+            let (made, mut ranks) = self.north_south_make(best.seat, need - won, left - 1);
+            // The winning card's rank only mattered if it beat another card of its suit.
+            if trick
+                .iter()
+                .any(|card| card.suit == best.suit && card.rank < best.rank)
+            {
+                ranks[best.suit] |= 1 << best.rank;
+            }
+            return (made, ranks);
+        }
+        let seat = (leader + played) % 4;
+        let north_south = seat.is_multiple_of(2);
+        let mut all_ranks = [0; 4];
+        for (suit, rank, lowest) in self.moves(seat, trick, played) {
+            self.hands[seat][suit] &= !(1 << rank);
+            trick[played] = Played { seat, suit, rank };
+            let (made, mut ranks) = self.play(leader, trick, played + 1, need, left);
+            // The card stood for the rest of its sequence too. If its rank decided a
+            // trick, so did theirs: a position where lower cards split the sequence
+            // gives the player choices this search never tried.
+            if ranks[suit] & (1 << rank) != 0 {
+                ranks[suit] |= 1 << lowest;
+            }
+            self.hands[seat][suit] |= 1 << rank;
+            if made == north_south {
+                self.history[seat][suit][rank as usize] += (left as u32) * (left as u32);
+                return (made, ranks);
+            }
+            for suit in 0..4 {
+                all_ranks[suit] |= ranks[suit];
+            }
+        }
+        (!north_south, all_ranks)
+    }
+
+    fn layout(&self) -> Layout {
+        let mut lengths = 0;
+        let mut live = [0; 4];
+        let mut holders = [0; 4];
+        for suit in 0..4 {
+            for (seat, hand) in self.hands.iter().enumerate() {
+                lengths |= (hand[suit].count_ones() as u64) << (16 * seat + 4 * suit);
+                live[suit] |= hand[suit];
+            }
+            let mut place = 0;
+            for rank in (2..=14u8).rev() {
+                if live[suit] & (1 << rank) == 0 {
+                    continue;
+                }
+                let holder = (0..4)
+                    .find(|seat| self.hands[*seat][suit] & (1 << rank) != 0)
+                    .expect("a live card is held");
+                holders[suit] |= (holder as u32) << (2 * place);
+                place += 1;
+            }
+        }
+        Layout {
+            lengths,
+            live,
+            holders,
+        }
+    }
+
+    /// Tricks the leader can take straight away by cashing top cards: in each suit, the
+    /// run of top cards they hold, cut short when an opponent who still has trumps could
+    /// ruff. Also returns the ranks of the cards counted.
+    fn quick_tricks(&self, leader: usize, layout: &Layout) -> (u8, Ranks) {
+        let opponents = [(leader + 1) % 4, (leader + 3) % 4];
+        let mut quick = 0;
+        let mut ranks = [0; 4];
+        for (suit, suit_ranks) in ranks.iter_mut().enumerate() {
+            let hand = self.hands[leader][suit];
+            let mut tops = 0;
+            for rank in (2..=14u8).rev() {
+                let bit = 1 << rank;
+                if hand & bit != 0 {
+                    tops += 1;
+                } else if layout.live[suit] & bit != 0 {
+                    break;
+                }
+            }
+            if let Some(trump) = self.trump.filter(|trump| *trump != suit) {
+                for opponent in opponents {
+                    if self.hands[opponent][trump] != 0 {
+                        tops = tops.min(self.hands[opponent][suit].count_ones());
+                    }
+                }
+            }
+            quick += tops as u8;
+            *suit_ranks = top_cards(layout.live[suit], tops);
+        }
+        (quick, ranks)
+    }
+
+    /// Whoever holds the top trump wins a trick with it, and one more with each trump
+    /// below it they also hold without a break.
+    fn top_trumps(&self, layout: &Layout) -> Option<(usize, u8, Ranks)> {
+        let trump = self.trump?;
+        let live = layout.live[trump];
+        if live == 0 {
+            return None;
+        }
+        let top = 15 - live.leading_zeros() as u8;
+        let holder = (0..4).find(|seat| self.hands[*seat][trump] & (1 << top) != 0)?;
+        let mut tricks = 0;
+        for rank in (2..=top).rev() {
+            let bit = 1 << rank;
+            if self.hands[holder][trump] & bit != 0 {
+                tricks += 1;
+            } else if live & bit != 0 {
+                break;
+            }
+        }
+        let mut ranks = [0; 4];
+        ranks[trump] = top_cards(live, tricks as u32);
+        Some((holder, tricks, ranks))
+    }
+
+    /// Cards worth trying for `seat`, best guesses first: one card from each sequence of
+    /// equivalent cards, in the suit led if possible, with the lowest rank of its sequence.
+    fn moves(&self, seat: usize, trick: &[Played; 4], played: usize) -> Vec<(usize, u8, u8)> {
+        let hand = self.hands[seat];
+        let suits: Vec<usize> = match trick[..played].first() {
+            Some(lead) if hand[lead.suit] != 0 => vec![lead.suit],
+            _ => (0..4).filter(|suit| hand[*suit] != 0).collect(),
+        };
+        let mut moves: Vec<(usize, u8, u8)> = Vec::with_capacity(13);
+        for suit in suits {
+            // Cards still in play in this suit, including those on the table.
+            let mut live = self.hands.iter().fold(0, |live, hand| live | hand[suit]);
+            for card in trick[..played].iter().filter(|card| card.suit == suit) {
+                live |= 1 << card.rank;
+            }
+            let mut in_sequence = false;
+            for rank in (2..=14u8).rev() {
+                let bit = 1 << rank;
+                if hand[suit] & bit != 0 {
+                    if in_sequence {
+                        moves.last_mut().expect("sequence started").2 = rank;
+                    } else {
+                        moves.push((suit, rank, rank));
+                    }
+                    in_sequence = true;
+                } else if live & bit != 0 {
+                    in_sequence = false;
+                }
+            }
+        }
+        if played == 0 {
+            moves.sort_by_key(|&(suit, rank, _)| {
+                std::cmp::Reverse(self.history[seat][suit][rank as usize])
+            });
+            return moves;
+        }
+        // Following: if partner is already winning, play low; otherwise try the cheapest
+        // card that takes the lead, then the lowest cards.
+        let best = self.winning_card(&trick[..played]);
+        let partner_winning = best.seat == (seat + 2) % 4;
+        moves.sort_by_key(|&(suit, rank, _)| {
+            let wins = !partner_winning && self.beats(suit, rank, best);
+            (!wins, rank)
+        });
+        moves
+    }
+
+    fn winning_card(&self, trick: &[Played]) -> Played {
+        let mut best = trick[0];
+        for card in &trick[1..] {
+            if self.beats(card.suit, card.rank, best) {
+                best = *card;
+            }
+        }
+        best
+    }
+
+    fn beats(&self, suit: usize, rank: u8, best: Played) -> bool {
+        if suit == best.suit {
+            rank > best.rank
+        } else {
+            Some(suit) == self.trump
+        }
+    }
+}
+
+/// FNV-1a; table keys are small and the default hasher is needlessly slow for them.
+struct PositionHasher(u64);
+
+impl Default for PositionHasher {
+    fn default() -> Self {
+        PositionHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for PositionHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+    use super::*;
+
+    /// North-South tricks by trying every legal card, remembering positions at the
+    /// start of each trick.
+    fn brute_force(
+        hands: &mut [[u16; 4]; 4],
+        trump: Option<usize>,
+        leader: usize,
+        seen: &mut HashMap<([[u16; 4]; 4], usize), u8>,
+    ) -> u8 {
+        if hands[leader].iter().all(|suit| *suit == 0) {
+            return 0;
+        }
+        if let Some(tricks) = seen.get(&(*hands, leader)) {
+            return *tricks;
+        }
+        let tricks = brute_force_trick(hands, trump, leader, &mut Vec::new(), seen);
+        seen.insert((*hands, leader), tricks);
+        tricks
+    }
+
+    fn brute_force_trick(
+        hands: &mut [[u16; 4]; 4],
+        trump: Option<usize>,
+        leader: usize,
+        trick: &mut Vec<Played>,
+        seen: &mut HashMap<([[u16; 4]; 4], usize), u8>,
+    ) -> u8 {
+        if trick.len() == 4 {
+            let mut best = trick[0];
+            for card in &trick[1..] {
+                let trumped = card.suit != best.suit && Some(card.suit) == trump;
+                if trumped || (card.suit == best.suit && card.rank > best.rank) {
+                    best = *card;
+                }
+            }
+            let won = if best.seat.is_multiple_of(2) { 1 } else { 0 };
+            return won + brute_force(hands, trump, best.seat, seen);
+        }
+        let seat = (leader + trick.len()) % 4;
+        let north_south = seat.is_multiple_of(2);
+        let follow = trick
+            .first()
+            .map(|lead| lead.suit)
+            .filter(|suit| hands[seat][*suit] != 0);
+        let mut best = if north_south { 0 } else { u8::MAX };
+        for suit in (0..4).filter(|suit| follow.is_none_or(|follow| follow == *suit)) {
+            let held = hands[seat][suit];
+            for rank in (2..=14u8).filter(|rank| held & (1 << rank) != 0) {
+                hands[seat][suit] &= !(1 << rank);
+                trick.push(Played { seat, suit, rank });
+                let tricks = brute_force_trick(hands, trump, leader, trick, seen);
+                trick.pop();
+                hands[seat][suit] |= 1 << rank;
+                best = if north_south {
+                    best.max(tricks)
+                } else {
+                    best.min(tricks)
+                };
+            }
+        }
+        best
+    }
+
+    /// North-South tricks from the solver, one target at a time as the search is run.
+    fn solved(solver: &mut Solver, leader: usize) -> u8 {
+        let left = solver.hands[0].iter().map(|s| s.count_ones()).sum::<u32>() as u8;
+        (1..=left)
+            .take_while(|need| solver.north_south_make(leader, *need as i8, left).0)
+            .count() as u8
+    }
+
+    fn solver(hands: [[u16; 4]; 4], trump: Option<usize>) -> Solver {
+        Solver {
+            hands,
+            trump,
+            table: Table::default(),
+            history: [[[0; 15]; 4]; 4],
+        }
+    }
+
+    #[test]
+    fn ending_with_equal_cards_in_different_hands() {
+        let hands = [
+            [8192, 16512, 16384, 0],
+            [20480, 512, 0, 32],
+            [516, 0, 512, 512],
+            [0, 5376, 1024, 0],
+        ];
+        let hearts = Some(Suit::Hearts.index());
+        assert_eq!(
+            brute_force(&mut hands.clone(), hearts, 2, &mut HashMap::new()),
+            1
+        );
+        assert!(!solver(hands, hearts).north_south_make(2, 2, 4).0);
+        assert_eq!(solved(&mut solver(hands, hearts), 2), 1);
+    }
+
+    #[test]
+    fn endings_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut deck: Vec<(usize, u8)> = (0..4)
+            .flat_map(|suit| (2..=14u8).map(move |rank| (suit, rank)))
+            .collect();
+        for cards in 1..=4 {
+            for _ in 0..60 {
+                deck.shuffle(&mut rng);
+                let mut hands = [[0u16; 4]; 4];
+                for (index, (suit, rank)) in deck[..4 * cards].iter().enumerate() {
+                    hands[index % 4][*suit] |= 1 << rank;
+                }
+                let trump = [None, Some(0), Some(1), Some(2), Some(3)][rng.random_range(0..5)];
+                // One solver for every leader, as when solving a deal.
+                let mut solver = solver(hands, trump);
+                let mut seen = HashMap::new();
+                for leader in 0..4 {
+                    let expected = brute_force(&mut hands, trump, leader, &mut seen);
+                    // A target out of order first, so the table holds bounds from other
+                    // searches when the counting starts.
+                    let need = rng.random_range(1..=cards);
+                    assert_eq!(
+                        solver.north_south_make(leader, need as i8, cards as u8).0,
+                        expected >= need as u8,
+                        "{hands:?}, trump {trump:?}, leader {leader}, need {need}"
+                    );
+                    assert_eq!(
+                        solved(&mut solver, leader),
+                        expected,
+                        "{hands:?}, trump {trump:?}, leader {leader}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solves_whole_suits() {
+        let deal: Deal = "N:AKQJT98765432... .AKQJT98765432.. ..AKQJT98765432. ...AKQJT98765432"
+            .parse()
+            .unwrap();
+        let table = solve_deal(&deal);
+        assert_eq!(table.tricks[strain_index(Strain::NoTrump)], [0, 0, 0, 0]);
+        assert_eq!(table.tricks[strain_index(Strain::Spades)], [13, 0, 13, 0]);
+        assert_eq!(table.tricks[strain_index(Strain::Hearts)], [0, 13, 0, 13]);
+        assert_eq!(table.tricks[strain_index(Strain::Diamonds)], [13, 0, 13, 0]);
+        assert_eq!(table.tricks[strain_index(Strain::Clubs)], [0, 13, 0, 13]);
+    }
+
+    #[test]
+    fn solves_a_deal_with_every_top_card_in_one_hand() {
+        let deal: Deal = "N:AKQJ.AKQ.AKQ.AKQ T98.JT9.JT9.JT98 765432.8765432.. ..8765432.765432"
+            .parse()
+            .unwrap();
+        let table = solve_deal(&deal);
+        assert_eq!(declarer_tricks(&deal, Strain::NoTrump, Seat::South), 13);
+        assert_eq!(table.tricks[strain_index(Strain::NoTrump)], [13, 0, 13, 0]);
+    }
+
+    #[test]
+    fn solves_a_published_deal() {
+        // The first example deal shipped with Bo Haglund's DDS library, with its table.
+        let deal: Deal = "N:QJ6.K652.J85.T98 873.J97.AT764.Q4 K5.T83.KQ9.A7652 AT942.AQ4.32.KJ3"
+            .parse()
+            .unwrap();
+        let table = solve_deal(&deal);
+        let expected = [
+            (Strain::Spades, [5, 8, 5, 8]),
+            (Strain::Hearts, [6, 6, 6, 6]),
+            (Strain::Diamonds, [5, 7, 5, 7]),
+            (Strain::Clubs, [7, 5, 7, 5]),
+            (Strain::NoTrump, [6, 6, 6, 6]),
+        ];
+        for (strain, tricks) in expected {
+            for (declarer, tricks) in Seat::ALL.into_iter().zip(tricks) {
+                assert_eq!(
+                    table.tricks(strain, declarer),
+                    tricks,
+                    "{strain} by {declarer}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod double_dummy;
+pub mod par;
\ No newline at end of file
@@ -0,0 +1,375 @@
+//! Par: the result when both sides bid to the best contract they can with every card in
+//! view, taking the double-dummy table as the tricks each declarer would make.
+//!
+//! Worked out as a bidding game over the 35 contracts. Whichever side's turn it is can
+//! pass, leaving the last contract to be played (doubled when it goes down, so a
+//! sacrifice costs its full penalty), or outbid it with any higher contract, played by
+//! whichever partner takes more tricks. The dealer's side gets the first chance, which
+//! decides par when both sides have a making contract at the same level.
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    board::{BoardMongoDTO, Room},
+    contract::{Contract, Doubled, Seat, Strain, Vulnerability},
+    scoring::{duplicate::ns_duplicate_score, imp::imps},
+};
+
+use super::double_dummy::DoubleDummyTable;
+
+const CONTRACTS: usize = 35;
+const SIDES: [[Seat; 2]; 2] = [[Seat::North, Seat::South], [Seat::East, Seat::West]];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Par {
+    /// Par score from North-South's point of view; 0 when the board should be passed out.
+    pub score: i32,
+    /// Every contract that scores par, lowest level first. Empty when passed out.
+    pub contracts: Vec<ParContract>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ParContract {
+    pub contract: Contract,
+    pub declarer: Seat,
+    pub tricks: u8,
+}
+
+/// Par score and contracts for a deal's double-dummy table.
+pub fn par(table: &DoubleDummyTable, dealer: Seat, vulnerability: Vulnerability) -> Par {
+    let game = ParGame::new(table, vulnerability);
+    let first = side_of(dealer);
+    let second = 1 - first;
+
+    // What each side gets out of acting with nothing bid yet; the second side passing
+    // passes the board out.
+    let second_value = game.choose(second, 0, game.bids(None, second));
+    let first_value = game.choose(first, second_value, game.bids(None, first));
+
+    let mut finals = Vec::new();
+    let mut seen = [[false; 2]; CONTRACTS];
+    for index in 0..CONTRACTS {
+        if game.values[index][first] == first_value {
+            game.collect(index, first, &mut finals, &mut seen);
+        }
+    }
+    if second_value == first_value {
+        for index in 0..CONTRACTS {
+            if game.values[index][second] == second_value {
+                game.collect(index, second, &mut finals, &mut seen);
+            }
+        }
+    }
+
+    // Overtricks can make a higher contract in the same strain score the same; only the
+    // lowest is par.
+    finals.sort_unstable();
+    let mut contracts = Vec::new();
+    for (position, (index, side)) in finals.iter().enumerate() {
+        let lower_exists = finals[..position]
+            .iter()
+            .any(|(other, other_side)| other_side == side && other % 5 == index % 5);
+        if lower_exists {
+            continue;
+        }
+        let (contract, tricks) = game.played_contract(*index, *side);
+        for declarer in SIDES[*side] {
+            if table.tricks(contract.strain, declarer) == tricks {
+                contracts.push(ParContract {
+                    contract,
+                    declarer,
+                    tricks,
+                });
+            }
+        }
+    }
+    Par {
+        score: first_value,
+        contracts,
+    }
+}
+
+fn side_of(seat: Seat) -> usize {
+    if seat.is_north_south() {
+        0
+    } else {
+        1
+    }
+}
+
+struct ParGame<'a> {
+    table: &'a DoubleDummyTable,
+    vulnerability: Vulnerability,
+    /// North-South's score with best bidding from here on, once a side has bid a contract
+    /// and it is the other side's turn. Indexed by contract (in bidding order) and the
+    /// side that bid it.
+    values: [[i32; 2]; CONTRACTS],
+}
+
+impl<'a> ParGame<'a> {
+    fn new(table: &'a DoubleDummyTable, vulnerability: Vulnerability) -> Self {
+        let mut game = ParGame {
+            table,
+            vulnerability,
+            values: [[0; 2]; CONTRACTS],
+        };
+        for index in (0..CONTRACTS).rev() {
+            for side in 0..2 {
+                let opponents = 1 - side;
+                let pass = game.played_score(index, side);
+                game.values[index][side] =
+                    game.choose(opponents, pass, game.bids(Some(index), opponents));
+            }
+        }
+        game
+    }
+
+    /// Scores `side` can reach by outbidding `index`: the values of every higher bid.
+    fn bids(&self, index: Option<usize>, side: usize) -> impl Iterator<Item = i32> + '_ {
+        let from = index.map_or(0, |index| index + 1);
+        (from..CONTRACTS).map(move |higher| self.values[higher][side])
+    }
+
+    /// The best of passing and bidding on for `side`.
+    fn choose(&self, side: usize, pass: i32, bids: impl Iterator<Item = i32>) -> i32 {
+        bids.fold(pass, |best, value| {
+            if side == 0 {
+                best.max(value)
+            } else {
+                best.min(value)
+            }
+        })
+    }
+
+    /// Contracts the auction can end in, following every best choice after `side` bids
+    /// the contract at `index`.
+    fn collect(
+        &self,
+        index: usize,
+        side: usize,
+        finals: &mut Vec<(usize, usize)>,
+        seen: &mut [[bool; 2]; CONTRACTS],
+    ) {
+        if std::mem::replace(&mut seen[index][side], true) {
+            return;
+        }
+        let value = self.values[index][side];
+        if self.played_score(index, side) == value {
+            finals.push((index, side));
+        }
+        let opponents = 1 - side;
+        for higher in index + 1..CONTRACTS {
+            if self.values[higher][opponents] == value {
+                self.collect(higher, opponents, finals, seen);
+            }
+        }
+    }
+
+    /// The contract at `index` as `side` would play it, doubled when it goes down, and
+    /// the tricks their better declarer takes.
+    fn played_contract(&self, index: usize, side: usize) -> (Contract, u8) {
+        let level = (index / 5 + 1) as u8;
+        let strain = Strain::ALL[index % 5];
+        let tricks = SIDES[side]
+            .iter()
+            .map(|declarer| self.table.tricks(strain, *declarer))
+            .max()
+            .unwrap_or(0);
+        let doubled = if tricks < level + 6 {
+            Doubled::Doubled
+        } else {
+            Doubled::Undoubled
+        };
+        let contract = Contract {
+            level,
+            strain,
+            doubled,
+        };
+        (contract, tricks)
+    }
+
+    fn played_score(&self, index: usize, side: usize) -> i32 {
+        let (contract, tricks) = self.played_contract(index, side);
+        ns_duplicate_score(&contract, SIDES[side][0], self.vulnerability, tricks)
+            .expect("double-dummy tricks are at most 13")
+    }
+}
+
+/// A board's results measured against par.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardPar {
+    pub board_number: u32,
+    pub dd_table: DoubleDummyTable,
+    pub par: Par,
+    pub results: Vec<ResultVsPar>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultVsPar {
+    pub room: Option<Room>,
+    pub ns_pair: Option<u32>,
+    pub ew_pair: Option<u32>,
+    pub contract: Option<Contract>,
+    pub declarer: Option<Seat>,
+    pub tricks: Option<u8>,
+    pub score: i32,
+    /// Score minus the par score, from North-South's point of view: positive when
+    /// North-South beat par, negative when they lost points to it.
+    pub par_difference: i32,
+    /// The difference converted to IMPs.
+    pub imps: i32,
+}
+
+/// Compares every result on a board with the par of its double-dummy table.
+pub fn board_par(board: &BoardMongoDTO, dd_table: DoubleDummyTable) -> BoardPar {
+    let par = par(&dd_table, board.dealer, board.vulnerability);
+    let results = board
+        .results
+        .iter()
+        .map(|result| {
+            let par_difference = result.score - par.score;
+            ResultVsPar {
+                room: result.room,
+                ns_pair: result.ns_pair,
+                ew_pair: result.ew_pair,
+                contract: result.contract,
+                declarer: result.declarer,
+                tricks: result.tricks,
+                score: result.score,
+                par_difference,
+                imps: imps(par_difference),
+            }
+        })
+        .collect();
+    BoardPar {
+        board_number: board.board_number,
+        dd_table,
+        par,
+        results,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::scoring::fixtures::{board, pair_result};
+
+    /// Rows are clubs, diamonds, hearts, spades and no trump; columns North, East, South
+    /// and West.
+    fn table(tricks: [[u8; 4]; 5]) -> DoubleDummyTable {
+        DoubleDummyTable { tricks }
+    }
+
+    fn names(par: &Par) -> Vec<String> {
+        par.contracts
+            .iter()
+            .map(|c| format!("{} {:?} {}", c.contract, c.declarer, c.tricks))
+            .collect()
+    }
+
+    #[test]
+    fn game_for_the_side_that_makes_it() {
+        let dd = table([
+            [6, 7, 6, 7],
+            [6, 7, 6, 7],
+            [7, 6, 7, 6],
+            [10, 3, 10, 3],
+            [8, 5, 8, 5],
+        ]);
+        let par = par(&dd, Seat::North, Vulnerability::None);
+        assert_eq!(par.score, 420);
+        assert_eq!(names(&par), ["4S North 10", "4S South 10"]);
+    }
+
+    #[test]
+    fn only_the_partner_taking_the_tricks_declares() {
+        let dd = table([
+            [6, 7, 6, 7],
+            [6, 7, 6, 7],
+            [7, 6, 7, 6],
+            [10, 3, 9, 3],
+            [8, 5, 8, 5],
+        ]);
+        let par = par(&dd, Seat::North, Vulnerability::None);
+        assert_eq!(par.score, 420);
+        assert_eq!(names(&par), ["4S North 10"]);
+    }
+
+    #[test]
+    fn sacrifice_depends_on_vulnerability() {
+        // North-South make 4S; East-West take 9 tricks in diamonds.
+        let dd = table([
+            [6, 7, 6, 7],
+            [4, 9, 4, 9],
+            [7, 6, 7, 6],
+            [10, 3, 10, 3],
+            [7, 5, 7, 5],
+        ]);
+        let none = par(&dd, Seat::North, Vulnerability::None);
+        assert_eq!(none.score, 300);
+        assert_eq!(names(&none), ["5DX East 9", "5DX West 9"]);
+        // Vulnerable, 5DX-2 costs 500, more than the game.
+        assert_eq!(par(&dd, Seat::North, Vulnerability::Ew).score, 420);
+        // North-South vulnerable: 620 for the game, so the save costs less.
+        assert_eq!(par(&dd, Seat::North, Vulnerability::Ns).score, 300);
+    }
+
+    #[test]
+    fn passed_out_when_nothing_makes() {
+        let dd = table([[6; 4]; 5]);
+        let par = par(&dd, Seat::East, Vulnerability::Both);
+        assert_eq!(par.score, 0);
+        assert!(par.contracts.is_empty());
+    }
+
+    #[test]
+    fn dealer_side_gets_the_contract_both_make() {
+        let dd = table([[6; 4], [6; 4], [6; 4], [6; 4], [7; 4]]);
+        assert_eq!(par(&dd, Seat::North, Vulnerability::None).score, 90);
+        assert_eq!(par(&dd, Seat::West, Vulnerability::None).score, -90);
+    }
+
+    #[test]
+    fn lowest_contract_with_overtricks() {
+        let dd = table([
+            [6, 7, 6, 7],
+            [6, 7, 6, 7],
+            [7, 6, 7, 6],
+            [11, 2, 11, 2],
+            [8, 5, 8, 5],
+        ]);
+        let par = par(&dd, Seat::North, Vulnerability::None);
+        assert_eq!(par.score, 450);
+        assert_eq!(names(&par), ["4S North 11", "4S South 11"]);
+    }
+
+    #[test]
+    fn compares_results_with_par() {
+        let dd = table([
+            [6, 7, 6, 7],
+            [6, 7, 6, 7],
+            [7, 6, 7, 6],
+            [10, 3, 10, 3],
+            [8, 5, 8, 5],
+        ]);
+        let board = board(
+            1,
+            vec![
+                pair_result(1, 2, 420),
+                pair_result(3, 4, 170),
+                pair_result(5, 6, -50),
+            ],
+        );
+        let board_par = board_par(&board, dd);
+        assert_eq!(board_par.par.score, 420);
+        let differences: Vec<(i32, i32)> = board_par
+            .results
+            .iter()
+            .map(|result| (result.par_difference, result.imps))
+            .collect();
+        assert_eq!(differences, [(0, 0), (-250, -6), (-470, -10)]);
+    }
+}
@@ -0,0 +1,387 @@
+//! A small language for describing the hands a deal should have, e.g.
+//! `North 15-17 balanced, South 4+ spades, 8+ HCP`.
+//!
+//! A description is a list of clauses separated by commas or semicolons. A clause may
+//! start with a seat; otherwise it applies to the seat of the previous clause. Each
+//! clause holds one or more predicates:
+//!
+//! - a count followed by what is counted: `15-17`, `15+`, `12-` (at most) or `4`
+//!   (exactly), then `hcp` (the default), a suit (`spades`, `h`, ...) or `ltc`/`losers`;
+//! - `balanced`, `semi-balanced` or `unbalanced`;
+//! - a shape: `4-4-4-1` or `4441` in any suit order, or `5=4=3=1` for exactly that many
+//!   spades, hearts, diamonds and clubs.
+use crate::models::{
+    card::{Rank, Suit},
+    contract::Seat,
+    deal::{Deal, Hand},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConstraintError {
+    #[error("Nothing to constrain")]
+    Empty,
+    #[error("Clause \"{0}\" comes before any seat is named")]
+    MissingSeat(String),
+    #[error("Could not understand \"{token}\" in \"{clause}\"")]
+    UnexpectedToken { clause: String, token: String },
+    #[error("Invalid range {0}")]
+    InvalidRange(String),
+    #[error("Invalid shape {0}")]
+    InvalidShape(String),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Range {
+    pub min: u8,
+    pub max: u8,
+}
+
+impl Range {
+    pub fn contains(&self, value: u8) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Shape {
+    /// 4-3-3-3, 4-4-3-2 or 5-3-3-2.
+    Balanced,
+    /// Balanced, or 5-4-2-2 or 6-3-2-2.
+    SemiBalanced,
+    Unbalanced,
+    /// Suit lengths in any order, longest first.
+    Pattern([u8; 4]),
+    /// Spade, heart, diamond and club lengths.
+    Exact([u8; 4]),
+}
+
+const BALANCED: [[u8; 4]; 3] = [[4, 3, 3, 3], [4, 4, 3, 2], [5, 3, 3, 2]];
+const SEMI_BALANCED: [[u8; 4]; 2] = [[5, 4, 2, 2], [6, 3, 2, 2]];
+
+impl Shape {
+    pub fn matches(&self, hand: &Hand) -> bool {
+        let lengths = Suit::ALL.map(|suit| hand.suit_length(suit) as u8);
+        let mut pattern = lengths;
+        pattern.sort_unstable_by(|a, b| b.cmp(a));
+        match self {
+            Shape::Balanced => BALANCED.contains(&pattern),
+            Shape::SemiBalanced => BALANCED.contains(&pattern) || SEMI_BALANCED.contains(&pattern),
+            Shape::Unbalanced => !BALANCED.contains(&pattern),
+            Shape::Pattern(wanted) => pattern == *wanted,
+            Shape::Exact(wanted) => lengths == *wanted,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Predicate {
+    Hcp(Range),
+    SuitLength(Suit, Range),
+    Losers(Range),
+    Shape(Shape),
+}
+
+impl Predicate {
+    pub fn matches(&self, hand: &Hand) -> bool {
+        match self {
+            Predicate::Hcp(range) => range.contains(hand.hcp()),
+            Predicate::SuitLength(suit, range) => range.contains(hand.suit_length(*suit) as u8),
+            Predicate::Losers(range) => range.contains(losing_trick_count(hand)),
+            Predicate::Shape(shape) => shape.matches(hand),
+        }
+    }
+}
+
+/// Losing trick count: in each suit, the top one, two or three cards (depending on
+/// length) that are not the ace, king or queen respectively.
+pub fn losing_trick_count(hand: &Hand) -> u8 {
+    let honours = [Rank::ACE, Rank::KING, Rank::QUEEN];
+    Suit::ALL
+        .into_iter()
+        .map(|suit| {
+            let counted = hand.suit_length(suit).min(3);
+            let held = honours[..counted]
+                .iter()
+                .filter(|rank| hand.suit_mask(suit) & (1 << rank.value()) != 0)
+                .count();
+            (counted - held) as u8
+        })
+        .sum()
+}
+
+/// Everything a deal must satisfy, grouped by seat.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DealConstraints {
+    seats: Vec<(Seat, Vec<Predicate>)>,
+}
+
+impl DealConstraints {
+    pub fn matches(&self, deal: &Deal) -> bool {
+        self.seats.iter().all(|(seat, predicates)| {
+            let hand = deal.hand(*seat);
+            predicates.iter().all(|predicate| predicate.matches(hand))
+        })
+    }
+}
+
+impl std::str::FromStr for DealConstraints {
+    type Err = ConstraintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut seats: Vec<(Seat, Vec<Predicate>)> = Vec::new();
+        for clause in s.split([',', ';']).map(str::trim).filter(|c| !c.is_empty()) {
+            let mut tokens = clause
+                .split_whitespace()
+                .filter(|token| !token.eq_ignore_ascii_case("and"))
+                .peekable();
+            if let Some(seat) = tokens.peek().and_then(|token| token.parse::<Seat>().ok()) {
+                tokens.next();
+                seats.push((seat, Vec::new()));
+            }
+            let (_, predicates) = seats
+                .last_mut()
+                .ok_or_else(|| ConstraintError::MissingSeat(clause.to_string()))?;
+            let unexpected = |token: &str| ConstraintError::UnexpectedToken {
+                clause: clause.to_string(),
+                token: token.to_string(),
+            };
+            while let Some(token) = tokens.next() {
+                let lower = token.to_ascii_lowercase();
+                let shape = match lower.as_str() {
+                    "balanced" | "bal" => Some(Shape::Balanced),
+                    "semi-balanced" | "semibalanced" => Some(Shape::SemiBalanced),
+                    "unbalanced" => Some(Shape::Unbalanced),
+                    _ => None,
+                };
+                if let Some(shape) = shape {
+                    predicates.push(Predicate::Shape(shape));
+                    continue;
+                }
+                if let Some(shape) = parse_shape(&lower)? {
+                    predicates.push(Predicate::Shape(shape));
+                    continue;
+                }
+                // A count, with what is counted either attached ("4+s") or next ("4+ spades").
+                let split = lower
+                    .find(|c: char| !(c.is_ascii_digit() || c == '+' || c == '-'))
+                    .unwrap_or(lower.len());
+                let (count, unit) = lower.split_at(split);
+                if count.is_empty() {
+                    return Err(unexpected(token));
+                }
+                let range = parse_range(count)?;
+                let unit = match unit {
+                    "" => match tokens.peek().and_then(|next| parse_unit(next)) {
+                        Some(unit) => {
+                            tokens.next();
+                            unit
+                        }
+                        None => Unit::Hcp,
+                    },
+                    unit => parse_unit(unit).ok_or_else(|| unexpected(token))?,
+                };
+                predicates.push(match unit {
+                    Unit::Hcp => Predicate::Hcp(range),
+                    Unit::Suit(suit) => Predicate::SuitLength(suit, range),
+                    Unit::Losers => Predicate::Losers(range),
+                });
+            }
+        }
+        if seats.iter().all(|(_, predicates)| predicates.is_empty()) {
+            return Err(ConstraintError::Empty);
+        }
+        Ok(DealConstraints { seats })
+    }
+}
+
+enum Unit {
+    Hcp,
+    Suit(Suit),
+    Losers,
+}
+
+fn parse_unit(s: &str) -> Option<Unit> {
+    match s.to_ascii_lowercase().as_str() {
+        "hcp" | "points" | "pts" => Some(Unit::Hcp),
+        "ltc" | "losers" => Some(Unit::Losers),
+        "s" | "spade" | "spades" => Some(Unit::Suit(Suit::Spades)),
+        "h" | "heart" | "hearts" => Some(Unit::Suit(Suit::Hearts)),
+        "d" | "diamond" | "diamonds" => Some(Unit::Suit(Suit::Diamonds)),
+        "c" | "club" | "clubs" => Some(Unit::Suit(Suit::Clubs)),
+        _ => None,
+    }
+}
+
+fn parse_range(s: &str) -> Result<Range, ConstraintError> {
+    let invalid = || ConstraintError::InvalidRange(s.to_string());
+    let number = |n: &str| n.parse::<u8>().map_err(|_| invalid());
+    let range = if let Some(min) = s.strip_suffix('+') {
+        Range {
+            min: number(min)?,
+            max: u8::MAX,
+        }
+    } else if let Some(max) = s.strip_suffix('-') {
+        Range {
+            min: 0,
+            max: number(max)?,
+        }
+    } else if let Some((min, max)) = s.split_once('-') {
+        Range {
+            min: number(min)?,
+            max: number(max)?,
+        }
+    } else {
+        let exact = number(s)?;
+        Range {
+            min: exact,
+            max: exact,
+        }
+    };
+    if range.min > range.max {
+        return Err(invalid());
+    }
+    Ok(range)
+}
+
+/// Shapes are four suit lengths adding up to 13, so `4-4-4-1` is never read as a range.
+fn parse_shape(s: &str) -> Result<Option<Shape>, ConstraintError> {
+    let invalid = || ConstraintError::InvalidShape(s.to_string());
+    let exact = s.contains('=');
+    let parts: Vec<String> = if exact {
+        s.split('=').map(str::to_string).collect()
+    } else if s.contains('-') {
+        s.split('-').map(str::to_string).collect()
+    } else if s.len() == 4 && s.chars().all(|c| c.is_ascii_digit()) {
+        s.chars().map(|c| c.to_string()).collect()
+    } else {
+        return Ok(None);
+    };
+    if parts.len() != 4 {
+        return if exact { Err(invalid()) } else { Ok(None) };
+    }
+    let mut shape = [0u8; 4];
+    for (length, part) in shape.iter_mut().zip(&parts) {
+        *length = part.parse().map_err(|_| invalid())?;
+    }
+    if shape.iter().map(|length| *length as u32).sum::<u32>() != 13 {
+        return Err(invalid());
+    }
+    if exact {
+        Ok(Some(Shape::Exact(shape)))
+    } else {
+        shape.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(Some(Shape::Pattern(shape)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEAL: &str = "N:AKQ.KQ2.A32.5432 JT98765.AJ.KQ.AK 432.T9876543.J.Q -";
+
+    fn at_least(min: u8) -> Range {
+        Range { min, max: u8::MAX }
+    }
+
+    #[test]
+    fn parses_clauses_by_seat() {
+        let constraints: DealConstraints = "North 15-17 balanced, South 4+ spades and 8+ HCP"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            constraints,
+            DealConstraints {
+                seats: vec![
+                    (
+                        Seat::North,
+                        vec![
+                            Predicate::Hcp(Range { min: 15, max: 17 }),
+                            Predicate::Shape(Shape::Balanced),
+                        ]
+                    ),
+                    (
+                        Seat::South,
+                        vec![
+                            Predicate::SuitLength(Suit::Spades, at_least(4)),
+                            Predicate::Hcp(at_least(8)),
+                        ]
+                    ),
+                ]
+            }
+        );
+        let constraints: DealConstraints = "E 4-4-4-1; 12-; W 5=4=3=1 4+s 7 ltc".parse().unwrap();
+        assert_eq!(
+            constraints.seats[0].1,
+            vec![
+                Predicate::Shape(Shape::Pattern([4, 4, 4, 1])),
+                Predicate::Hcp(Range { min: 0, max: 12 }),
+            ]
+        );
+        assert_eq!(
+            constraints.seats[1].1,
+            vec![
+                Predicate::Shape(Shape::Exact([5, 4, 3, 1])),
+                Predicate::SuitLength(Suit::Spades, at_least(4)),
+                Predicate::Losers(Range { min: 7, max: 7 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let error = |s: &str| s.parse::<DealConstraints>().unwrap_err();
+        assert!(matches!(error(""), ConstraintError::Empty));
+        assert!(matches!(error("North"), ConstraintError::Empty));
+        assert!(matches!(
+            error("4+ spades"),
+            ConstraintError::MissingSeat(_)
+        ));
+        assert!(matches!(
+            error("North 15-17 shiny"),
+            ConstraintError::UnexpectedToken { token, .. } if token == "shiny"
+        ));
+        assert!(matches!(
+            error("North 4+x"),
+            ConstraintError::UnexpectedToken { token, .. } if token == "4+x"
+        ));
+        assert!(matches!(
+            error("North 17-15"),
+            ConstraintError::InvalidRange(_)
+        ));
+        assert!(matches!(
+            error("North 99999+"),
+            ConstraintError::InvalidRange(_)
+        ));
+        assert!(matches!(
+            error("North 5=4=3=2"),
+            ConstraintError::InvalidShape(_)
+        ));
+        assert!(matches!(
+            error("North 5=4=4"),
+            ConstraintError::InvalidShape(_)
+        ));
+        assert!(matches!(
+            error("North 4-4-4-2"),
+            ConstraintError::InvalidShape(_)
+        ));
+    }
+
+    #[test]
+    fn matches_hands() {
+        let deal: Deal = DEAL.parse().unwrap();
+        let north = deal.hand(Seat::North);
+        assert_eq!(losing_trick_count(north), 6);
+        assert!(Shape::Balanced.matches(north));
+        assert!(Shape::Pattern([4, 3, 3, 3]).matches(north));
+        assert!(Shape::Exact([3, 3, 3, 4]).matches(north));
+        assert!(!Shape::Exact([4, 3, 3, 3]).matches(north));
+        assert!(Shape::Unbalanced.matches(deal.hand(Seat::East)));
+        assert!(!Shape::SemiBalanced.matches(deal.hand(Seat::South)));
+
+        let matching: DealConstraints = "North 16-18 balanced, East 7 spades".parse().unwrap();
+        assert!(matching.matches(&deal));
+        let failing: DealConstraints = "North 19+, South 4+ spades".parse().unwrap();
+        assert!(!failing.matches(&deal));
+    }
+}
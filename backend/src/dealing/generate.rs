@@ -0,0 +1,178 @@
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use super::constraints::DealConstraints;
+use crate::models::{
+    board::NewBoardDTO,
+    card::{Card, Rank, Suit},
+    contract::Seat,
+    deal::{Deal, Hand},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DealingError {
+    #[error("Only {found} of {wanted} deals matching the constraints were found in {seconds}s")]
+    TimedOut {
+        found: usize,
+        wanted: usize,
+        seconds: u64,
+    },
+}
+
+/// Deals uniformly random boards from a seed. The same seed always gives the same
+/// sequence of deals, so a session's hand records can be regenerated from its seed.
+///
+/// Everything between the seed and the deal is fixed here rather than left to `rand`:
+/// ChaCha20 keyed with the seed's little-endian bytes, and a Fisher–Yates shuffle of
+/// the pack in suit and rank order drawing on the raw output. A stored seed therefore
+/// keeps dealing the same hands across dependency upgrades and platforms.
+pub struct DealGenerator {
+    rng: ChaCha20Rng,
+}
+
+impl DealGenerator {
+    pub fn new(seed: i64) -> Self {
+        let mut key = [0u8; 32];
+        key[..8].copy_from_slice(&seed.to_le_bytes());
+        DealGenerator {
+            rng: ChaCha20Rng::from_seed(key),
+        }
+    }
+
+    /// Shuffles the pack and gives 13 cards to each seat, North first.
+    pub fn next_deal(&mut self) -> Deal {
+        let mut pack: Vec<Card> = Suit::ALL
+            .into_iter()
+            .flat_map(|suit| Rank::all().map(move |rank| Card::new(suit, rank)))
+            .collect();
+        for i in (1..pack.len()).rev() {
+            let j = self.below(i as u32 + 1) as usize;
+            pack.swap(i, j);
+        }
+        let mut hands = [Hand::default(); 4];
+        for (seat, cards) in Seat::ALL.into_iter().zip(pack.chunks(13)) {
+            for card in cards {
+                hands[seat.index()].insert(*card);
+            }
+        }
+        Deal::new(hands).expect("a shuffled pack makes a valid deal")
+    }
+
+    /// A uniform number in `0..bound`, rejecting the top of the range that would bias it.
+    fn below(&mut self, bound: u32) -> u32 {
+        let zone = u32::MAX - u32::MAX % bound;
+        loop {
+            let value = self.rng.next_u32();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+impl Iterator for DealGenerator {
+    type Item = Deal;
+
+    fn next(&mut self) -> Option<Deal> {
+        Some(self.next_deal())
+    }
+}
+
+/// A fresh seed for sessions that do not choose their own.
+pub fn new_seed() -> i64 {
+    StdRng::from_os_rng().random()
+}
+
+/// Hand records for boards `1..=board_count`, dealt in board order from `seed`.
+pub fn hand_records(seed: i64, board_count: u32) -> Vec<NewBoardDTO> {
+    hand_records_from(DealGenerator::new(seed).take(board_count as usize))
+}
+
+/// Hand records numbered from board 1 in the order the deals are given.
+pub fn hand_records_from(deals: impl IntoIterator<Item = Deal>) -> Vec<NewBoardDTO> {
+    (1..)
+        .zip(deals)
+        .map(|(board_number, deal)| NewBoardDTO {
+            board_number,
+            dealer: None,
+            vulnerability: None,
+            deal: Some(deal),
+            results: Vec::new(),
+        })
+        .collect()
+}
+
+/// Deals from `seed` until `count` deals match `constraints`, giving up after `timeout`.
+/// This is rejection sampling and CPU bound, so async callers should run it on a
+/// blocking thread.
+pub fn deal_matching(
+    constraints: &DealConstraints,
+    seed: i64,
+    count: usize,
+    timeout: Duration,
+) -> Result<Vec<Deal>, DealingError> {
+    let started = Instant::now();
+    let mut deals = Vec::with_capacity(count);
+    let mut generator = DealGenerator::new(seed);
+    let mut tried: u64 = 0;
+    while deals.len() < count {
+        let deal = generator.next_deal();
+        if constraints.matches(&deal) {
+            deals.push(deal);
+        }
+        tried += 1;
+        if tried.is_multiple_of(1024) && started.elapsed() > timeout {
+            return Err(DealingError::TimedOut {
+                found: deals.len(),
+                wanted: count,
+                seconds: timeout.as_secs(),
+            });
+        }
+    }
+    Ok(deals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deals stored seeds must keep producing. A change here means every session dealt
+    /// from a seed would show different hands.
+    #[test]
+    fn seeds_deal_fixed_hands() {
+        let deals: Vec<String> = DealGenerator::new(1)
+            .take(2)
+            .map(|deal| deal.to_string())
+            .collect();
+        assert_eq!(
+            deals,
+            vec![
+                "N:86.53.AQJ5.K9842 T97542.9876.4.Q6 .KT2.KT9862.JT75 AKQJ3.AQJ4.73.A3",
+                "N:QJ842.Q32.J92.92 T5.KJT7.A865.K54 AK3.864.Q43.AJT6 976.A95.KT7.Q873",
+            ]
+        );
+        assert_eq!(
+            DealGenerator::new(-20250101).next_deal().to_string(),
+            "N:K54.Q987.Q3.QT93 QJT98.AJ.A65.J82 2.532.KJ842.AK75 A763.KT64.T97.64"
+        );
+    }
+
+    #[test]
+    fn hand_records_are_numbered_from_one() {
+        let boards = hand_records(7, 3);
+        let numbers: Vec<u32> = boards.iter().map(|board| board.board_number).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        let deals: Vec<Option<Deal>> = boards.iter().map(|board| board.deal).collect();
+        let again: Vec<Option<Deal>> = hand_records(7, 3).iter().map(|board| board.deal).collect();
+        assert_eq!(deals, again);
+        assert_ne!(
+            deals,
+            hand_records(8, 3)
+                .iter()
+                .map(|board| board.deal)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,2 @@
+pub mod constraints;
+pub mod generate;
\ No newline at end of file
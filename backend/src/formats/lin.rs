@@ -0,0 +1,386 @@
+//! Reader for BBO hand files in LIN format: `key|value|` records such as `md|` (deal),
+//! `mb|` (call), `an|` (alert explanation), `pc|` (card played) and `mc|` (claim).
+use super::{group_games, GameRecord};
+use crate::models::{
+    auction::{Auction, AuctionCall, Call},
+    board::{NewBoardDTO, NewBoardResultDTO, Room},
+    card::{Card, Rank, Suit},
+    contract::{Seat, Strain, Vulnerability},
+    deal::{trick_winner, Deal, Hand},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum LinError {
+    #[error("Record {index} ({key}|{value}|): {message}")]
+    InvalidRecord {
+        index: usize,
+        key: String,
+        value: String,
+        message: String,
+    },
+    #[error("File ends in the middle of a record")]
+    UnterminatedRecord,
+    #[error("Board starting at record {index}: {message}")]
+    IncompleteBoard { index: usize, message: String },
+    #[error("Board {0} appears with different deals, dealers or vulnerabilities")]
+    InconsistentBoard(u32),
+}
+
+#[derive(Debug, Default)]
+struct LinBoard {
+    start: usize,
+    board_number: Option<u32>,
+    room: Option<Room>,
+    dealer: Option<Seat>,
+    deal: Option<Deal>,
+    vulnerability: Option<Vulnerability>,
+    auction: Auction,
+    play: Vec<Card>,
+    claim: Option<u8>,
+}
+
+impl LinBoard {
+    fn is_empty(&self) -> bool {
+        self.deal.is_none() && self.auction.calls.is_empty() && self.play.is_empty()
+    }
+}
+
+/// Parses a LIN file and groups its boards, one result per table played.
+pub fn boards_from_lin(input: &str) -> Result<Vec<NewBoardDTO>, LinError> {
+    group_games(parse_lin(input)?).map_err(LinError::InconsistentBoard)
+}
+
+/// Parses every board of a LIN file. Vugraph files hold several boards, each started by
+/// a `qx|` record; single-hand files just have one `md|`.
+pub fn parse_lin(input: &str) -> Result<Vec<GameRecord>, LinError> {
+    let flat: String = input.chars().filter(|c| *c != '\n' && *c != '\r').collect();
+    let mut fields: Vec<&str> = flat.split('|').collect();
+    if fields.last().is_some_and(|last| last.trim().is_empty()) {
+        fields.pop();
+    }
+    if !fields.len().is_multiple_of(2) {
+        return Err(LinError::UnterminatedRecord);
+    }
+
+    let mut boards = Vec::new();
+    let mut current = LinBoard::default();
+    for (index, record) in fields.chunks(2).enumerate() {
+        let key = record[0].trim().to_ascii_lowercase();
+        let value = record[1].trim();
+        let invalid = |message: &str| LinError::InvalidRecord {
+            index,
+            key: key.clone(),
+            value: value.to_string(),
+            message: message.to_string(),
+        };
+        match key.as_str() {
+            "qx" => {
+                if !current.is_empty() {
+                    boards.push(std::mem::take(&mut current));
+                }
+                current.start = index;
+                let (room, number) = value
+                    .split_at_checked(1)
+                    .ok_or_else(|| invalid("missing room"))?;
+                current.room = match room {
+                    "o" | "O" => Some(Room::Open),
+                    "c" | "C" => Some(Room::Closed),
+                    _ => return Err(invalid("room must be o or c")),
+                };
+                current.board_number =
+                    Some(parse_board_number(number).ok_or_else(|| invalid("not a board number"))?);
+            }
+            "md" => {
+                if current.deal.is_some() {
+                    let next = LinBoard {
+                        start: index,
+                        ..LinBoard::default()
+                    };
+                    boards.push(std::mem::replace(&mut current, next));
+                }
+                let (dealer, deal) = parse_deal(value).map_err(|message| invalid(&message))?;
+                current.dealer = dealer;
+                current.deal = Some(deal);
+            }
+            "ah" => {
+                let number = value
+                    .trim_start_matches(|c: char| !c.is_ascii_digit())
+                    .trim();
+                current.board_number =
+                    Some(parse_board_number(number).ok_or_else(|| invalid("not a board number"))?);
+            }
+            "sv" => {
+                current.vulnerability = Some(match value.to_ascii_lowercase().as_str() {
+                    "o" | "0" | "-" | "" => Vulnerability::None,
+                    "n" => Vulnerability::Ns,
+                    "e" => Vulnerability::Ew,
+                    "b" => Vulnerability::Both,
+                    _ => return Err(invalid("vulnerability must be o, n, e or b")),
+                });
+            }
+            "mb" => {
+                let call = parse_call(value).ok_or_else(|| invalid("not a call"))?;
+                current.auction.calls.push(call);
+            }
+            "an" => {
+                let call = current
+                    .auction
+                    .calls
+                    .last_mut()
+                    .ok_or_else(|| invalid("annotation before any call"))?;
+                call.alerted = true;
+                call.explanation = Some(value.to_string());
+            }
+            "pc" => {
+                let card: Card = value.parse().map_err(|_| invalid("not a card"))?;
+                current.play.push(card);
+            }
+            "mc" => {
+                let tricks = value
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|tricks| *tricks <= 13)
+                    .ok_or_else(|| invalid("claim must be a number of tricks"))?;
+                current.claim = Some(tricks);
+            }
+            // Player names, page breaks, commentary and the like.
+            _ => {}
+        }
+    }
+    if !current.is_empty() {
+        boards.push(current);
+    }
+    boards.into_iter().map(to_game).collect()
+}
+
+fn parse_board_number(s: &str) -> Option<u32> {
+    s.trim().parse::<u32>().ok().filter(|n| *n > 0)
+}
+
+/// `md|` holds the dealer (1 = South, 2 = West, 3 = North, 4 = East) followed by the
+/// hands of South, West, North and East, e.g. `3SAK2HQJ4...,S...,S...,`. The last hand
+/// is often left out.
+fn parse_deal(value: &str) -> Result<(Option<Seat>, Deal), String> {
+    let mut chars = value.chars();
+    let dealer = match chars.next() {
+        Some('1') => Some(Seat::South),
+        Some('2') => Some(Seat::West),
+        Some('3') => Some(Seat::North),
+        Some('4') => Some(Seat::East),
+        Some('0') => None,
+        _ => return Err("deal must start with the dealer".to_string()),
+    };
+    let hands: Vec<&str> = chars.as_str().split(',').collect();
+    let order = [Seat::South, Seat::West, Seat::North, Seat::East];
+    let mut parsed = [Hand::default(); 4];
+    let mut missing = Vec::new();
+    for (i, seat) in order.into_iter().enumerate() {
+        match hands
+            .get(i)
+            .map(|hand| hand.trim())
+            .filter(|hand| !hand.is_empty())
+        {
+            Some(hand) => parsed[seat.index()] = parse_hand(hand)?,
+            None => missing.push(seat),
+        }
+    }
+    match missing.as_slice() {
+        [] => {}
+        [seat] => {
+            let mut rest = Hand::default();
+            for suit in Suit::ALL {
+                for rank in Rank::all() {
+                    let card = Card::new(suit, rank);
+                    if parsed.iter().all(|hand| !hand.contains(card)) {
+                        rest.insert(card);
+                    }
+                }
+            }
+            parsed[seat.index()] = rest;
+        }
+        _ => return Err("deal is missing more than one hand".to_string()),
+    }
+    let deal = Deal::new(parsed).map_err(|e| e.to_string())?;
+    Ok((dealer, deal))
+}
+
+fn parse_hand(s: &str) -> Result<Hand, String> {
+    let mut hand = Hand::default();
+    let mut suit: Option<Suit> = None;
+    for c in s.chars() {
+        if let Ok(next) = c.to_string().parse::<Suit>() {
+            suit = Some(next);
+            continue;
+        }
+        let suit = suit.ok_or_else(|| format!("hand {} does not start with a suit", s))?;
+        let rank: Rank = c
+            .to_string()
+            .parse()
+            .map_err(|_| format!("invalid rank {} in hand {}", c, s))?;
+        if !hand.insert(Card::new(suit, rank)) {
+            return Err(format!("hand {} repeats a card", s));
+        }
+    }
+    Ok(hand)
+}
+
+/// LIN calls are `p`, `d`, `r` or a bid such as `1N`, optionally followed by `!` when
+/// alerted.
+fn parse_call(value: &str) -> Option<AuctionCall> {
+    let alerted = value.ends_with('!');
+    let call = match value.trim_end_matches('!').to_ascii_lowercase().as_str() {
+        "d" => Call::Double,
+        "r" => Call::Redouble,
+        call => call.parse().ok()?,
+    };
+    Some(AuctionCall {
+        alerted,
+        ..AuctionCall::new(call)
+    })
+}
+
+fn to_game(board: LinBoard) -> Result<GameRecord, LinError> {
+    let incomplete = |message: &str| LinError::IncompleteBoard {
+        index: board.start,
+        message: message.to_string(),
+    };
+    let board_number = board
+        .board_number
+        .ok_or_else(|| incomplete("no board number (qx| or ah| record)"))?;
+    let result = if board.auction.calls.is_empty() && board.play.is_empty() {
+        None
+    } else {
+        let dealer = board
+            .dealer
+            .ok_or_else(|| incomplete("auction without a dealer"))?;
+        match board
+            .auction
+            .final_contract(dealer)
+            .map_err(|e| incomplete(&e.to_string()))?
+        {
+            None => Some(NewBoardResultDTO {
+                room: board.room,
+                ns_pair: None,
+                ew_pair: None,
+                contract: None,
+                declarer: None,
+                opening_lead: None,
+                tricks: None,
+                auction: Some(board.auction.clone()),
+                play: None,
+                claim: None,
+                adjustment: None,
+            }),
+            Some((contract, declarer)) => {
+                let tricks = match board.claim {
+                    Some(claim) => claim,
+                    None if board.play.len() == 52 => {
+                        declarer_tricks(&board.play, declarer, contract.strain)
+                    }
+                    None => return Err(incomplete("play stops before the end without a claim")),
+                };
+                Some(NewBoardResultDTO {
+                    room: board.room,
+                    ns_pair: None,
+                    ew_pair: None,
+                    contract: Some(contract),
+                    declarer: Some(declarer),
+                    opening_lead: board.play.first().copied(),
+                    tricks: Some(tricks),
+                    auction: Some(board.auction.clone()),
+                    play: (!board.play.is_empty()).then(|| board.play.clone()),
+                    claim: board.claim,
+                    adjustment: None,
+                })
+            }
+        }
+    };
+    Ok(GameRecord {
+        board_number,
+        dealer: board.dealer,
+        vulnerability: board.vulnerability,
+        deal: board.deal,
+        result,
+    })
+}
+
+fn declarer_tricks(play: &[Card], declarer: Seat, strain: Strain) -> u8 {
+    let mut leader = declarer.next();
+    let mut tricks = 0;
+    for trick in play.chunks(4) {
+        leader = trick_winner(leader, trick, strain.trump());
+        if leader == declarer || leader == declarer.partner() {
+            tricks += 1;
+        }
+    }
+    tricks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HANDS: &str = "md|3S765432H8765432,D8765432C765432,SAKQJHAKQDAKQCAKQ,|";
+
+    #[test]
+    fn reads_a_vugraph_file() {
+        let input = format!(
+            "qx|o1|{HANDS}sv|n|ah|Board 1|\
+             mb|1N|mb|p|mb|2C!|an|Stayman|mb|p|mb|2D|mb|p|mb|3N|mb|p|mb|p|mb|p|\n\
+             pc|CJ|pc|S2|pc|C7|pc|CA|mc|13|\n\
+             qx|c1|{HANDS}sv|n|mb|p|mb|p|mb|p|mb|p|"
+        );
+        let boards = boards_from_lin(&input).unwrap();
+        assert_eq!(boards.len(), 1);
+        let board = &boards[0];
+        assert_eq!(board.board_number, 1);
+        assert_eq!(board.dealer, Some(Seat::North));
+        assert_eq!(board.vulnerability, Some(Vulnerability::Ns));
+        assert_eq!(
+            board.deal,
+            "N:AKQJ.AKQ.AKQ.AKQ T98.JT9.JT9.JT98 765432.8765432.. ..8765432.765432"
+                .parse()
+                .ok()
+        );
+
+        let open = &board.results[0];
+        assert_eq!(open.room, Some(Room::Open));
+        assert_eq!(open.contract, "3NT".parse().ok());
+        assert_eq!(open.declarer, Some(Seat::North));
+        assert_eq!(open.tricks, Some(13));
+        assert_eq!(open.claim, Some(13));
+        assert_eq!(open.opening_lead, "CJ".parse().ok());
+        assert_eq!(open.play.as_ref().map(Vec::len), Some(4));
+        let stayman = &open.auction.as_ref().unwrap().calls[2];
+        assert!(stayman.alerted);
+        assert_eq!(stayman.explanation.as_deref(), Some("Stayman"));
+
+        let closed = &board.results[1];
+        assert_eq!(closed.room, Some(Room::Closed));
+        assert!(closed.contract.is_none());
+    }
+
+    #[test]
+    fn reports_bad_files() {
+        assert!(matches!(
+            boards_from_lin("qx|o1|md"),
+            Err(LinError::UnterminatedRecord)
+        ));
+        assert!(matches!(
+            boards_from_lin(&format!("qx|o1|{HANDS}mb|9Z|")),
+            Err(LinError::InvalidRecord { index: 2, .. })
+        ));
+        assert!(matches!(
+            boards_from_lin(&format!("qx|x1|{HANDS}")),
+            Err(LinError::InvalidRecord { index: 0, .. })
+        ));
+        assert!(matches!(
+            boards_from_lin(&format!("qx|o1|{HANDS}mb|1N|mb|p|mb|p|mb|p|pc|CJ|pc|S2|")),
+            Err(LinError::IncompleteBoard { .. })
+        ));
+        assert!(matches!(
+            boards_from_lin(&format!("{HANDS}mb|p|")),
+            Err(LinError::IncompleteBoard { index: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+pub mod lin;
+pub mod pbn;
+
+use crate::models::{
+    board::{NewBoardDTO, NewBoardResultDTO},
+    contract::{Seat, Vulnerability},
+    deal::Deal,
+};
+
+/// One game read from a hand file: a board and, if it was played, the result at one table.
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    pub board_number: u32,
+    pub dealer: Option<Seat>,
+    pub vulnerability: Option<Vulnerability>,
+    pub deal: Option<Deal>,
+    pub result: Option<NewBoardResultDTO>,
+}
+
+/// Groups games into boards, one result per played game. Fails with the board number
+/// when two games of the same board disagree on its deal, dealer or vulnerability.
+pub fn group_games(games: Vec<GameRecord>) -> Result<Vec<NewBoardDTO>, u32> {
+    let mut boards: Vec<NewBoardDTO> = Vec::new();
+    for game in games {
+        let existing = boards
+            .iter_mut()
+            .find(|board| board.board_number == game.board_number);
+        let board = match existing {
+            Some(board) => {
+                let consistent = agrees(&board.deal, &game.deal)
+                    && agrees(&board.dealer, &game.dealer)
+                    && agrees(&board.vulnerability, &game.vulnerability);
+                if !consistent {
+                    return Err(game.board_number);
+                }
+                board.deal = board.deal.or(game.deal);
+                board.dealer = board.dealer.or(game.dealer);
+                board.vulnerability = board.vulnerability.or(game.vulnerability);
+                board
+            }
+            None => {
+                boards.push(NewBoardDTO {
+                    board_number: game.board_number,
+                    dealer: game.dealer,
+                    vulnerability: game.vulnerability,
+                    deal: game.deal,
+                    results: Vec::new(),
+                });
+                boards.last_mut().unwrap()
+            }
+        };
+        if let Some(result) = game.result {
+            board.results.push(result);
+        }
+    }
+    boards.sort_by_key(|board| board.board_number);
+    Ok(boards)
+}
+
+fn agrees<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+    }
+}
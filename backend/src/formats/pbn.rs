@@ -0,0 +1,912 @@
+//! Reader and writer for PBN (Portable Bridge Notation) files, as used by club scoring
+//! software.
+use std::collections::HashMap;
+
+use super::{group_games, GameRecord};
+use crate::{
+    models::{
+        auction::{Auction, AuctionCall, Call},
+        board::{BoardMongoDTO, BoardResult, NewBoardDTO, NewBoardResultDTO, Room},
+        card::Card,
+        contract::{Contract, Seat, Strain, Vulnerability},
+        deal::{trick_winner, Deal},
+        session::{ScoringType, SessionMongoDTO},
+    },
+    tournament::teams::{EventRound, TeamEvent, TeamMatchup},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PbnError {
+    #[error("Line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("Line {line}: invalid [{tag}] value \"{value}\": {message}")]
+    InvalidTag {
+        line: usize,
+        tag: String,
+        value: String,
+        message: String,
+    },
+    #[error("Game starting on line {line} is missing the [{tag}] tag")]
+    MissingTag { line: usize, tag: &'static str },
+    #[error("Board {0} appears with different deals, dealers or vulnerabilities")]
+    InconsistentBoard(u32),
+}
+
+#[derive(Debug, Clone)]
+struct RawTag {
+    value: String,
+    line: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct RawGame {
+    start_line: usize,
+    tags: HashMap<String, RawTag>,
+    /// Tokens following the `[Auction]` and `[Play]` tags.
+    sections: HashMap<String, Vec<(String, usize)>>,
+    /// `[Note]` tags following the `[Auction]` and `[Play]` sections, which the `=n=`
+    /// tokens of the section refer to.
+    notes: HashMap<String, Vec<RawTag>>,
+}
+
+/// Parses a PBN file and groups its games into boards, one result per played game.
+pub fn boards_from_pbn(input: &str) -> Result<Vec<NewBoardDTO>, PbnError> {
+    group_games(parse_pbn(input)?).map_err(PbnError::InconsistentBoard)
+}
+
+/// Parses every game of a PBN file.
+pub fn parse_pbn(input: &str) -> Result<Vec<GameRecord>, PbnError> {
+    let raw_games = read_games(input)?;
+    let mut games = Vec::with_capacity(raw_games.len());
+    let mut previous: HashMap<String, RawTag> = HashMap::new();
+    for mut raw in raw_games {
+        // A value of "#" repeats the value the tag had in the previous game.
+        for (name, tag) in raw.tags.iter_mut() {
+            if tag.value == "#" {
+                if let Some(previous) = previous.get(name) {
+                    tag.value = previous.value.clone();
+                }
+            }
+        }
+        games.push(parse_game(&raw)?);
+        previous = raw.tags;
+    }
+    Ok(games)
+}
+
+fn read_games(input: &str) -> Result<Vec<RawGame>, PbnError> {
+    let mut games = Vec::new();
+    let mut current = RawGame::default();
+    let mut section: Option<String> = None;
+    let mut in_comment = false;
+
+    for (index, raw_line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        if raw_line.starts_with('%') {
+            continue;
+        }
+        let line = strip_comments(raw_line, &mut in_comment);
+        let line = line.trim();
+        if line.is_empty() {
+            // Only a truly blank line ends a game, not one holding just a comment.
+            let blank = raw_line.trim().is_empty() && !in_comment;
+            if blank && !current.tags.is_empty() {
+                games.push(std::mem::take(&mut current));
+                section = None;
+            }
+            continue;
+        }
+        if line.starts_with('[') {
+            let (name, value) = parse_tag_line(line, line_number)?;
+            if current.tags.is_empty() {
+                current.start_line = line_number;
+            }
+            if name == "Note" {
+                if let Some(section) = &section {
+                    current
+                        .notes
+                        .entry(section.clone())
+                        .or_default()
+                        .push(RawTag {
+                            value,
+                            line: line_number,
+                        });
+                    continue;
+                }
+            }
+            section = match name.as_str() {
+                "Auction" | "Play" => Some(name.clone()),
+                _ => None,
+            };
+            current.tags.insert(
+                name,
+                RawTag {
+                    value,
+                    line: line_number,
+                },
+            );
+        } else if let Some(name) = &section {
+            let tokens = current.sections.entry(name.clone()).or_default();
+            tokens.extend(
+                line.split_whitespace()
+                    .map(|token| (token.to_string(), line_number)),
+            );
+        }
+        // Data lines of other sections (score tables and the like) are not needed.
+    }
+    if !current.tags.is_empty() {
+        games.push(current);
+    }
+    Ok(games)
+}
+
+/// Removes `;` line comments and `{ }` comments, which may span several lines.
+fn strip_comments(line: &str, in_comment: &mut bool) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_quotes = false;
+    for c in line.chars() {
+        if *in_comment {
+            if c == '}' {
+                *in_comment = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                out.push(c);
+            }
+            '{' if !in_quotes => *in_comment = true,
+            ';' if !in_quotes => break,
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn parse_tag_line(line: &str, line_number: usize) -> Result<(String, String), PbnError> {
+    let syntax = |message: &str| PbnError::Syntax {
+        line: line_number,
+        message: message.to_string(),
+    };
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or_else(|| syntax("tag is not enclosed in [ ]"))?;
+    let (name, value) = inner
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| syntax("tag has no value"))?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .ok_or_else(|| syntax("tag value is not quoted"))?;
+    Ok((name.to_string(), value.replace("\\\"", "\"")))
+}
+
+fn parse_game(raw: &RawGame) -> Result<GameRecord, PbnError> {
+    let board_tag = raw.tags.get("Board").ok_or(PbnError::MissingTag {
+        line: raw.start_line,
+        tag: "Board",
+    })?;
+    let board_number = parse_tag(raw, "Board", |value| {
+        value
+            .parse::<u32>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| "board number must be a positive integer".to_string())
+    })?
+    .ok_or(PbnError::MissingTag {
+        line: board_tag.line,
+        tag: "Board",
+    })?;
+    let dealer = parse_tag(raw, "Dealer", |value| {
+        value.parse::<Seat>().map_err(|e| e.to_string())
+    })?;
+    let vulnerability = parse_tag(raw, "Vulnerable", |value| {
+        value.parse::<Vulnerability>().map_err(|e| e.to_string())
+    })?;
+    let deal = parse_tag(raw, "Deal", |value| {
+        value.parse::<Deal>().map_err(|e| e.to_string())
+    })?;
+
+    Ok(GameRecord {
+        board_number,
+        dealer,
+        vulnerability,
+        deal,
+        result: parse_result(raw, dealer)?,
+    })
+}
+
+/// Parses a tag's value, treating a missing tag or an empty / `?` value as absent. `-`
+/// is absent too, except in [Vulnerable], where it means no one is vulnerable.
+fn parse_tag<T>(
+    raw: &RawGame,
+    name: &str,
+    parse: impl FnOnce(&str) -> Result<T, String>,
+) -> Result<Option<T>, PbnError> {
+    let Some(tag) = raw.tags.get(name) else {
+        return Ok(None);
+    };
+    let value = tag.value.trim();
+    if value.is_empty() || value == "?" || (value == "-" && name != "Vulnerable") {
+        return Ok(None);
+    }
+    parse(value)
+        .map(Some)
+        .map_err(|message| PbnError::InvalidTag {
+            line: tag.line,
+            tag: name.to_string(),
+            value: value.to_string(),
+            message,
+        })
+}
+
+fn parse_result(
+    raw: &RawGame,
+    dealer: Option<Seat>,
+) -> Result<Option<NewBoardResultDTO>, PbnError> {
+    let passed_out = raw
+        .tags
+        .get("Contract")
+        .is_some_and(|tag| tag.value.trim().eq_ignore_ascii_case("pass"));
+    let contract = if passed_out {
+        None
+    } else {
+        parse_tag(raw, "Contract", |value| {
+            value.parse::<Contract>().map_err(|e| e.to_string())
+        })?
+    };
+    if contract.is_none() && !passed_out {
+        // A hand record without a result.
+        return Ok(None);
+    }
+
+    let room = parse_tag(raw, "Room", |value| {
+        match value.to_ascii_lowercase().as_str() {
+            "open" => Ok(Room::Open),
+            "closed" => Ok(Room::Closed),
+            _ => Err("room must be Open or Closed".to_string()),
+        }
+    })?;
+    let pair_number = |value: &str| {
+        value
+            .parse::<u32>()
+            .map_err(|_| "pair id must be a number".to_string())
+    };
+    let ns_pair = parse_tag(raw, "PairId_NS", pair_number)?;
+    let ew_pair = parse_tag(raw, "PairId_EW", pair_number)?;
+    let auction_dealer = parse_tag(raw, "Auction", |value| {
+        value.parse::<Seat>().map_err(|e| e.to_string())
+    })?
+    .or(dealer);
+    let auction = match raw.sections.get("Auction") {
+        Some(tokens) if auction_dealer.is_some() => {
+            let notes = parse_notes(raw.notes.get("Auction").map_or(&[], Vec::as_slice))?;
+            Some(parse_auction(tokens, &notes)?)
+        }
+        _ => None,
+    };
+
+    let Some(contract) = contract else {
+        return Ok(Some(NewBoardResultDTO {
+            room,
+            ns_pair,
+            ew_pair,
+            contract: None,
+            declarer: None,
+            opening_lead: None,
+            tricks: None,
+            auction,
+            play: None,
+            claim: None,
+            adjustment: None,
+        }));
+    };
+
+    let declarer = parse_tag(raw, "Declarer", |value| {
+        value.parse::<Seat>().map_err(|e| e.to_string())
+    })?
+    .ok_or(PbnError::MissingTag {
+        line: raw.start_line,
+        tag: "Declarer",
+    })?;
+    let tricks = parse_tag(raw, "Result", |value| {
+        value
+            .parse::<u8>()
+            .ok()
+            .filter(|tricks| *tricks <= 13)
+            .ok_or_else(|| "result must be the number of tricks taken by declarer".to_string())
+    })?
+    .ok_or(PbnError::MissingTag {
+        line: raw.start_line,
+        tag: "Result",
+    })?;
+    let play = match raw.sections.get("Play") {
+        Some(tokens) => {
+            let leader = parse_tag(raw, "Play", |value| {
+                value.parse::<Seat>().map_err(|e| e.to_string())
+            })?
+            .unwrap_or(declarer.next());
+            Some(parse_play(tokens, leader, contract.strain)?)
+        }
+        None => None,
+    };
+    // PBN has no claim of its own: play that stops early ended in a claim or concession
+    // of the tricks in [Result].
+    let claim = play.as_ref().filter(|play| play.len() < 52).map(|_| tricks);
+
+    Ok(Some(NewBoardResultDTO {
+        room,
+        ns_pair,
+        ew_pair,
+        contract: Some(contract),
+        declarer: Some(declarer),
+        opening_lead: play.as_ref().and_then(|play| play.first().copied()),
+        tricks: Some(tricks),
+        auction,
+        play,
+        claim,
+        adjustment: None,
+    }))
+}
+
+/// Notes by number, from `[Note "n:text"]` tags.
+fn parse_notes(tags: &[RawTag]) -> Result<HashMap<u32, String>, PbnError> {
+    tags.iter()
+        .map(|tag| {
+            tag.value
+                .split_once(':')
+                .and_then(|(number, text)| Some((number.trim().parse().ok()?, text.trim())))
+                .map(|(number, text)| (number, text.to_string()))
+                .ok_or_else(|| PbnError::InvalidTag {
+                    line: tag.line,
+                    tag: "Note".to_string(),
+                    value: tag.value.clone(),
+                    message: "note must be a number, a colon and the text".to_string(),
+                })
+        })
+        .collect()
+}
+
+/// A call marked `!` was alerted, and a `=n=` note after it explains it. Other
+/// annotations and the end marker are dropped.
+fn parse_auction(
+    tokens: &[(String, usize)],
+    notes: &HashMap<u32, String>,
+) -> Result<Auction, PbnError> {
+    let mut calls: Vec<AuctionCall> = Vec::new();
+    for (token, line) in tokens {
+        if let Some(number) = token
+            .strip_prefix('=')
+            .and_then(|rest| rest.strip_suffix('='))
+        {
+            if let Some(call) = calls.last_mut() {
+                call.explanation = number
+                    .parse::<u32>()
+                    .ok()
+                    .and_then(|number| notes.get(&number).cloned());
+            }
+            continue;
+        }
+        let alerted = token.trim_end_matches('?').ends_with('!');
+        let call = token.trim_end_matches(['!', '?']);
+        if call.is_empty() || call == "-" || call == "+" || call.starts_with('$') {
+            continue;
+        }
+        if call == "*" {
+            break;
+        }
+        match call.to_ascii_uppercase().as_str() {
+            "AP" => calls.extend(std::iter::repeat_n(AuctionCall::new(Call::Pass), 3)),
+            call => match call.parse::<Call>() {
+                Ok(call) => calls.push(AuctionCall {
+                    alerted,
+                    ..AuctionCall::new(call)
+                }),
+                Err(_) => {
+                    return Err(PbnError::InvalidTag {
+                        line: *line,
+                        tag: "Auction".to_string(),
+                        value: token.clone(),
+                        message: "not a call".to_string(),
+                    })
+                }
+            },
+        }
+    }
+    Ok(Auction { calls })
+}
+
+/// PBN lists each trick in seat order starting from the opening leader; this puts the
+/// cards back in the order they were played, working out who led to each trick.
+fn parse_play(
+    tokens: &[(String, usize)],
+    opening_leader: Seat,
+    strain: Strain,
+) -> Result<Vec<Card>, PbnError> {
+    let mut cards: Vec<Option<Card>> = Vec::new();
+    for (token, line) in tokens {
+        let token = token.trim_end_matches(['!', '?']);
+        if token.is_empty() || token.starts_with('$') || token.starts_with('=') {
+            continue;
+        }
+        if token == "*" {
+            break;
+        }
+        if token == "-" {
+            cards.push(None);
+            continue;
+        }
+        let card = token.parse::<Card>().map_err(|e| PbnError::InvalidTag {
+            line: *line,
+            tag: "Play".to_string(),
+            value: token.to_string(),
+            message: e.to_string(),
+        })?;
+        cards.push(Some(card));
+    }
+
+    let mut play = Vec::new();
+    let mut leader = opening_leader;
+    for trick in cards.chunks(4) {
+        let mut ordered = Vec::with_capacity(4);
+        for i in 0..4 {
+            let seat = Seat::from_index(leader.index() + i);
+            // Column of this seat, counted from the opening leader.
+            let column = (seat.index() + 4 - opening_leader.index()) % 4;
+            match trick.get(column).copied().flatten() {
+                Some(card) => ordered.push(card),
+                None => break,
+            }
+        }
+        let complete = ordered.len() == 4;
+        if complete {
+            leader = trick_winner(leader, &ordered, strain.trump());
+        }
+        play.extend(ordered);
+        if !complete {
+            break;
+        }
+    }
+    Ok(play)
+}
+
+/// Writes a session's boards as a PBN file in export format: one game per result, or a
+/// single game without a result for boards nobody has played yet.
+pub fn export_pbn(session: &SessionMongoDTO, boards: &[BoardMongoDTO]) -> String {
+    let mut out = String::new();
+    out.push_str("% PBN 2.1\r\n% EXPORT\r\n");
+    let date = session.date.to_chrono().format("%Y.%m.%d").to_string();
+    for board in boards {
+        let results: Vec<Option<&BoardResult>> = if board.results.is_empty() {
+            vec![None]
+        } else {
+            board.results.iter().map(Some).collect()
+        };
+        for result in results {
+            let team_match = result.and_then(|result| team_match(session, board, result));
+            out.push_str("\r\n");
+            // The mandatory tags come first, in the order the standard lists them.
+            write_tag(&mut out, "Event", &session.name);
+            write_tag(&mut out, "Site", &session.location);
+            write_tag(&mut out, "Date", &date);
+            let round = team_match
+                .map(|(_, round, _)| round.round.to_string())
+                .unwrap_or_default();
+            write_tag(&mut out, "Round", &round);
+            write_tag(&mut out, "Board", &board.board_number.to_string());
+            for seat in ["West", "North", "East", "South"] {
+                write_tag(&mut out, seat, "");
+            }
+            write_tag(&mut out, "Dealer", &board.dealer.to_string());
+            write_tag(
+                &mut out,
+                "Vulnerable",
+                pbn_vulnerability(board.vulnerability),
+            );
+            let deal = board
+                .deal
+                .map(|deal| deal.to_pbn(board.dealer))
+                .unwrap_or_else(|| "?".to_string());
+            write_tag(&mut out, "Deal", &deal);
+            write_tag(&mut out, "Scoring", pbn_scoring(session.scoring_type));
+            match result {
+                Some(result) => write_result(&mut out, board, result, team_match),
+                None => {
+                    write_tag(&mut out, "Declarer", "?");
+                    write_tag(&mut out, "Contract", "?");
+                    write_tag(&mut out, "Result", "?");
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The team event, round and match a result belongs to, if the session has a team event.
+fn team_match<'a>(
+    session: &'a SessionMongoDTO,
+    board: &BoardMongoDTO,
+    result: &BoardResult,
+) -> Option<(&'a TeamEvent, &'a EventRound, &'a TeamMatchup)> {
+    let event = session.team_event.as_ref()?;
+    let (round, matchup) =
+        event.find_match(board.board_number, result.ns_pair?, result.ew_pair?)?;
+    Some((event, round, matchup))
+}
+
+/// The result's mandatory tags, then the supplemental ones and the auction and play.
+fn write_result(
+    out: &mut String,
+    board: &BoardMongoDTO,
+    result: &BoardResult,
+    team_match: Option<(&TeamEvent, &EventRound, &TeamMatchup)>,
+) {
+    let declarer = result
+        .declarer
+        .map(|seat| seat.to_string())
+        .unwrap_or_default();
+    let contract = result
+        .contract
+        .map(|contract| contract.to_string())
+        .unwrap_or_else(|| "Pass".to_string());
+    let tricks = result
+        .tricks
+        .map(|tricks| tricks.to_string())
+        .unwrap_or_default();
+    write_tag(out, "Declarer", &declarer);
+    write_tag(out, "Contract", &contract);
+    write_tag(out, "Result", &tricks);
+    if let Some((event, _, matchup)) = team_match {
+        write_tag(out, "Table", &matchup.table.to_string());
+        write_tag(
+            out,
+            "HomeTeam",
+            event.team_name(matchup.home).unwrap_or_default(),
+        );
+        write_tag(
+            out,
+            "VisitTeam",
+            event.team_name(matchup.away).unwrap_or_default(),
+        );
+    }
+    if let Some(room) = result.room {
+        let room = match room {
+            Room::Open => "Open",
+            Room::Closed => "Closed",
+        };
+        write_tag(out, "Room", room);
+    }
+    if let Some(pair) = result.ns_pair {
+        write_tag(out, "PairId_NS", &pair.to_string());
+    }
+    if let Some(pair) = result.ew_pair {
+        write_tag(out, "PairId_EW", &pair.to_string());
+    }
+    if let Some(auction) = &result.auction {
+        write_tag(out, "Auction", &board.dealer.to_string());
+        let mut notes = Vec::new();
+        for calls in auction.calls.chunks(4) {
+            let calls: Vec<String> = calls
+                .iter()
+                .map(|call| {
+                    let mut token = call.call.to_string();
+                    if call.alerted {
+                        token.push('!');
+                    }
+                    if let Some(explanation) = &call.explanation {
+                        notes.push(explanation);
+                        token.push_str(&format!(" ={}=", notes.len()));
+                    }
+                    token
+                })
+                .collect();
+            out.push_str(&calls.join(" "));
+            out.push_str("\r\n");
+        }
+        // An auction that stopped before the end is marked as such.
+        if !auction.is_finished(board.dealer).unwrap_or(false) {
+            out.push_str("*\r\n");
+        }
+        for (i, explanation) in notes.iter().enumerate() {
+            write_tag(out, "Note", &format!("{}:{}", i + 1, explanation));
+        }
+    }
+    if let (Some(play), Some(contract), Some(declarer)) =
+        (&result.play, result.contract, result.declarer)
+    {
+        let opening_leader = declarer.next();
+        write_tag(out, "Play", &opening_leader.to_string());
+        let mut leader = opening_leader;
+        for trick in play.chunks(4) {
+            // Columns are in seat order starting from the opening leader.
+            let mut columns = [
+                "-".to_string(),
+                "-".to_string(),
+                "-".to_string(),
+                "-".to_string(),
+            ];
+            for (i, card) in trick.iter().enumerate() {
+                let seat = Seat::from_index(leader.index() + i);
+                columns[(seat.index() + 4 - opening_leader.index()) % 4] = card.to_string();
+            }
+            out.push_str(&columns.join(" "));
+            out.push_str("\r\n");
+            if trick.len() == 4 {
+                leader = trick_winner(leader, trick, contract.strain.trump());
+            }
+        }
+        out.push_str("*\r\n");
+    }
+}
+
+fn write_tag(out: &mut String, name: &str, value: &str) {
+    out.push_str(&format!(
+        "[{} \"{}\"]\r\n",
+        name,
+        value.replace('"', "\\\"")
+    ));
+}
+
+fn pbn_scoring(scoring_type: ScoringType) -> &'static str {
+    match scoring_type {
+        ScoringType::Imp => "IMP",
+        ScoringType::Mp => "MP",
+        ScoringType::Rubber => "Rubber",
+        ScoringType::Chicago => "Chicago",
+        ScoringType::Bam => "BAM",
+        ScoringType::TotalPoints => "TotalPoints",
+        ScoringType::Butler => "Butler",
+        ScoringType::CrossImp => "CrossImps",
+    }
+}
+
+fn pbn_vulnerability(vulnerability: Vulnerability) -> &'static str {
+    match vulnerability {
+        Vulnerability::None => "None",
+        Vulnerability::Ns => "NS",
+        Vulnerability::Ew => "EW",
+        Vulnerability::Both => "All",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::{oid::ObjectId, DateTime};
+
+    use super::*;
+    use crate::models::board::dealer_for_board;
+
+    const DEAL: &str = "N:AKQJ.AKQ.AKQ.AKQ T98.JT9.JT9.JT98 765432.8765432.. ..8765432.765432";
+
+    fn session() -> SessionMongoDTO {
+        SessionMongoDTO {
+            id: ObjectId::new(),
+            name: "Club \"Monday\" pairs".to_string(),
+            location: "Town hall".to_string(),
+            date: DateTime::from_millis(1_700_000_000_000),
+            owner: ObjectId::new(),
+            scoring_type: ScoringType::Mp,
+            should_use_victory_points: false,
+            matchpoint_convention: Default::default(),
+            board_count: None,
+            deal_seed: None,
+            deal_constraints: None,
+            movement: None,
+            team_event: None,
+            participants: Vec::new(),
+            status: Default::default(),
+            reopenings: Vec::new(),
+        }
+    }
+
+    fn board(board_number: u32, deal: Option<Deal>, results: Vec<BoardResult>) -> BoardMongoDTO {
+        BoardMongoDTO {
+            id: ObjectId::new(),
+            session_id: ObjectId::new(),
+            board_number,
+            dealer: dealer_for_board(board_number).unwrap(),
+            vulnerability: Vulnerability::Ns,
+            deal,
+            results,
+            dd_table: None,
+        }
+    }
+
+    fn played_result() -> BoardResult {
+        let result: NewBoardResultDTO = serde_json::from_value(serde_json::json!({
+            "nsPair": 1,
+            "ewPair": 2,
+            "contract": null,
+            "declarer": null,
+            "openingLead": null,
+            "tricks": null,
+            "auction": [
+                {"call": "1NT", "alerted": true},
+                "Pass",
+                {"call": "3NT", "explanation": "to play, no major"},
+                "Pass",
+                "Pass",
+                "Pass",
+            ],
+            "play": ["CJ", "S2", "C7", "CA", "SA", "S8", "S3", "D2"],
+            "claim": 13,
+        }))
+        .unwrap();
+        let deal: Deal = DEAL.parse().unwrap();
+        let result = result
+            .with_auction(Seat::North)
+            .unwrap()
+            .with_play(Some(&deal))
+            .unwrap();
+        BoardResult::score_result(result, Vulnerability::Ns).unwrap()
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let deal: Deal = DEAL.parse().unwrap();
+        let passed_out: NewBoardResultDTO = serde_json::from_value(serde_json::json!({
+            "nsPair": 3, "ewPair": 4, "contract": null, "declarer": null,
+            "openingLead": null, "tricks": null,
+        }))
+        .unwrap();
+        let passed_out = BoardResult::score_result(passed_out, Vulnerability::Ns).unwrap();
+        let boards = [board(1, Some(deal), vec![played_result(), passed_out])];
+        let exported = export_pbn(&session(), &boards);
+        let imported = boards_from_pbn(&exported).unwrap();
+
+        assert_eq!(imported.len(), 1);
+        let board = &imported[0];
+        assert_eq!(board.board_number, 1);
+        assert_eq!(board.dealer, Some(Seat::North));
+        assert_eq!(board.vulnerability, Some(Vulnerability::Ns));
+        assert_eq!(board.deal, Some(deal));
+        assert_eq!(board.results.len(), 2);
+
+        let played = &board.results[0];
+        let original = played_result();
+        assert_eq!(played.contract, original.contract);
+        assert_eq!(played.declarer, Some(Seat::North));
+        assert_eq!(played.tricks, Some(13));
+        assert_eq!((played.ns_pair, played.ew_pair), (Some(1), Some(2)));
+        assert_eq!(played.auction, original.auction);
+        assert_eq!(played.play, original.play);
+        assert_eq!(played.claim, Some(13));
+        assert_eq!(played.opening_lead, "CJ".parse().ok());
+
+        let passed_out = &board.results[1];
+        assert!(passed_out.contract.is_none());
+        assert!(passed_out.declarer.is_none());
+        assert_eq!(passed_out.auction, None);
+    }
+
+    #[test]
+    fn export_writes_alerts_and_notes() {
+        let deal: Deal = DEAL.parse().unwrap();
+        let exported = export_pbn(&session(), &[board(1, Some(deal), vec![played_result()])]);
+        assert!(exported.contains("1NT! Pass 3NT =1= Pass\r\n"));
+        assert!(exported.contains("[Note \"1:to play, no major\"]"));
+    }
+
+    #[test]
+    fn export_writes_mandatory_tags_in_order() {
+        let boards = [board(2, None, Vec::new())];
+        let exported = export_pbn(&session(), &boards);
+        let tags: Vec<&str> = exported
+            .lines()
+            .filter_map(|line| line.strip_prefix('['))
+            .filter_map(|line| line.split_whitespace().next())
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                "Event",
+                "Site",
+                "Date",
+                "Round",
+                "Board",
+                "West",
+                "North",
+                "East",
+                "South",
+                "Dealer",
+                "Vulnerable",
+                "Deal",
+                "Scoring",
+                "Declarer",
+                "Contract",
+                "Result",
+            ]
+        );
+        assert!(exported.contains("[Event \"Club \\\"Monday\\\" pairs\"]"));
+        assert!(exported.contains("[Date \"2023.11.14\"]"));
+        assert!(exported.contains("[Deal \"?\"]"));
+        assert_eq!(boards_from_pbn(&exported).unwrap()[0].deal, None);
+    }
+
+    #[test]
+    fn export_ends_an_unfinished_auction() {
+        let mut unfinished = played_result();
+        unfinished.auction.as_mut().unwrap().calls.truncate(3);
+        let exported = export_pbn(&session(), &[board(1, None, vec![unfinished])]);
+        assert!(exported.contains("1NT! Pass 3NT =1=\r\n*\r\n[Note"));
+        let imported = boards_from_pbn(&exported).unwrap();
+        let auction = imported[0].results[0].auction.as_ref().unwrap();
+        assert_eq!(auction.calls.len(), 3);
+
+        let finished = export_pbn(&session(), &[board(1, None, vec![played_result()])]);
+        assert!(!finished.contains("Pass\r\n*"));
+    }
+
+    #[test]
+    fn imports_a_club_file() {
+        let input = "% PBN 2.1\n\
+            [Event \"Club\"]\n\
+            [Board \"3\"]\n\
+            [Dealer \"S\"]\n\
+            [Vulnerable \"EW\"]\n\
+            [Deal \"N:AKQJ.AKQ.AKQ.AKQ T98.JT9.JT9.JT98 765432.8765432.. -\"]\n\
+            [Declarer \"W\"]\n\
+            [Contract \"4HX\"]\n\
+            [Result \"8\"] ; two down\n\
+            [Auction \"S\"]\n\
+            Pass 1H! Pass 4H =1=\n\
+            X AP\n\
+            [Note \"1:transfer\"]\n\
+            \n\
+            [Board \"3\"]\n\
+            [Dealer \"#\"]\n\
+            [Vulnerable \"#\"]\n\
+            [Contract \"Pass\"]\n\
+            {a passed-out\n\
+            table}\n";
+        let boards = boards_from_pbn(input).unwrap();
+        assert_eq!(boards.len(), 1);
+        assert_eq!(boards[0].dealer, Some(Seat::South));
+        assert_eq!(boards[0].results.len(), 2);
+        let doubled = &boards[0].results[0];
+        assert_eq!(doubled.contract, "4HX".parse().ok());
+        assert_eq!(doubled.tricks, Some(8));
+        let calls = &doubled.auction.as_ref().unwrap().calls;
+        assert_eq!(calls.len(), 8);
+        assert!(calls[1].alerted && calls[1].explanation.is_none());
+        assert!(!calls[3].alerted);
+        assert_eq!(calls[3].explanation.as_deref(), Some("transfer"));
+        assert!(!calls[0].alerted && !calls[4].alerted);
+        assert!(boards[0].results[1].contract.is_none());
+    }
+
+    #[test]
+    fn dash_means_none_vulnerable() {
+        let boards =
+            boards_from_pbn("[Board \"2\"]\n[Vulnerable \"-\"]\n[Declarer \"-\"]\n").unwrap();
+        assert_eq!(boards[0].vulnerability, Some(Vulnerability::None));
+        let boards = boards_from_pbn("[Board \"2\"]\n[Vulnerable \"?\"]\n").unwrap();
+        assert_eq!(boards[0].vulnerability, None);
+    }
+
+    #[test]
+    fn reports_bad_files() {
+        assert!(matches!(
+            boards_from_pbn("[Event \"Club\"]\n[Dealer \"N\"]\n"),
+            Err(PbnError::MissingTag { tag: "Board", .. })
+        ));
+        assert!(matches!(
+            boards_from_pbn(
+                "[Board \"1\"]\n[Contract \"3NT\"]\n[Declarer \"N\"]\n[Result \"14\"]\n"
+            ),
+            Err(PbnError::InvalidTag { line: 4, .. })
+        ));
+        assert!(matches!(
+            boards_from_pbn("[Board \"1\"]\n[Contract 3NT]\n"),
+            Err(PbnError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            boards_from_pbn("[Board \"1\"]\n[Dealer \"N\"]\n\n[Board \"1\"]\n[Dealer \"E\"]\n"),
+            Err(PbnError::InconsistentBoard(1))
+        ));
+    }
+}
@@ -1 +1,7 @@
-pub mod user;
\ No newline at end of file
+use async_graphql::MergedObject;
+
+pub mod session;
+pub mod user;
+
+#[derive(MergedObject, Default)]
+pub struct QueryRoot(user::Query, session::SessionQuery);
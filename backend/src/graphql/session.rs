@@ -0,0 +1,53 @@
+use async_graphql::{Context, Object};
+use mongodb::Client;
+
+use crate::models::{
+    board::BoardError,
+    session::{get_session, SessionError},
+    standings::{get_session_standings, Standings},
+    user::User,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionQueryError {
+    #[error("No database connection")]
+    NoDbConnectionError,
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("Session {0} not found")]
+    SessionNotFound(String),
+    #[error("Session error: {0}")]
+    SessionError(#[from] SessionError),
+    #[error("Board error: {0}")]
+    BoardError(#[from] BoardError),
+}
+
+#[derive(Default)]
+pub struct SessionQuery;
+
+#[Object]
+impl SessionQuery {
+    /// Standings for one of the signed-in user's sessions.
+    #[tracing::instrument(target = "graphql", skip(self, context))]
+    pub async fn standings(
+        &self,
+        context: &Context<'_>,
+        session_id: String,
+    ) -> Result<Standings, SessionQueryError> {
+        let db = context
+            .data::<Client>()
+            .map_err(|_| SessionQueryError::NoDbConnectionError)?;
+        let user = context
+            .data::<Option<User>>()
+            .ok()
+            .and_then(|user| user.as_ref())
+            .ok_or(SessionQueryError::Unauthorized)?;
+        let session = get_session(db, &session_id)
+            .await?
+            .ok_or_else(|| SessionQueryError::SessionNotFound(session_id.clone()))?;
+        if session.owner != user.id {
+            return Err(SessionQueryError::Unauthorized);
+        }
+        Ok(get_session_standings(db, &session).await?)
+    }
+}
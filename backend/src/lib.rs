@@ -1,9 +1,13 @@
+pub mod analysis;
 pub mod auth;
 pub mod configuration;
+pub mod dealing;
+pub mod formats;
 pub mod graphql;
 pub mod middlewares;
 pub mod models;
 pub mod startup;
 pub mod state;
 pub mod telemetry;
+pub mod tournament;
 pub mod web;
\ No newline at end of file
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use axum::{
+    body::Body,
+    extract::{Path, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+    Extension,
+};
+
+use bson::oid::ObjectId;
+
+use crate::{models::user::User, state::AppState};
+
+use super::session_owner_guard::{lookup_session, status_response};
+
+/// Role that lets a user act as director for sessions they do not own.
+pub const DIRECTOR_ROLE: &str = "director";
+
+/// Whether the user may direct a session with the given owner.
+fn may_direct(user: &User, owner: &ObjectId) -> bool {
+    user.id == *owner || user.has_role(DIRECTOR_ROLE)
+}
+
+/// For routes nested under `/api/session/{session_id}`: lets through the session's owner
+/// and users with the director role, handing the session to the handler as an
+/// `Extension<SessionMongoDTO>`.
+#[tracing::instrument(skip(user, mongodb_client, next, request))]
+pub async fn session_director_guard(
+    Extension(user): Extension<User>,
+    Path(params): Path<HashMap<String, String>>,
+    State(AppState {
+        mongodb_client,
+        keys: _,
+    }): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response<Body> {
+    let session_id = params.get("session_id").cloned().unwrap_or_default();
+    let session = match lookup_session(&mongodb_client, &session_id).await {
+        Ok(session) => session,
+        Err(response) => return response,
+    };
+    if !may_direct(&user, &session.owner) {
+        tracing::warn!(
+            "User {} is neither the owner of session {} nor a director",
+            user.id,
+            session.id
+        );
+        return status_response(StatusCode::UNAUTHORIZED, "Unauthorized");
+    }
+    request.extensions_mut().insert(session);
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::DateTime;
+
+    use crate::models::user::Role;
+
+    use super::*;
+
+    fn user(roles: &[&str]) -> User {
+        User {
+            id: ObjectId::new(),
+            username: "user".to_string(),
+            password: String::new(),
+            salt: String::new(),
+            email: "user@example.com".to_string(),
+            roles: roles
+                .iter()
+                .map(|name| Role {
+                    id: ObjectId::new(),
+                    name: name.to_string(),
+                    created_at: DateTime::now(),
+                    updated_at: DateTime::now(),
+                })
+                .collect(),
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        }
+    }
+
+    #[test]
+    fn owner_may_direct() {
+        let owner = user(&[]);
+        assert!(may_direct(&owner, &owner.id));
+    }
+
+    #[test]
+    fn director_may_direct_any_session() {
+        assert!(may_direct(&user(&["director"]), &ObjectId::new()));
+        assert!(may_direct(&user(&["user", "Director"]), &ObjectId::new()));
+    }
+
+    #[test]
+    fn anyone_else_is_refused() {
+        assert!(!may_direct(&user(&[]), &ObjectId::new()));
+        assert!(!may_direct(&user(&["user", "admin"]), &ObjectId::new()));
+    }
+}
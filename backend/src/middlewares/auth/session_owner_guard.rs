@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
 use axum::{
-    body::Body, extract::{Path, Request}, http::StatusCode, middleware::Next, response::Response, Extension
+    body::Body, extract::{Path, Request, State}, http::{Method, StatusCode}, middleware::Next, response::{IntoResponse, Response}, Extension, Json
 };
+use serde_json::json;
 
-use crate::models::user::User;
+use crate::{models::{session::{get_session, SessionError, SessionMongoDTO}, user::User}, state::AppState};
 /*
 pub async fn lookup_user_from_token(
     Extension(claims): Extension<Claims>,
@@ -14,10 +17,11 @@ pub async fn lookup_user_from_token(
 #[tracing::instrument(skip(user, next, request))]
 pub async fn session_owner_guard(
     Extension(user): Extension<User>,
-    Path(user_id): Path<String>,
+    Path(params): Path<HashMap<String, String>>,
     request: Request,
     next: Next,
 ) -> Response<Body> {
+    let user_id = params.get("user_id").cloned().unwrap_or_default();
     if user.id.to_string() == user_id {
         return next.run(request).await;
     }
@@ -28,4 +32,79 @@ pub async fn session_owner_guard(
             .unwrap()
 
 
-}
\ No newline at end of file
+}
+
+/// For routes nested under `/api/user/{user_id}/session/{session_id}`: checks that the
+/// caller is `user_id` and owns the session, then hands the session to the handler as an
+/// `Extension<SessionMongoDTO>`.
+#[tracing::instrument(skip(user, mongodb_client, next, request))]
+pub async fn owned_session_guard(
+    Extension(user): Extension<User>,
+    Path(params): Path<HashMap<String, String>>,
+    State(AppState{ mongodb_client, keys: _}): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response<Body> {
+    let user_id = params.get("user_id").cloned().unwrap_or_default();
+    let session_id = params.get("session_id").cloned().unwrap_or_default();
+    if user.id.to_string() != user_id {
+        tracing::warn!("User {} tried to access user {}'s session", user.id, user_id);
+        return status_response(StatusCode::UNAUTHORIZED, "Unauthorized");
+    }
+    let session = match lookup_session(&mongodb_client, &session_id).await {
+        Ok(session) => session,
+        Err(response) => return response,
+    };
+    if session.owner != user.id {
+        tracing::warn!("User {} tried to access session {} owned by {}", user.id, session.id, session.owner);
+        return status_response(StatusCode::UNAUTHORIZED, "Unauthorized");
+    }
+    request.extensions_mut().insert(session);
+    next.run(request).await
+}
+
+/// Layered inside `owned_session_guard` on routes that change a session's boards or set-up:
+/// once the session is final, only reads get through.
+#[tracing::instrument(skip(session, next, request), fields(session_id = %session.id))]
+pub async fn unlocked_session_guard(
+    Extension(session): Extension<SessionMongoDTO>,
+    request: Request,
+    next: Next,
+) -> Response<Body> {
+    if request.method() == Method::GET || request.method() == Method::HEAD {
+        return next.run(request).await;
+    }
+    match session.ensure_editable() {
+        Ok(()) => next.run(request).await,
+        Err(e) => (StatusCode::CONFLICT, Json(json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+/// The session named in the path, or the response to send when there is none: 400 for an
+/// id that is not an ObjectId, 404 for an unknown session and 500 when the query fails.
+pub(super) async fn lookup_session(
+    mongodb_client: &mongodb::Client,
+    session_id: &str,
+) -> Result<SessionMongoDTO, Response<Body>> {
+    match get_session(mongodb_client, session_id).await {
+        Ok(Some(session)) => Ok(session),
+        Ok(None) => Err(status_response(StatusCode::NOT_FOUND, "Session not found")),
+        Err(SessionError::InvalidObjectId(_)) => {
+            Err(status_response(StatusCode::BAD_REQUEST, "Invalid session"))
+        }
+        Err(e) => {
+            tracing::error!("Error looking up session {}: {:?}", session_id, e);
+            Err(status_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal server error",
+            ))
+        }
+    }
+}
+
+pub(super) fn status_response(status: StatusCode, message: &'static str) -> Response<Body> {
+    Response::builder()
+            .status(status)
+            .body(message.into())
+            .unwrap()
+}
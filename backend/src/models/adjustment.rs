@@ -0,0 +1,63 @@
+//! Adjusted scores under Law 12. A director either awards an artificial score, when no
+//! result could be obtained, or assigns a score in place of the one obtained at the
+//! table, possibly weighted over several likely outcomes. Either way the table result
+//! stays on the board result alongside the adjustment.
+use serde::{Deserialize, Serialize};
+
+use super::contract::{Contract, Seat};
+
+/// Artificial adjusted scores (Law 12C2), awarded to each side separately, so a split
+/// score such as Average-plus / Average-minus is possible.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ArtificialScore {
+    AveragePlus,
+    Average,
+    AverageMinus,
+}
+
+impl ArtificialScore {
+    /// Share of the top at matchpoints.
+    pub fn percentage(&self) -> f64 {
+        match self {
+            ArtificialScore::AveragePlus => 60.0,
+            ArtificialScore::Average => 50.0,
+            ArtificialScore::AverageMinus => 40.0,
+        }
+    }
+
+    /// IMPs at teams and IMP pairs.
+    pub fn imps(&self) -> i32 {
+        match self {
+            ArtificialScore::AveragePlus => 3,
+            ArtificialScore::Average => 0,
+            ArtificialScore::AverageMinus => -3,
+        }
+    }
+}
+
+/// One outcome of an assigned score, e.g. 30% of 4S= .
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WeightedScore {
+    /// Percentage of the assigned score this outcome accounts for.
+    pub weight: f64,
+    /// `None` for a pass-out.
+    pub contract: Option<Contract>,
+    pub declarer: Option<Seat>,
+    pub tricks: Option<u8>,
+    /// North-South score, worked out when the result is scored.
+    #[serde(default)]
+    pub score: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Adjustment {
+    Artificial {
+        ns: ArtificialScore,
+        ew: ArtificialScore,
+    },
+    /// Law 12C1: the weights add up to 100.
+    Assigned { scores: Vec<WeightedScore> },
+}
@@ -0,0 +1,466 @@
+//! Auctions as called at the table, with alerts, checked against the laws on sufficient
+//! bids, doubles and redoubles. A finished auction gives the final contract and declarer.
+use serde::{Deserialize, Serialize};
+
+use super::contract::{Contract, ContractError, Doubled, Seat, Strain};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuctionError {
+    #[error("Not a call: {0}")]
+    InvalidCall(String),
+    #[error("Call {position}: {bid} is insufficient over {last}")]
+    InsufficientBid {
+        position: usize,
+        bid: Call,
+        last: Call,
+    },
+    #[error("Call {0}: a side cannot double its own bid")]
+    DoubleOfOwnSide(usize),
+    #[error("Call {0}: there is no undoubled bid by the opponents to double")]
+    NothingToDouble(usize),
+    #[error("Call {0}: there is no double of the side's own bid to redouble")]
+    RedoubleWithoutDouble(usize),
+    #[error("Call {0} comes after the auction has ended")]
+    CallAfterEnd(usize),
+    #[error("The auction has not ended")]
+    Unfinished,
+    #[error("Contract error: {0}")]
+    ContractError(#[from] ContractError),
+}
+
+/// A single call, written as on a traveller: `Pass`, `X`, `XX` or a bid such as `1S` or
+/// `3NT`.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum Call {
+    Pass,
+    Double,
+    Redouble,
+    Bid { level: u8, strain: Strain },
+}
+
+impl std::fmt::Display for Call {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Call::Pass => write!(f, "Pass"),
+            Call::Double => write!(f, "X"),
+            Call::Redouble => write!(f, "XX"),
+            Call::Bid { level, strain } => write!(f, "{}{}", level, strain),
+        }
+    }
+}
+
+impl std::str::FromStr for Call {
+    type Err = AuctionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "P" | "PASS" => Ok(Call::Pass),
+            "X" => Ok(Call::Double),
+            "XX" => Ok(Call::Redouble),
+            bid => bid
+                .split_at_checked(1)
+                .and_then(|(level, strain)| {
+                    let level = level.parse::<u8>().ok().filter(|l| (1..=7).contains(l))?;
+                    let strain = strain.parse::<Strain>().ok()?;
+                    Some(Call::Bid { level, strain })
+                })
+                .ok_or_else(|| AuctionError::InvalidCall(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for Call {
+    type Error = AuctionError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Call> for String {
+    fn from(call: Call) -> Self {
+        call.to_string()
+    }
+}
+
+/// A call and whether it was alerted. Also reads calls stored as plain strings.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", from = "AuctionCallRecord")]
+pub struct AuctionCall {
+    pub call: Call,
+    pub alerted: bool,
+    pub explanation: Option<String>,
+}
+
+impl AuctionCall {
+    pub fn new(call: Call) -> Self {
+        AuctionCall {
+            call,
+            alerted: false,
+            explanation: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AuctionCallRecord {
+    Plain(Call),
+    #[serde(rename_all = "camelCase")]
+    Annotated {
+        call: Call,
+        #[serde(default)]
+        alerted: bool,
+        #[serde(default)]
+        explanation: Option<String>,
+    },
+}
+
+impl From<AuctionCallRecord> for AuctionCall {
+    fn from(record: AuctionCallRecord) -> Self {
+        match record {
+            AuctionCallRecord::Plain(call) => AuctionCall::new(call),
+            AuctionCallRecord::Annotated {
+                call,
+                alerted,
+                explanation,
+            } => AuctionCall {
+                call,
+                alerted,
+                explanation,
+            },
+        }
+    }
+}
+
+/// The calls in order, starting with the dealer's.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(transparent)]
+pub struct Auction {
+    pub calls: Vec<AuctionCall>,
+}
+
+/// Where an auction stands after its calls so far.
+struct AuctionState {
+    /// The last bid and who made it.
+    last_bid: Option<(u8, Strain, Seat)>,
+    doubled: Doubled,
+    /// Consecutive passes at the end.
+    passes: usize,
+}
+
+impl AuctionState {
+    fn has_ended(&self) -> bool {
+        match self.last_bid {
+            Some(_) => self.passes >= 3,
+            None => self.passes >= 4,
+        }
+    }
+}
+
+impl Auction {
+    /// Checks every call was legal when it was made.
+    pub fn validate(&self, dealer: Seat) -> Result<(), AuctionError> {
+        self.replay(dealer).map(|_| ())
+    }
+
+    /// Whether the calls so far end the auction: four passes, or three after a bid.
+    pub fn is_finished(&self, dealer: Seat) -> Result<bool, AuctionError> {
+        Ok(self.replay(dealer)?.has_ended())
+    }
+
+    /// The contract the auction ended in and its declarer, who is whoever on the
+    /// declaring side first named the final strain. `None` when it was passed out.
+    pub fn final_contract(&self, dealer: Seat) -> Result<Option<(Contract, Seat)>, AuctionError> {
+        let state = self.replay(dealer)?;
+        if !state.has_ended() {
+            return Err(AuctionError::Unfinished);
+        }
+        let Some((level, strain, last_bidder)) = state.last_bid else {
+            return Ok(None);
+        };
+        let declarer = self
+            .calls
+            .iter()
+            .enumerate()
+            .map(|(i, call)| (Seat::from_index(dealer.index() + i), call.call))
+            .find(|(seat, call)| {
+                (*seat == last_bidder || *seat == last_bidder.partner())
+                    && matches!(call, Call::Bid { strain: named, .. } if *named == strain)
+            })
+            .map(|(seat, _)| seat)
+            .unwrap_or(last_bidder);
+        Ok(Some((
+            Contract::new(level, strain, state.doubled)?,
+            declarer,
+        )))
+    }
+
+    fn replay(&self, dealer: Seat) -> Result<AuctionState, AuctionError> {
+        let mut state = AuctionState {
+            last_bid: None,
+            doubled: Doubled::Undoubled,
+            passes: 0,
+        };
+        for (i, call) in self.calls.iter().enumerate() {
+            let position = i + 1;
+            let seat = Seat::from_index(dealer.index() + i);
+            if state.has_ended() {
+                return Err(AuctionError::CallAfterEnd(position));
+            }
+            let same_side = |bidder: Seat| bidder == seat || bidder == seat.partner();
+            match call.call {
+                Call::Pass => {
+                    state.passes += 1;
+                    continue;
+                }
+                Call::Bid { level, strain } => {
+                    if let Some((last_level, last_strain, _)) = state.last_bid {
+                        if (level, strain) <= (last_level, last_strain) {
+                            return Err(AuctionError::InsufficientBid {
+                                position,
+                                bid: call.call,
+                                last: Call::Bid {
+                                    level: last_level,
+                                    strain: last_strain,
+                                },
+                            });
+                        }
+                    }
+                    state.last_bid = Some((level, strain, seat));
+                    state.doubled = Doubled::Undoubled;
+                }
+                Call::Double => match state.last_bid {
+                    Some((_, _, bidder)) if same_side(bidder) => {
+                        return Err(AuctionError::DoubleOfOwnSide(position))
+                    }
+                    Some(_) if state.doubled == Doubled::Undoubled => {
+                        state.doubled = Doubled::Doubled
+                    }
+                    _ => return Err(AuctionError::NothingToDouble(position)),
+                },
+                Call::Redouble => match state.last_bid {
+                    Some((_, _, bidder))
+                        if same_side(bidder) && state.doubled == Doubled::Doubled =>
+                    {
+                        state.doubled = Doubled::Redoubled
+                    }
+                    _ => return Err(AuctionError::RedoubleWithoutDouble(position)),
+                },
+            }
+            state.passes = 0;
+        }
+        Ok(state)
+    }
+}
+
+/// Calls separated by spaces, alerted ones marked with `!`.
+impl std::fmt::Display for Auction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let calls: Vec<String> = self
+            .calls
+            .iter()
+            .map(|call| {
+                if call.alerted {
+                    format!("{}!", call.call)
+                } else {
+                    call.call.to_string()
+                }
+            })
+            .collect();
+        write!(f, "{}", calls.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auction(calls: &str) -> Auction {
+        Auction {
+            calls: calls
+                .split_whitespace()
+                .map(|call| AuctionCall::new(call.parse().unwrap()))
+                .collect(),
+        }
+    }
+
+    fn contract(calls: &str, dealer: Seat) -> Option<(String, Seat)> {
+        auction(calls)
+            .final_contract(dealer)
+            .unwrap()
+            .map(|(contract, declarer)| (contract.to_string(), declarer))
+    }
+
+    #[test]
+    fn parses_calls() {
+        assert_eq!("p".parse::<Call>().unwrap(), Call::Pass);
+        assert_eq!("xx".parse::<Call>().unwrap(), Call::Redouble);
+        assert_eq!("3nt".parse::<Call>().unwrap().to_string(), "3NT");
+        for call in ["", "8S", "0C", "1Z", "S1", "XXX", "2"] {
+            assert!(
+                matches!(call.parse::<Call>(), Err(AuctionError::InvalidCall(_))),
+                "{call}"
+            );
+        }
+    }
+
+    #[test]
+    fn declarer_first_named_the_strain() {
+        assert_eq!(
+            contract("1S Pass 2NT Pass 3NT Pass Pass Pass", Seat::North),
+            Some(("3NT".to_string(), Seat::South))
+        );
+        assert_eq!(
+            contract("1H Pass 4H Pass Pass Pass", Seat::West),
+            Some(("4H".to_string(), Seat::West))
+        );
+        assert_eq!(
+            contract("Pass 1H X XX Pass Pass 2S X Pass Pass Pass", Seat::North),
+            Some(("2SX".to_string(), Seat::South))
+        );
+        assert_eq!(
+            contract("1C X XX Pass Pass Pass", Seat::East),
+            Some(("1CXX".to_string(), Seat::East))
+        );
+        assert_eq!(contract("Pass Pass Pass Pass", Seat::South), None);
+    }
+
+    #[test]
+    fn knows_when_the_auction_ends() {
+        assert!(!auction("Pass Pass Pass").is_finished(Seat::North).unwrap());
+        assert!(!auction("1S Pass Pass").is_finished(Seat::North).unwrap());
+        assert!(auction("1S Pass Pass Pass")
+            .is_finished(Seat::North)
+            .unwrap());
+        assert!(auction("Pass Pass Pass Pass")
+            .is_finished(Seat::North)
+            .unwrap());
+    }
+
+    #[test]
+    fn rejects_insufficient_bids() {
+        for (calls, position) in [("1S 1H", 2), ("1S Pass 1S", 3), ("2NT X 2S", 3)] {
+            match auction(calls).validate(Seat::North) {
+                Err(AuctionError::InsufficientBid {
+                    position: at,
+                    bid,
+                    last,
+                }) => {
+                    assert_eq!(at, position, "{calls}");
+                    assert_eq!(last, auction(calls).calls[0].call, "{calls}");
+                    assert_eq!(bid, auction(calls).calls[position - 1].call, "{calls}");
+                }
+                other => panic!("{calls}: {other:?}"),
+            }
+        }
+        assert!(auction("1S 1NT 2C").validate(Seat::North).is_ok());
+    }
+
+    #[test]
+    fn rejects_doubles_of_own_side() {
+        assert!(matches!(
+            auction("1S Pass X").validate(Seat::North),
+            Err(AuctionError::DoubleOfOwnSide(3))
+        ));
+        assert!(matches!(
+            auction("1S X Pass Pass X").validate(Seat::East),
+            Err(AuctionError::DoubleOfOwnSide(5))
+        ));
+    }
+
+    #[test]
+    fn rejects_doubles_with_nothing_to_double() {
+        for (calls, position) in [
+            ("X", 1),
+            ("Pass Pass X", 3),
+            ("1S X Pass X", 4),
+            ("1S X XX X", 4),
+        ] {
+            assert!(
+                matches!(
+                    auction(calls).validate(Seat::North),
+                    Err(AuctionError::NothingToDouble(at)) if at == position
+                ),
+                "{calls}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_redoubles_without_a_double() {
+        for (calls, position) in [
+            ("XX", 1),
+            ("1S XX", 2),
+            ("1S X Pass XX", 4),
+            ("1S X XX Pass Pass XX", 6),
+        ] {
+            assert!(
+                matches!(
+                    auction(calls).validate(Seat::North),
+                    Err(AuctionError::RedoubleWithoutDouble(at)) if at == position
+                ),
+                "{calls}"
+            );
+        }
+        assert!(auction("1S X Pass Pass XX").validate(Seat::North).is_ok());
+    }
+
+    #[test]
+    fn rejects_calls_after_the_end() {
+        assert!(matches!(
+            auction("1S Pass Pass Pass 2H").validate(Seat::North),
+            Err(AuctionError::CallAfterEnd(5))
+        ));
+        assert!(matches!(
+            auction("Pass Pass Pass Pass Pass").validate(Seat::West),
+            Err(AuctionError::CallAfterEnd(5))
+        ));
+    }
+
+    #[test]
+    fn unfinished_auction_has_no_contract() {
+        for calls in ["", "1S Pass", "1S Pass Pass", "Pass Pass Pass"] {
+            assert!(
+                matches!(
+                    auction(calls).final_contract(Seat::North),
+                    Err(AuctionError::Unfinished)
+                ),
+                "{calls}"
+            );
+        }
+    }
+
+    #[test]
+    fn reads_plain_and_annotated_calls() {
+        let read: Auction = serde_json::from_value(serde_json::json!([
+            "1C",
+            {"call": "1D", "explanation": "negative"},
+            {"call": "1H", "alerted": true},
+        ]))
+        .unwrap();
+        assert!(!read.calls[0].alerted);
+        assert!(!read.calls[1].alerted);
+        assert_eq!(read.calls[1].explanation.as_deref(), Some("negative"));
+        assert!(read.calls[2].alerted && read.calls[2].explanation.is_none());
+        assert_eq!(read.to_string(), "1C 1D 1H!");
+        assert!(serde_json::from_value::<Auction>(serde_json::json!(["1C", "9C"])).is_err());
+    }
+
+    #[test]
+    fn alerts_survive_a_round_trip() {
+        let auction: Auction = serde_json::from_value(serde_json::json!([
+            {"call": "1C", "alerted": true, "explanation": "could be short"},
+            {"call": "1D", "explanation": "natural"},
+            "Pass",
+        ]))
+        .unwrap();
+        let stored = bson::to_bson(&auction).unwrap();
+        let read: Auction = bson::from_bson(stored).unwrap();
+        assert_eq!(read, auction);
+        assert!(read.calls[0].alerted);
+        assert!(!read.calls[1].alerted);
+        assert_eq!(read.calls[1].explanation.as_deref(), Some("natural"));
+    }
+}
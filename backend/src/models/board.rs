@@ -0,0 +1,1025 @@
+use bson::{oid::ObjectId, Document};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::doc,
+    error::{ErrorKind, WriteFailure},
+    options::IndexOptions,
+    Client, Collection, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::double_dummy::DoubleDummyTable;
+
+use super::{
+    adjustment::{Adjustment, ArtificialScore},
+    auction::{Auction, AuctionError},
+    card::Card,
+    contract::{Contract, Seat, Vulnerability},
+    deal::Deal,
+    play::{replay_play, PlayError},
+    scoring::{duplicate::ns_duplicate_score, ScoringError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum BoardError {
+    #[error("Invalid board number: {0}")]
+    InvalidBoardNumber(u32),
+    #[error("Board {0} not found")]
+    BoardNotFound(String),
+    #[error("Board {0} already exists in this session")]
+    DuplicateBoardNumber(u32),
+    #[error("Board {0} appears more than once")]
+    RepeatedBoardNumber(u32),
+    #[error("Board {0} already has results, which would no longer match a new deal")]
+    BoardHasResults(u32),
+    #[error("A result with a contract needs a declarer and the number of tricks taken")]
+    IncompleteResult,
+    #[error("A passed-out result cannot have a declarer or tricks taken")]
+    PassedOutWithResult,
+    #[error("Weights of an assigned score must be positive and add up to 100")]
+    InvalidAdjustmentWeights,
+    #[error("Illegal auction: {0}")]
+    AuctionError(#[from] AuctionError),
+    #[error("The contract and declarer do not match the auction")]
+    AuctionMismatch,
+    #[error("Invalid play: {0}")]
+    PlayError(#[from] PlayError),
+    #[error("Board {0} has no deal to analyse")]
+    MissingDeal(u32),
+    #[error("Double-dummy analysis did not finish: {0}")]
+    AnalysisInterrupted(#[from] tokio::task::JoinError),
+    #[error("Scoring error: {0}")]
+    ScoringError(#[from] ScoringError),
+    #[error("Query error: {0}")]
+    QueryError(#[from] mongodb::error::Error),
+    #[error("Invalid board record: {0}")]
+    InvalidBoardRecord(#[from] bson::de::Error),
+    #[error("Could not serialize board: {0}")]
+    SerializationError(#[from] bson::ser::Error),
+    #[error("Could not convert {0} to ObjectId")]
+    InvalidObjectId(#[from] bson::oid::Error),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardMongoDTO {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub session_id: ObjectId,
+    pub board_number: u32,
+    pub dealer: Seat,
+    pub vulnerability: Vulnerability,
+    #[serde(default)]
+    pub deal: Option<Deal>,
+    pub results: Vec<BoardResult>,
+    /// Double-dummy tricks for the deal, kept once solved and cleared when the deal changes.
+    #[serde(default)]
+    pub dd_table: Option<DoubleDummyTable>,
+}
+
+/// Which table of a team match a result was played at.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Room {
+    Open,
+    Closed,
+}
+
+/// One table's result on a board. A `None` contract means the board was passed out.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardResult {
+    #[serde(default)]
+    pub room: Option<Room>,
+    #[serde(default)]
+    pub ns_pair: Option<u32>,
+    #[serde(default)]
+    pub ew_pair: Option<u32>,
+    pub contract: Option<Contract>,
+    pub declarer: Option<Seat>,
+    pub opening_lead: Option<Card>,
+    pub tricks: Option<u8>,
+    /// Calls in order from the dealer.
+    #[serde(default)]
+    pub auction: Option<Auction>,
+    /// Cards in the order they were played.
+    #[serde(default)]
+    pub play: Option<Vec<Card>>,
+    /// Total tricks declarer claimed (or the defence conceded) when play stopped early.
+    #[serde(default)]
+    pub claim: Option<u8>,
+    /// Director's adjusted score, which counts instead of the table result.
+    #[serde(default)]
+    pub adjustment: Option<Adjustment>,
+    /// Duplicate score from North-South's point of view, as obtained at the table.
+    pub score: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewBoardDTO {
+    pub board_number: u32,
+    /// Dealer and vulnerability follow the board number unless given.
+    #[serde(default)]
+    pub dealer: Option<Seat>,
+    #[serde(default)]
+    pub vulnerability: Option<Vulnerability>,
+    #[serde(default)]
+    pub deal: Option<Deal>,
+    #[serde(default)]
+    pub results: Vec<NewBoardResultDTO>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewBoardResultDTO {
+    #[serde(default)]
+    pub room: Option<Room>,
+    #[serde(default)]
+    pub ns_pair: Option<u32>,
+    #[serde(default)]
+    pub ew_pair: Option<u32>,
+    pub contract: Option<Contract>,
+    pub declarer: Option<Seat>,
+    pub opening_lead: Option<Card>,
+    pub tricks: Option<u8>,
+    /// Calls in order from the dealer. Once the auction has ended it gives the contract
+    /// and declarer, which may then be left out.
+    #[serde(default)]
+    pub auction: Option<Auction>,
+    #[serde(default)]
+    pub play: Option<Vec<Card>>,
+    #[serde(default)]
+    pub claim: Option<u8>,
+    #[serde(default)]
+    pub adjustment: Option<Adjustment>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardUpdateDTO {
+    pub board_number: Option<u32>,
+    /// Dealer and vulnerability follow a new board number unless given, or unless they
+    /// were set apart from the old number's.
+    #[serde(default)]
+    pub dealer: Option<Seat>,
+    #[serde(default)]
+    pub vulnerability: Option<Vulnerability>,
+    pub deal: Option<Deal>,
+    pub results: Option<Vec<NewBoardResultDTO>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardJsonDTO {
+    pub id: String,
+    pub session_id: String,
+    pub board_number: u32,
+    pub dealer: Seat,
+    pub vulnerability: Vulnerability,
+    pub deal: Option<Deal>,
+    pub results: Vec<BoardResult>,
+    pub dd_table: Option<DoubleDummyTable>,
+}
+
+impl From<BoardMongoDTO> for BoardJsonDTO {
+    fn from(board: BoardMongoDTO) -> Self {
+        BoardJsonDTO {
+            id: board.id.to_string(),
+            session_id: board.session_id.to_string(),
+            board_number: board.board_number,
+            dealer: board.dealer,
+            vulnerability: board.vulnerability,
+            deal: board.deal,
+            results: board.results,
+            dd_table: board.dd_table,
+        }
+    }
+}
+
+impl BoardMongoDTO {
+    /// Dealer and vulnerability once `update` is applied. Those the update gives are
+    /// taken as they are. A new board number re-derives the others, but only where they
+    /// followed the old number, so a dealer or vulnerability set on purpose is kept.
+    fn updated_dealing(
+        &self,
+        update: &BoardUpdateDTO,
+    ) -> Result<(Seat, Vulnerability), BoardError> {
+        let renumbered = update
+            .board_number
+            .filter(|board_number| *board_number != self.board_number);
+        let dealer = match (update.dealer, renumbered) {
+            (Some(dealer), _) => dealer,
+            (None, Some(board_number))
+                if dealer_for_board(self.board_number).ok() == Some(self.dealer) =>
+            {
+                dealer_for_board(board_number)?
+            }
+            (None, _) => self.dealer,
+        };
+        let vulnerability = match (update.vulnerability, renumbered) {
+            (Some(vulnerability), _) => vulnerability,
+            (None, Some(board_number))
+                if vulnerability_for_board(self.board_number).ok()
+                    == Some(self.vulnerability) =>
+            {
+                vulnerability_for_board(board_number)?
+            }
+            (None, _) => self.vulnerability,
+        };
+        Ok((dealer, vulnerability))
+    }
+}
+
+/// Dealer rotates clockwise starting with North on board 1.
+pub fn dealer_for_board(board_number: u32) -> Result<Seat, BoardError> {
+    if board_number == 0 {
+        return Err(BoardError::InvalidBoardNumber(board_number));
+    }
+    Ok(Seat::from_index((board_number as usize - 1) % 4))
+}
+
+/// Standard duplicate vulnerability, which repeats every 16 boards.
+pub fn vulnerability_for_board(board_number: u32) -> Result<Vulnerability, BoardError> {
+    use Vulnerability::{Both, Ew, None, Ns};
+    const CYCLE: [Vulnerability; 16] = [
+        None, Ns, Ew, Both, Ns, Ew, Both, None, Ew, Both, None, Ns, Both, None, Ns, Ew,
+    ];
+    if board_number == 0 {
+        return Err(BoardError::InvalidBoardNumber(board_number));
+    }
+    Ok(CYCLE[(board_number as usize - 1) % 16])
+}
+
+impl BoardResult {
+    pub fn score_result(
+        result: NewBoardResultDTO,
+        vulnerability: Vulnerability,
+    ) -> Result<BoardResult, BoardError> {
+        let score = table_score(
+            result.contract.as_ref(),
+            result.declarer,
+            result.tricks,
+            vulnerability,
+        )?;
+        let adjustment = match result.adjustment {
+            Some(Adjustment::Assigned { mut scores }) => {
+                let total: f64 = scores.iter().map(|weighted| weighted.weight).sum();
+                if scores.iter().any(|weighted| weighted.weight <= 0.0)
+                    || (total - 100.0).abs() > 0.01
+                {
+                    return Err(BoardError::InvalidAdjustmentWeights);
+                }
+                for weighted in &mut scores {
+                    weighted.score = table_score(
+                        weighted.contract.as_ref(),
+                        weighted.declarer,
+                        weighted.tricks,
+                        vulnerability,
+                    )?;
+                }
+                Some(Adjustment::Assigned { scores })
+            }
+            adjustment => adjustment,
+        };
+        Ok(BoardResult {
+            room: result.room,
+            ns_pair: result.ns_pair,
+            ew_pair: result.ew_pair,
+            contract: result.contract,
+            declarer: result.declarer,
+            opening_lead: result.opening_lead,
+            tricks: result.tricks,
+            auction: result.auction,
+            play: result.play,
+            claim: result.claim,
+            adjustment,
+            score,
+        })
+    }
+
+    /// The artificial scores awarded to North-South and East-West, if any.
+    pub fn artificial(&self) -> Option<(ArtificialScore, ArtificialScore)> {
+        match self.adjustment {
+            Some(Adjustment::Artificial { ns, ew }) => Some((ns, ew)),
+            _ => None,
+        }
+    }
+
+    /// The North-South scores this result counts as, with weights adding up to 1: the
+    /// table score unless a score was assigned.
+    pub fn weighted_scores(&self) -> Vec<(f64, i32)> {
+        match &self.adjustment {
+            Some(Adjustment::Assigned { scores }) => scores
+                .iter()
+                .map(|weighted| (weighted.weight / 100.0, weighted.score))
+                .collect(),
+            _ => vec![(1.0, self.score)],
+        }
+    }
+}
+
+/// North-South score of a contract played at the table. A board with no contract was
+/// passed out and scores nothing, so it cannot have a declarer or tricks taken.
+fn table_score(
+    contract: Option<&Contract>,
+    declarer: Option<Seat>,
+    tricks: Option<u8>,
+    vulnerability: Vulnerability,
+) -> Result<i32, BoardError> {
+    match (contract, declarer, tricks) {
+        (None, None, None) => Ok(0),
+        (None, _, _) => Err(BoardError::PassedOutWithResult),
+        (Some(contract), Some(declarer), Some(tricks)) => {
+            Ok(ns_duplicate_score(contract, declarer, vulnerability, tricks)?)
+        }
+        _ => Err(BoardError::IncompleteResult),
+    }
+}
+
+impl NewBoardResultDTO {
+    /// Checks the auction is legal and, if it has ended, that the contract and declarer
+    /// are the ones it arrived at, filling them in where they were left out.
+    pub fn with_auction(mut self, dealer: Seat) -> Result<Self, BoardError> {
+        let Some(auction) = &self.auction else {
+            return Ok(self);
+        };
+        if !auction.is_finished(dealer)? {
+            return Ok(self);
+        }
+        match auction.final_contract(dealer)? {
+            None if self.contract.is_some() => return Err(BoardError::AuctionMismatch),
+            None => {}
+            Some((contract, declarer)) => {
+                if self.contract.is_some_and(|given| given != contract)
+                    || self.declarer.is_some_and(|given| given != declarer)
+                {
+                    return Err(BoardError::AuctionMismatch);
+                }
+                self.contract = Some(contract);
+                self.declarer = Some(declarer);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Replays the play against the deal and checks the tricks and opening lead entered
+    /// agree with it, filling them in where they were left out. Play can only be checked
+    /// once the board's deal is known.
+    pub fn with_play(mut self, deal: Option<&Deal>) -> Result<Self, BoardError> {
+        let (Some(play), Some(deal)) = (&self.play, deal) else {
+            if let (Some(claim), Some(entered)) = (self.claim, self.tricks) {
+                if claim != entered {
+                    return Err(PlayError::TricksMismatch { entered, played: claim }.into());
+                }
+            }
+            return Ok(self);
+        };
+        let Some(contract) = self.contract else {
+            return Err(PlayError::NoContract.into());
+        };
+        let Some(declarer) = self.declarer else {
+            return Err(BoardError::IncompleteResult);
+        };
+        let record = replay_play(deal, &contract, declarer, play)?;
+        if let (Some(given), Some(played)) = (self.opening_lead, play.first().copied()) {
+            if given != played {
+                return Err(PlayError::OpeningLeadMismatch { given, played }.into());
+            }
+        }
+        let tricks = record.declarer_total(self.claim)?;
+        if let Some(entered) = self.tricks.filter(|entered| *entered != tricks) {
+            return Err(PlayError::TricksMismatch { entered, played: tricks }.into());
+        }
+        self.opening_lead = self.opening_lead.or(play.first().copied());
+        self.tricks = Some(tricks);
+        Ok(self)
+    }
+}
+
+impl From<BoardResult> for NewBoardResultDTO {
+    fn from(result: BoardResult) -> Self {
+        NewBoardResultDTO {
+            room: result.room,
+            ns_pair: result.ns_pair,
+            ew_pair: result.ew_pair,
+            contract: result.contract,
+            declarer: result.declarer,
+            opening_lead: result.opening_lead,
+            tricks: result.tricks,
+            auction: result.auction,
+            play: result.play,
+            claim: result.claim,
+            adjustment: result.adjustment,
+        }
+    }
+}
+
+fn build_board(session_id: &ObjectId, board: NewBoardDTO) -> Result<BoardMongoDTO, BoardError> {
+    let dealer = board.dealer.unwrap_or(dealer_for_board(board.board_number)?);
+    let vulnerability = board
+        .vulnerability
+        .unwrap_or(vulnerability_for_board(board.board_number)?);
+    Ok(BoardMongoDTO {
+        id: ObjectId::new(),
+        session_id: *session_id,
+        board_number: board.board_number,
+        dealer,
+        vulnerability,
+        deal: board.deal,
+        results: score_results(board.results, dealer, vulnerability, board.deal.as_ref())?,
+        dd_table: None,
+    })
+}
+
+/// Checks each result's auction and play before scoring it.
+fn score_results(
+    results: Vec<NewBoardResultDTO>,
+    dealer: Seat,
+    vulnerability: Vulnerability,
+    deal: Option<&Deal>,
+) -> Result<Vec<BoardResult>, BoardError> {
+    results
+        .into_iter()
+        .map(|result| {
+            let result = result.with_auction(dealer)?.with_play(deal)?;
+            BoardResult::score_result(result, vulnerability)
+        })
+        .collect()
+}
+
+fn boards_collection(db: &Client) -> Collection<BoardMongoDTO> {
+    db.database("bridge_scorecard_api").collection("boards")
+}
+
+/// MongoDB's error code for a write that breaks a unique index.
+const DUPLICATE_KEY: i32 = 11000;
+
+/// Board numbers are unique within a session, which the checks before each write cannot
+/// guarantee on their own when boards are created concurrently.
+pub async fn ensure_board_indexes(db: &Client) -> Result<(), BoardError> {
+    let index = IndexModel::builder()
+        .keys(doc! { "sessionId": 1, "boardNumber": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    boards_collection(db).create_index(index).await?;
+    Ok(())
+}
+
+/// Reports a write rejected by the board number index as `DuplicateBoardNumber`, given
+/// the number of the board at each position of the write.
+fn duplicate_board_number(
+    error: mongodb::error::Error,
+    board_number: impl Fn(usize) -> u32,
+) -> BoardError {
+    let failed_at = match error.kind.as_ref() {
+        ErrorKind::Write(WriteFailure::WriteError(e)) if e.code == DUPLICATE_KEY => Some(0),
+        ErrorKind::InsertMany(e) => e
+            .write_errors
+            .iter()
+            .flatten()
+            .find(|e| e.code == DUPLICATE_KEY)
+            .map(|e| e.index),
+        _ => None,
+    };
+    match failed_at {
+        Some(index) => BoardError::DuplicateBoardNumber(board_number(index)),
+        None => error.into(),
+    }
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn get_boards_for_session(
+    db: &Client,
+    session_id: &ObjectId,
+) -> Result<Vec<BoardMongoDTO>, BoardError> {
+    let collection = boards_collection(db);
+    let pipeline = vec![
+        doc! { "$match": { "sessionId": session_id } },
+        doc! { "$sort": { "boardNumber": 1 } },
+    ];
+    let mut boards: Vec<BoardMongoDTO> = Vec::new();
+    let mut cursor = collection.aggregate(pipeline).await?;
+    while let Some(document) = cursor.try_next().await? {
+        let board = bson::from_document::<BoardMongoDTO>(document).map_err(|e| {
+            tracing::error!("Error in from_document: {:?}", e);
+            e
+        })?;
+        boards.push(board);
+    }
+    Ok(boards)
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn get_board(
+    db: &Client,
+    session_id: &ObjectId,
+    board_id: &str,
+) -> Result<BoardMongoDTO, BoardError> {
+    let collection = boards_collection(db);
+    let id = ObjectId::parse_str(board_id)?;
+    collection
+        .find_one(doc! { "_id": id, "sessionId": session_id })
+        .await?
+        .ok_or_else(|| BoardError::BoardNotFound(board_id.to_string()))
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn create_board(
+    db: &Client,
+    session_id: &ObjectId,
+    board: NewBoardDTO,
+) -> Result<String, BoardError> {
+    let collection = boards_collection(db);
+    ensure_board_number_free(&collection, session_id, board.board_number).await?;
+    let new_board = build_board(session_id, board)?;
+    collection
+        .insert_one(&new_board)
+        .await
+        .map_err(|e| duplicate_board_number(e, |_| new_board.board_number))?;
+    let inserted_id = new_board.id.to_string();
+    tracing::info!("Created board id: {:?}", inserted_id);
+    Ok(inserted_id)
+}
+
+/// Creates several boards at once, e.g. from an imported file. Nothing is written unless
+/// every board is valid and none of the board numbers is already used in the session.
+#[tracing::instrument(target = "database", skip(db, boards), fields(count = boards.len()))]
+pub async fn create_boards(
+    db: &Client,
+    session_id: &ObjectId,
+    boards: Vec<NewBoardDTO>,
+) -> Result<Vec<String>, BoardError> {
+    let collection = boards_collection(db);
+    let mut seen = std::collections::HashSet::new();
+    for board in &boards {
+        if !seen.insert(board.board_number) {
+            return Err(BoardError::RepeatedBoardNumber(board.board_number));
+        }
+        ensure_board_number_free(&collection, session_id, board.board_number).await?;
+    }
+    let new_boards = boards
+        .into_iter()
+        .map(|board| build_board(session_id, board))
+        .collect::<Result<Vec<_>, _>>()?;
+    if new_boards.is_empty() {
+        return Ok(Vec::new());
+    }
+    collection
+        .insert_many(&new_boards)
+        .await
+        .map_err(|e| duplicate_board_number(e, |index| new_boards[index].board_number))?;
+    let inserted_ids: Vec<String> = new_boards.iter().map(|board| board.id.to_string()).collect();
+    tracing::info!("Created {} boards", inserted_ids.len());
+    Ok(inserted_ids)
+}
+
+/// Writes dealt hand records into a session. Boards that already exist have their deal
+/// replaced and missing boards are created. Results belong to the deal they were played
+/// with, so boards that have any are only re-dealt when `clear_results` allows their
+/// results to be discarded; otherwise nothing is written.
+#[tracing::instrument(target = "database", skip(db, boards), fields(count = boards.len()))]
+pub async fn set_board_deals(
+    db: &Client,
+    session_id: &ObjectId,
+    boards: Vec<NewBoardDTO>,
+    clear_results: bool,
+) -> Result<Vec<String>, BoardError> {
+    let collection = boards_collection(db);
+    let existing = get_boards_for_session(db, session_id).await?;
+    if !clear_results {
+        if let Some(played) = existing.iter().find(|existing| {
+            !existing.results.is_empty()
+                && boards
+                    .iter()
+                    .any(|board| board.board_number == existing.board_number)
+        }) {
+            return Err(BoardError::BoardHasResults(played.board_number));
+        }
+    }
+    let mut board_ids = Vec::new();
+    let mut new_boards = Vec::new();
+    for board in boards {
+        match existing
+            .iter()
+            .find(|existing| existing.board_number == board.board_number)
+        {
+            Some(existing) => {
+                collection
+                    .update_one(
+                        doc! { "_id": existing.id },
+                        doc! { "$set": {
+                            "deal": bson::to_bson(&board.deal)?,
+                            "ddTable": bson::Bson::Null,
+                            "results": [],
+                        } },
+                    )
+                    .await?;
+                board_ids.push(existing.id.to_string());
+            }
+            None => {
+                let new_board = build_board(session_id, board)?;
+                board_ids.push(new_board.id.to_string());
+                new_boards.push(new_board);
+            }
+        }
+    }
+    if !new_boards.is_empty() {
+        collection
+            .insert_many(&new_boards)
+            .await
+            .map_err(|e| duplicate_board_number(e, |index| new_boards[index].board_number))?;
+    }
+    tracing::info!("Dealt {} boards", board_ids.len());
+    Ok(board_ids)
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn update_board(
+    db: &Client,
+    session_id: &ObjectId,
+    board_id: &str,
+    board_update: BoardUpdateDTO,
+) -> Result<(), BoardError> {
+    let collection = boards_collection(db);
+    let board = get_board(db, session_id, board_id).await?;
+    let mut updates = doc! {};
+    if let Some(board_number) = board_update.board_number {
+        if board_number == 0 {
+            return Err(BoardError::InvalidBoardNumber(board_number));
+        }
+        if board_number != board.board_number {
+            ensure_board_number_free(&collection, session_id, board_number).await?;
+        }
+        updates.insert("boardNumber", board_number);
+    }
+    let (dealer, vulnerability) = board.updated_dealing(&board_update)?;
+    if dealer != board.dealer {
+        updates.insert("dealer", bson::to_bson(&dealer)?);
+    }
+    if vulnerability != board.vulnerability {
+        updates.insert("vulnerability", bson::to_bson(&vulnerability)?);
+    }
+    let deal_changed = board_update.deal.is_some() && board_update.deal != board.deal;
+    if let Some(deal) = &board_update.deal {
+        updates.insert("deal", bson::to_bson(deal)?);
+        updates.insert("ddTable", bson::Bson::Null);
+    }
+    let deal = board_update.deal.as_ref().or(board.deal.as_ref());
+    // Auctions are checked against the dealer, play against the deal and scores depend on
+    // vulnerability, so the results are checked and scored again when any of them change.
+    // A result that no longer fits refuses the change.
+    let results = match board_update.results {
+        Some(results) => Some(score_results(results, dealer, vulnerability, deal)?),
+        None if deal_changed
+            || dealer != board.dealer
+            || vulnerability != board.vulnerability =>
+        {
+            let results = board.results.into_iter().map(Into::into).collect();
+            Some(score_results(results, dealer, vulnerability, deal)?)
+        }
+        None => None,
+    };
+    if let Some(results) = results {
+        updates.insert("results", bson::to_bson(&results)?);
+    }
+    if updates.is_empty() {
+        return Ok(());
+    }
+    let board_number = board_update.board_number.unwrap_or(board.board_number);
+    let update: Document = doc! { "$set": updates };
+    collection
+        .update_one(doc! { "_id": board.id }, update)
+        .await
+        .map_err(|e| duplicate_board_number(e, |_| board_number))?;
+    tracing::info!("Updated board id: {:?}", board.id);
+    Ok(())
+}
+
+/// Caches a board's double-dummy table, unless its deal changed while it was being solved.
+#[tracing::instrument(target = "database", skip(db, deal, dd_table))]
+pub async fn set_double_dummy_table(
+    db: &Client,
+    board_id: &ObjectId,
+    deal: &Deal,
+    dd_table: &DoubleDummyTable,
+) -> Result<(), BoardError> {
+    let collection = boards_collection(db);
+    collection
+        .update_one(
+            doc! { "_id": board_id, "deal": bson::to_bson(deal)? },
+            doc! { "$set": { "ddTable": bson::to_bson(dd_table)? } },
+        )
+        .await?;
+    tracing::info!("Stored double-dummy table for board id: {:?}", board_id);
+    Ok(())
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn delete_board(
+    db: &Client,
+    session_id: &ObjectId,
+    board_id: &str,
+) -> Result<(), BoardError> {
+    let collection = boards_collection(db);
+    let id = ObjectId::parse_str(board_id)?;
+    let result = collection
+        .delete_one(doc! { "_id": id, "sessionId": session_id })
+        .await?;
+    if result.deleted_count == 0 {
+        return Err(BoardError::BoardNotFound(board_id.to_string()));
+    }
+    tracing::info!("Deleted board id: {:?}", id);
+    Ok(())
+}
+
+async fn ensure_board_number_free(
+    collection: &Collection<BoardMongoDTO>,
+    session_id: &ObjectId,
+    board_number: u32,
+) -> Result<(), BoardError> {
+    let existing = collection
+        .count_documents(doc! { "sessionId": session_id, "boardNumber": board_number })
+        .await?;
+    if existing > 0 {
+        return Err(BoardError::DuplicateBoardNumber(board_number));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(value: serde_json::Value) -> NewBoardResultDTO {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn passed_out_board_scores_nothing() {
+        let passed_out = result(serde_json::json!({
+            "contract": null, "declarer": null, "openingLead": null, "tricks": null
+        }));
+        let scored = BoardResult::score_result(passed_out, Vulnerability::Both).unwrap();
+        assert_eq!(scored.score, 0);
+        assert!(scored.contract.is_none());
+    }
+
+    #[test]
+    fn passed_out_board_has_no_declarer_or_tricks() {
+        for (declarer, tricks) in [(Some("NORTH"), None), (None, Some(7))] {
+            let passed_out = serde_json::json!({
+                "contract": null, "declarer": declarer, "openingLead": null, "tricks": tricks
+            });
+            assert!(matches!(
+                BoardResult::score_result(result(passed_out), Vulnerability::None),
+                Err(BoardError::PassedOutWithResult)
+            ));
+        }
+    }
+
+    #[test]
+    fn contract_needs_declarer_and_tricks() {
+        let incomplete = result(serde_json::json!({
+            "contract": "4S", "declarer": "SOUTH", "openingLead": null, "tricks": null
+        }));
+        assert!(matches!(
+            BoardResult::score_result(incomplete, Vulnerability::None),
+            Err(BoardError::IncompleteResult)
+        ));
+    }
+
+    #[test]
+    fn dealer_and_vulnerability_follow_board_number() {
+        assert_eq!(dealer_for_board(1).unwrap(), Seat::North);
+        assert_eq!(dealer_for_board(6).unwrap(), Seat::East);
+        assert_eq!(vulnerability_for_board(1).unwrap(), Vulnerability::None);
+        assert_eq!(vulnerability_for_board(4).unwrap(), Vulnerability::Both);
+        assert_eq!(vulnerability_for_board(13).unwrap(), Vulnerability::Both);
+        assert_eq!(vulnerability_for_board(17).unwrap(), Vulnerability::None);
+        assert!(matches!(dealer_for_board(0), Err(BoardError::InvalidBoardNumber(0))));
+    }
+
+    fn assigned(scores: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "contract": "3NT", "declarer": "NORTH", "openingLead": null, "tricks": 9,
+            "adjustment": { "kind": "ASSIGNED", "scores": scores }
+        })
+    }
+
+    #[test]
+    fn assigned_scores_are_scored_and_keep_the_table_result() {
+        let scores = serde_json::json!([
+            { "weight": 30.0, "contract": "4S", "declarer": "NORTH", "tricks": 10 },
+            { "weight": 50.0, "contract": "4S", "declarer": "NORTH", "tricks": 9 },
+            { "weight": 20.0, "contract": null, "declarer": null, "tricks": null }
+        ]);
+        let scored =
+            BoardResult::score_result(result(assigned(scores)), Vulnerability::Ns).unwrap();
+        assert_eq!(scored.score, 600);
+        assert_eq!(scored.tricks, Some(9));
+        assert!(scored.artificial().is_none());
+        assert_eq!(
+            scored.weighted_scores(),
+            vec![(0.3, 620), (0.5, -100), (0.2, 0)]
+        );
+    }
+
+    #[test]
+    fn assigned_weights_must_add_up_to_100() {
+        let cases = [
+            serde_json::json!([
+                { "weight": 50.0, "contract": "4S", "declarer": "NORTH", "tricks": 10 }
+            ]),
+            serde_json::json!([
+                { "weight": 110.0, "contract": "4S", "declarer": "NORTH", "tricks": 10 },
+                { "weight": -10.0, "contract": "4S", "declarer": "NORTH", "tricks": 9 }
+            ]),
+            serde_json::json!([
+                { "weight": 100.0, "contract": "4S", "declarer": "NORTH", "tricks": 10 },
+                { "weight": 0.0, "contract": "4S", "declarer": "NORTH", "tricks": 9 }
+            ]),
+        ];
+        for scores in cases {
+            assert!(matches!(
+                BoardResult::score_result(result(assigned(scores)), Vulnerability::None),
+                Err(BoardError::InvalidAdjustmentWeights)
+            ));
+        }
+    }
+
+    #[test]
+    fn assigned_outcomes_must_be_complete() {
+        let scores = serde_json::json!([
+            { "weight": 100.0, "contract": "4S", "declarer": "NORTH", "tricks": null }
+        ]);
+        assert!(matches!(
+            BoardResult::score_result(result(assigned(scores)), Vulnerability::None),
+            Err(BoardError::IncompleteResult)
+        ));
+    }
+
+    #[test]
+    fn artificial_scores_keep_the_table_score() {
+        let split = result(serde_json::json!({
+            "contract": "4S", "declarer": "SOUTH", "openingLead": null, "tricks": 10,
+            "adjustment": { "kind": "ARTIFICIAL", "ns": "AVERAGE_MINUS", "ew": "AVERAGE_PLUS" }
+        }));
+        let scored = BoardResult::score_result(split, Vulnerability::None).unwrap();
+        assert_eq!(scored.score, 420);
+        assert_eq!(
+            scored.artificial(),
+            Some((ArtificialScore::AverageMinus, ArtificialScore::AveragePlus))
+        );
+        assert_eq!(scored.weighted_scores(), vec![(1.0, 420)]);
+        assert_eq!(ArtificialScore::AverageMinus.percentage(), 40.0);
+        assert_eq!(ArtificialScore::AveragePlus.imps(), 3);
+    }
+
+    const DEAL: &str = "N:AKQJ.AKQ.AKQ.AKQ T98.JT9.JT9.JT98 765432.8765432.. ..8765432.765432";
+
+    fn played(fields: serde_json::Value) -> NewBoardResultDTO {
+        let mut value = serde_json::json!({
+            "contract": "3NT", "declarer": "NORTH", "openingLead": null, "tricks": null,
+            "play": ["CJ", "S2", "C7", "CA", "SA", "S8", "S3", "D2"],
+            "claim": 13
+        });
+        for (key, field) in fields.as_object().unwrap() {
+            value[key] = field.clone();
+        }
+        result(value)
+    }
+
+    #[test]
+    fn play_fills_in_the_lead_and_tricks() {
+        let deal: Deal = DEAL.parse().unwrap();
+        let checked = played(serde_json::json!({})).with_play(Some(&deal)).unwrap();
+        assert_eq!(checked.tricks, Some(13));
+        assert_eq!(checked.opening_lead, "CJ".parse().ok());
+    }
+
+    #[test]
+    fn play_must_agree_with_the_result() {
+        let deal: Deal = DEAL.parse().unwrap();
+        assert!(matches!(
+            played(serde_json::json!({ "tricks": 12 })).with_play(Some(&deal)),
+            Err(BoardError::PlayError(PlayError::TricksMismatch { entered: 12, played: 13 }))
+        ));
+        assert!(matches!(
+            played(serde_json::json!({ "openingLead": "CT" })).with_play(Some(&deal)),
+            Err(BoardError::PlayError(PlayError::OpeningLeadMismatch { .. }))
+        ));
+        assert!(matches!(
+            played(serde_json::json!({ "claim": null })).with_play(Some(&deal)),
+            Err(BoardError::PlayError(PlayError::IncompletePlay(8)))
+        ));
+        assert!(matches!(
+            played(serde_json::json!({ "claim": 1 })).with_play(Some(&deal)),
+            Err(BoardError::PlayError(PlayError::ImpossibleClaim { .. }))
+        ));
+        assert!(matches!(
+            played(serde_json::json!({ "contract": null, "declarer": null })).with_play(Some(&deal)),
+            Err(BoardError::PlayError(PlayError::NoContract))
+        ));
+    }
+
+    #[test]
+    fn claim_is_checked_against_the_tricks_without_a_deal() {
+        let unchecked = played(serde_json::json!({ "tricks": 13 })).with_play(None).unwrap();
+        assert_eq!(unchecked.tricks, Some(13));
+        assert!(matches!(
+            played(serde_json::json!({ "tricks": 9 })).with_play(None),
+            Err(BoardError::PlayError(PlayError::TricksMismatch { entered: 9, played: 13 }))
+        ));
+    }
+
+    #[test]
+    fn auction_must_agree_with_the_dealer() {
+        let bid = result(serde_json::json!({
+            "contract": null, "declarer": null, "openingLead": null, "tricks": 10,
+            "auction": ["1S", "Pass", "4S", "Pass", "Pass", "Pass"]
+        }));
+        let checked = bid.clone().with_auction(Seat::East).unwrap();
+        assert_eq!(checked.contract, "4S".parse().ok());
+        assert_eq!(checked.declarer, Some(Seat::East));
+        // Moving the dealer moves the declarer the auction gives.
+        assert!(matches!(
+            checked.with_auction(Seat::South),
+            Err(BoardError::AuctionMismatch)
+        ));
+        assert!(bid.with_auction(Seat::South).is_ok());
+    }
+
+    fn board_with(board_number: u32, dealer: Seat, vulnerability: Vulnerability) -> BoardMongoDTO {
+        BoardMongoDTO {
+            id: ObjectId::new(),
+            session_id: ObjectId::new(),
+            board_number,
+            dealer,
+            vulnerability,
+            deal: None,
+            results: Vec::new(),
+            dd_table: None,
+        }
+    }
+
+    fn renumber(board_number: u32) -> BoardUpdateDTO {
+        BoardUpdateDTO {
+            board_number: Some(board_number),
+            dealer: None,
+            vulnerability: None,
+            deal: None,
+            results: None,
+        }
+    }
+
+    #[test]
+    fn renumbering_follows_the_new_number() {
+        let board = board_with(1, Seat::North, Vulnerability::None);
+        assert_eq!(
+            board.updated_dealing(&renumber(2)).unwrap(),
+            (Seat::East, Vulnerability::Ns)
+        );
+        assert_eq!(
+            board.updated_dealing(&renumber(1)).unwrap(),
+            (Seat::North, Vulnerability::None)
+        );
+    }
+
+    #[test]
+    fn renumbering_keeps_a_custom_dealer_and_vulnerability() {
+        let custom = board_with(1, Seat::West, Vulnerability::Both);
+        assert_eq!(
+            custom.updated_dealing(&renumber(2)).unwrap(),
+            (Seat::West, Vulnerability::Both)
+        );
+        let custom_dealer = board_with(1, Seat::South, Vulnerability::None);
+        assert_eq!(
+            custom_dealer.updated_dealing(&renumber(2)).unwrap(),
+            (Seat::South, Vulnerability::Ns)
+        );
+    }
+
+    #[test]
+    fn given_dealer_and_vulnerability_win() {
+        let board = board_with(1, Seat::North, Vulnerability::None);
+        let update = BoardUpdateDTO {
+            dealer: Some(Seat::West),
+            vulnerability: Some(Vulnerability::Ew),
+            ..renumber(2)
+        };
+        assert_eq!(
+            board.updated_dealing(&update).unwrap(),
+            (Seat::West, Vulnerability::Ew)
+        );
+        let unnumbered = BoardUpdateDTO {
+            board_number: None,
+            dealer: Some(Seat::South),
+            ..renumber(1)
+        };
+        assert_eq!(
+            board.updated_dealing(&unnumbered).unwrap(),
+            (Seat::South, Vulnerability::None)
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+use super::contract::Strain;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CardError {
+    #[error("Invalid suit string: {0}")]
+    InvalidSuitString(String),
+    #[error("Invalid rank string: {0}")]
+    InvalidRankString(String),
+    #[error("Invalid card string: {0}")]
+    InvalidCardString(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    /// Suits in the order hands are written: spades first.
+    pub const ALL: [Suit; 4] = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+
+    pub fn index(&self) -> usize {
+        match self {
+            Suit::Spades => 0,
+            Suit::Hearts => 1,
+            Suit::Diamonds => 2,
+            Suit::Clubs => 3,
+        }
+    }
+}
+
+impl From<Suit> for Strain {
+    fn from(suit: Suit) -> Self {
+        match suit {
+            Suit::Clubs => Strain::Clubs,
+            Suit::Diamonds => Strain::Diamonds,
+            Suit::Hearts => Strain::Hearts,
+            Suit::Spades => Strain::Spades,
+        }
+    }
+}
+
+impl Strain {
+    /// The trump suit, or `None` for no trump.
+    pub fn trump(&self) -> Option<Suit> {
+        match self {
+            Strain::Clubs => Some(Suit::Clubs),
+            Strain::Diamonds => Some(Suit::Diamonds),
+            Strain::Hearts => Some(Suit::Hearts),
+            Strain::Spades => Some(Suit::Spades),
+            Strain::NoTrump => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Suit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Suit::Clubs => write!(f, "C"),
+            Suit::Diamonds => write!(f, "D"),
+            Suit::Hearts => write!(f, "H"),
+            Suit::Spades => write!(f, "S"),
+        }
+    }
+}
+
+impl std::str::FromStr for Suit {
+    type Err = CardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "C" => Ok(Suit::Clubs),
+            "D" => Ok(Suit::Diamonds),
+            "H" => Ok(Suit::Hearts),
+            "S" => Ok(Suit::Spades),
+            _ => Err(CardError::InvalidSuitString(s.to_string())),
+        }
+    }
+}
+
+/// Card rank, numbered so that `Two` is 2 and `Ace` is 14.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Rank(u8);
+
+impl Rank {
+    pub const TWO: Rank = Rank(2);
+    pub const TEN: Rank = Rank(10);
+    pub const JACK: Rank = Rank(11);
+    pub const QUEEN: Rank = Rank(12);
+    pub const KING: Rank = Rank(13);
+    pub const ACE: Rank = Rank(14);
+
+    pub fn new(value: u8) -> Option<Rank> {
+        (2..=14).contains(&value).then_some(Rank(value))
+    }
+
+    pub fn value(&self) -> u8 {
+        self.0
+    }
+
+    /// All thirteen ranks, ace first.
+    pub fn all() -> impl Iterator<Item = Rank> {
+        (2..=14).rev().map(Rank)
+    }
+
+    /// Milton Work high card points.
+    pub fn hcp(&self) -> u8 {
+        self.0.saturating_sub(10)
+    }
+}
+
+impl std::fmt::Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            14 => write!(f, "A"),
+            13 => write!(f, "K"),
+            12 => write!(f, "Q"),
+            11 => write!(f, "J"),
+            10 => write!(f, "T"),
+            n => write!(f, "{}", n),
+        }
+    }
+}
+
+impl std::str::FromStr for Rank {
+    type Err = CardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = match s.to_ascii_uppercase().as_str() {
+            "A" => 14,
+            "K" => 13,
+            "Q" => 12,
+            "J" => 11,
+            "T" | "10" => 10,
+            n => n
+                .parse::<u8>()
+                .ok()
+                .filter(|n| (2..=9).contains(n))
+                .ok_or_else(|| CardError::InvalidRankString(s.to_string()))?,
+        };
+        Ok(Rank(value))
+    }
+}
+
+/// A single card, written suit first as in `SA`, `HT` or `C2`.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Card {
+    pub suit: Suit,
+    pub rank: Rank,
+}
+
+impl Card {
+    pub fn new(suit: Suit, rank: Rank) -> Self {
+        Card { suit, rank }
+    }
+}
+
+impl std::fmt::Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.suit, self.rank)
+    }
+}
+
+impl std::str::FromStr for Card {
+    type Err = CardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.len() < 2 || !s.is_ascii() {
+            return Err(CardError::InvalidCardString(s.to_string()));
+        }
+        let (suit, rank) = s.split_at(1);
+        let suit = suit
+            .parse()
+            .map_err(|_| CardError::InvalidCardString(s.to_string()))?;
+        let rank = rank
+            .parse()
+            .map_err(|_| CardError::InvalidCardString(s.to_string()))?;
+        Ok(Card { suit, rank })
+    }
+}
+
+impl TryFrom<String> for Card {
+    type Error = CardError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Card> for String {
+    fn from(card: Card) -> Self {
+        card.to_string()
+    }
+}
@@ -0,0 +1,319 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ContractError {
+    #[error("Invalid contract level: {0}")]
+    InvalidLevel(u8),
+    #[error("Invalid contract string: {0}")]
+    InvalidContractString(String),
+    #[error("Invalid strain string: {0}")]
+    InvalidStrainString(String),
+    #[error("Invalid seat string: {0}")]
+    InvalidSeatString(String),
+    #[error("Invalid vulnerability string: {0}")]
+    InvalidVulnerabilityString(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Strain {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+    NoTrump,
+}
+
+impl Strain {
+    pub const ALL: [Strain; 5] = [
+        Strain::Clubs,
+        Strain::Diamonds,
+        Strain::Hearts,
+        Strain::Spades,
+        Strain::NoTrump,
+    ];
+
+    pub fn is_minor(&self) -> bool {
+        matches!(self, Strain::Clubs | Strain::Diamonds)
+    }
+
+    pub fn is_major(&self) -> bool {
+        matches!(self, Strain::Hearts | Strain::Spades)
+    }
+}
+
+impl std::fmt::Display for Strain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Strain::Clubs => write!(f, "C"),
+            Strain::Diamonds => write!(f, "D"),
+            Strain::Hearts => write!(f, "H"),
+            Strain::Spades => write!(f, "S"),
+            Strain::NoTrump => write!(f, "NT"),
+        }
+    }
+}
+
+impl std::str::FromStr for Strain {
+    type Err = ContractError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "C" => Ok(Strain::Clubs),
+            "D" => Ok(Strain::Diamonds),
+            "H" => Ok(Strain::Hearts),
+            "S" => Ok(Strain::Spades),
+            "N" | "NT" => Ok(Strain::NoTrump),
+            _ => Err(ContractError::InvalidStrainString(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq, Hash, Default)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Doubled {
+    #[default]
+    Undoubled,
+    Doubled,
+    Redoubled,
+}
+
+impl std::fmt::Display for Doubled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Doubled::Undoubled => write!(f, ""),
+            Doubled::Doubled => write!(f, "X"),
+            Doubled::Redoubled => write!(f, "XX"),
+        }
+    }
+}
+
+/// A contract as it is written on a traveller, e.g. `4S`, `3NTX` or `6HXX`.
+/// Stored as that string so the documents stay readable.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Contract {
+    pub level: u8,
+    pub strain: Strain,
+    pub doubled: Doubled,
+}
+
+impl Contract {
+    pub fn new(level: u8, strain: Strain, doubled: Doubled) -> Result<Self, ContractError> {
+        if !(1..=7).contains(&level) {
+            return Err(ContractError::InvalidLevel(level));
+        }
+        Ok(Contract {
+            level,
+            strain,
+            doubled,
+        })
+    }
+
+    /// Number of tricks declarer needs to make the contract.
+    pub fn tricks_required(&self) -> u8 {
+        self.level + 6
+    }
+}
+
+impl std::fmt::Display for Contract {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.level, self.strain, self.doubled)
+    }
+}
+
+impl std::str::FromStr for Contract {
+    type Err = ContractError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ContractError::InvalidContractString(s.to_string());
+        let upper = s.trim().to_ascii_uppercase();
+        let mut chars = upper.chars();
+        let level = chars
+            .next()
+            .and_then(|c| c.to_digit(10))
+            .ok_or_else(invalid)? as u8;
+        let rest = chars.as_str();
+        let doubled_at = rest.find('X').unwrap_or(rest.len());
+        let (strain, doubled) = rest.split_at(doubled_at);
+        let strain: Strain = strain.parse().map_err(|_| invalid())?;
+        let doubled = match doubled {
+            "" => Doubled::Undoubled,
+            "X" => Doubled::Doubled,
+            "XX" => Doubled::Redoubled,
+            _ => return Err(invalid()),
+        };
+        Contract::new(level, strain, doubled).map_err(|_| invalid())
+    }
+}
+
+impl TryFrom<String> for Contract {
+    type Error = ContractError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Contract> for String {
+    fn from(contract: Contract) -> Self {
+        contract.to_string()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Seat {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Seat {
+    pub const ALL: [Seat; 4] = [Seat::North, Seat::East, Seat::South, Seat::West];
+
+    pub fn index(&self) -> usize {
+        match self {
+            Seat::North => 0,
+            Seat::East => 1,
+            Seat::South => 2,
+            Seat::West => 3,
+        }
+    }
+
+    pub fn from_index(index: usize) -> Seat {
+        Seat::ALL[index % 4]
+    }
+
+    /// The next player clockwise.
+    pub fn next(&self) -> Seat {
+        Seat::from_index(self.index() + 1)
+    }
+
+    pub fn partner(&self) -> Seat {
+        Seat::from_index(self.index() + 2)
+    }
+
+    pub fn is_north_south(&self) -> bool {
+        matches!(self, Seat::North | Seat::South)
+    }
+}
+
+impl std::fmt::Display for Seat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Seat::North => write!(f, "N"),
+            Seat::East => write!(f, "E"),
+            Seat::South => write!(f, "S"),
+            Seat::West => write!(f, "W"),
+        }
+    }
+}
+
+impl std::str::FromStr for Seat {
+    type Err = ContractError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "N" | "NORTH" => Ok(Seat::North),
+            "E" | "EAST" => Ok(Seat::East),
+            "S" | "SOUTH" => Ok(Seat::South),
+            "W" | "WEST" => Ok(Seat::West),
+            _ => Err(ContractError::InvalidSeatString(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Vulnerability {
+    None,
+    Ns,
+    Ew,
+    Both,
+}
+
+impl Vulnerability {
+    pub fn is_vulnerable(&self, seat: Seat) -> bool {
+        match self {
+            Vulnerability::None => false,
+            Vulnerability::Ns => seat.is_north_south(),
+            Vulnerability::Ew => !seat.is_north_south(),
+            Vulnerability::Both => true,
+        }
+    }
+}
+
+impl std::fmt::Display for Vulnerability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Vulnerability::None => write!(f, "None"),
+            Vulnerability::Ns => write!(f, "NS"),
+            Vulnerability::Ew => write!(f, "EW"),
+            Vulnerability::Both => write!(f, "Both"),
+        }
+    }
+}
+
+impl std::str::FromStr for Vulnerability {
+    type Err = ContractError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "NONE" | "LOVE" | "-" => Ok(Vulnerability::None),
+            "NS" => Ok(Vulnerability::Ns),
+            "EW" => Ok(Vulnerability::Ew),
+            "BOTH" | "ALL" => Ok(Vulnerability::Both),
+            _ => Err(ContractError::InvalidVulnerabilityString(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_contracts() {
+        let contract: Contract = "3ntx".parse().unwrap();
+        assert_eq!(
+            contract,
+            Contract::new(3, Strain::NoTrump, Doubled::Doubled).unwrap()
+        );
+        assert_eq!(contract.to_string(), "3NTX");
+        assert_eq!(
+            "6HXX".parse::<Contract>().unwrap().doubled,
+            Doubled::Redoubled
+        );
+        assert_eq!("1N".parse::<Contract>().unwrap().to_string(), "1NT");
+        assert_eq!("7C".parse::<Contract>().unwrap().tricks_required(), 13);
+    }
+
+    #[test]
+    fn rejects_invalid_contracts() {
+        for invalid in ["", "0S", "8C", "4Z", "4SXXX", "S4", "Pass"] {
+            assert!(
+                matches!(
+                    invalid.parse::<Contract>(),
+                    Err(ContractError::InvalidContractString(_))
+                ),
+                "{invalid}"
+            );
+        }
+        assert!(matches!(
+            Contract::new(0, Strain::Clubs, Doubled::Undoubled),
+            Err(ContractError::InvalidLevel(0))
+        ));
+    }
+
+    #[test]
+    fn vulnerability_by_seat() {
+        assert!(Vulnerability::Ns.is_vulnerable(Seat::South));
+        assert!(!Vulnerability::Ns.is_vulnerable(Seat::East));
+        assert!(Vulnerability::Ew.is_vulnerable(Seat::West));
+        assert!(Vulnerability::Both.is_vulnerable(Seat::North));
+        assert!(!Vulnerability::None.is_vulnerable(Seat::North));
+    }
+}
@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    card::{Card, Rank, Suit},
+    contract::Seat,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DealError {
+    #[error("Invalid deal string: {0}")]
+    InvalidDealString(String),
+    #[error("{0} holds {1} cards instead of 13")]
+    WrongCardCount(Seat, usize),
+    #[error("{0} appears more than once in the deal")]
+    DuplicateCard(Card),
+}
+
+/// A hand as one bitmask per suit, with bit `n` set when the card of rank `n` is held.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Hand {
+    suits: [u16; 4],
+}
+
+impl Hand {
+    pub fn contains(&self, card: Card) -> bool {
+        self.suits[card.suit.index()] & (1 << card.rank.value()) != 0
+    }
+
+    /// Adds a card, returning `false` if it was already there.
+    pub fn insert(&mut self, card: Card) -> bool {
+        let had = self.contains(card);
+        self.suits[card.suit.index()] |= 1 << card.rank.value();
+        !had
+    }
+
+    /// Removes a card, returning `false` if it was not there.
+    pub fn remove(&mut self, card: Card) -> bool {
+        let had = self.contains(card);
+        self.suits[card.suit.index()] &= !(1 << card.rank.value());
+        had
+    }
+
+    pub fn len(&self) -> usize {
+        self.suits.iter().map(|s| s.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn suit_length(&self, suit: Suit) -> usize {
+        self.suits[suit.index()].count_ones() as usize
+    }
+
+    /// Raw rank bitmask for a suit.
+    pub fn suit_mask(&self, suit: Suit) -> u16 {
+        self.suits[suit.index()]
+    }
+
+    /// Cards held in a suit, highest first.
+    pub fn cards_in(&self, suit: Suit) -> impl Iterator<Item = Card> + '_ {
+        Rank::all()
+            .map(move |rank| Card::new(suit, rank))
+            .filter(move |card| self.contains(*card))
+    }
+
+    /// All cards, spades first and highest first within a suit.
+    pub fn cards(&self) -> impl Iterator<Item = Card> + '_ {
+        Suit::ALL.into_iter().flat_map(move |suit| self.cards_in(suit))
+    }
+
+    pub fn hcp(&self) -> u8 {
+        self.cards().map(|card| card.rank.hcp()).sum()
+    }
+
+    fn parse_pbn(s: &str) -> Option<Hand> {
+        let suits: Vec<&str> = s.split('.').collect();
+        if suits.len() != 4 {
+            return None;
+        }
+        let mut hand = Hand::default();
+        for (suit, holding) in Suit::ALL.into_iter().zip(suits) {
+            for c in holding.chars() {
+                let rank: Rank = c.to_string().parse().ok()?;
+                if !hand.insert(Card::new(suit, rank)) {
+                    return None;
+                }
+            }
+        }
+        Some(hand)
+    }
+
+    /// Written the PBN way: `AKQ2.T9.J8765.3`, with empty suits left blank.
+    pub fn to_pbn(&self) -> String {
+        Suit::ALL
+            .into_iter()
+            .map(|suit| {
+                self.cards_in(suit)
+                    .map(|card| card.rank.to_string())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+/// The four hands of a board. Stored as a PBN deal string, e.g.
+/// `N:AKQ2.T9.J8765.3 ...`, with North's hand first.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Deal {
+    hands: [Hand; 4],
+}
+
+impl Deal {
+    /// Builds a deal, checking that every hand holds 13 cards and no card is dealt twice.
+    pub fn new(hands: [Hand; 4]) -> Result<Self, DealError> {
+        let mut seen = Hand::default();
+        for seat in Seat::ALL {
+            let hand = &hands[seat.index()];
+            if hand.len() != 13 {
+                return Err(DealError::WrongCardCount(seat, hand.len()));
+            }
+            for card in hand.cards() {
+                if !seen.insert(card) {
+                    return Err(DealError::DuplicateCard(card));
+                }
+            }
+        }
+        Ok(Deal { hands })
+    }
+
+    pub fn hand(&self, seat: Seat) -> &Hand {
+        &self.hands[seat.index()]
+    }
+
+    pub fn hands(&self) -> &[Hand; 4] {
+        &self.hands
+    }
+
+    /// Who holds a card.
+    pub fn holder(&self, card: Card) -> Seat {
+        Seat::ALL
+            .into_iter()
+            .find(|seat| self.hand(*seat).contains(card))
+            .expect("a valid deal holds every card")
+    }
+
+    /// PBN deal string listing the hands clockwise from `first`.
+    pub fn to_pbn(&self, first: Seat) -> String {
+        let hands = (0..4)
+            .map(|i| self.hand(Seat::from_index(first.index() + i)).to_pbn())
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!("{}:{}", first, hands)
+    }
+}
+
+impl std::fmt::Display for Deal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_pbn(Seat::North))
+    }
+}
+
+impl std::str::FromStr for Deal {
+    type Err = DealError;
+
+    /// Parses a PBN deal string. One hand may be given as `-`, in which case it is
+    /// made up of the cards missing from the other three.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DealError::InvalidDealString(s.to_string());
+        let (first, hands) = s.trim().split_once(':').ok_or_else(invalid)?;
+        let first: Seat = first.parse().map_err(|_| invalid())?;
+        let hands: Vec<&str> = hands.split_whitespace().collect();
+        if hands.len() != 4 {
+            return Err(invalid());
+        }
+        let mut parsed = [Hand::default(); 4];
+        let mut missing = None;
+        for (i, hand) in hands.into_iter().enumerate() {
+            let seat = Seat::from_index(first.index() + i);
+            if hand == "-" {
+                if missing.is_some() {
+                    return Err(invalid());
+                }
+                missing = Some(seat);
+            } else {
+                parsed[seat.index()] = Hand::parse_pbn(hand).ok_or_else(invalid)?;
+            }
+        }
+        if let Some(missing) = missing {
+            let mut rest = Hand::default();
+            for suit in Suit::ALL {
+                for card in Rank::all().map(|rank| Card::new(suit, rank)) {
+                    if parsed.iter().all(|hand| !hand.contains(card)) {
+                        rest.insert(card);
+                    }
+                }
+            }
+            parsed[missing.index()] = rest;
+        }
+        Deal::new(parsed)
+    }
+}
+
+impl TryFrom<String> for Deal {
+    type Error = DealError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Deal> for String {
+    fn from(deal: Deal) -> Self {
+        deal.to_string()
+    }
+}
+
+/// Who wins a trick: the highest trump if any were played, otherwise the highest card
+/// of the suit led. `cards` are in the order they were played, starting with `leader`.
+pub fn trick_winner(leader: Seat, cards: &[Card], trump: Option<Suit>) -> Seat {
+    let mut winner = 0;
+    for (i, card) in cards.iter().enumerate().skip(1) {
+        let best = cards[winner];
+        let beats = if card.suit == best.suit {
+            card.rank > best.rank
+        } else {
+            Some(card.suit) == trump
+        };
+        if beats {
+            winner = i;
+        }
+    }
+    Seat::from_index(leader.index() + winner)
+}
@@ -1,2 +1,14 @@
 pub mod user;
-pub mod session;
\ No newline at end of file
+pub mod session;
+pub mod contract;
+pub mod scoring;
+pub mod card;
+pub mod adjustment;
+pub mod auction;
+pub mod play;
+pub mod board;
+pub mod deal;
+pub mod summary;
+pub mod player;
+pub mod standings;
+pub mod ruling;
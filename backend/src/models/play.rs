@@ -0,0 +1,289 @@
+//! The play of the hand, card by card, replayed against the deal. Each card must come
+//! from the hand whose turn it is, the winner of a trick leads to the next, and players
+//! follow suit when they can. Play that stops early needs a claim or concession.
+use serde::Serialize;
+
+use super::{
+    card::{Card, Suit},
+    contract::{Contract, Seat},
+    deal::{trick_winner, Deal, Hand},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PlayError {
+    #[error("A passed-out board has no play")]
+    NoContract,
+    #[error("Card {position}: {card} has already been played")]
+    CardPlayedTwice { position: usize, card: Card },
+    #[error("Card {position}: it is {seat}'s turn to play, but {card} is in {holder}'s hand")]
+    WrongHand {
+        position: usize,
+        seat: Seat,
+        holder: Seat,
+        card: Card,
+    },
+    #[error("Card {position}: {seat} played {card} while holding a {suit} to follow suit")]
+    DidNotFollowSuit {
+        position: usize,
+        seat: Seat,
+        card: Card,
+        suit: Suit,
+    },
+    #[error("The opening lead {given} is not the first card played, {played}")]
+    OpeningLeadMismatch { given: Card, played: Card },
+    #[error("A claim of {claim} tricks is impossible with {won} won and {remaining} to play")]
+    ImpossibleClaim { claim: u8, won: u8, remaining: u8 },
+    #[error("Play stops after {0} cards without a claim or concession")]
+    IncompletePlay(usize),
+    #[error("The result of {entered} tricks does not match the {played} taken in the play")]
+    TricksMismatch { entered: u8, played: u8 },
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Trick {
+    pub leader: Seat,
+    pub cards: Vec<Card>,
+    /// `None` while the trick is incomplete.
+    pub winner: Option<Seat>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayRecord {
+    pub tricks: Vec<Trick>,
+    pub declarer_tricks: u8,
+    pub defence_tricks: u8,
+}
+
+impl PlayRecord {
+    /// Tricks not yet won by either side, counting one in progress.
+    pub fn remaining(&self) -> u8 {
+        13 - self.declarer_tricks - self.defence_tricks
+    }
+
+    /// Declarer's tricks at the end of the hand: the claim or concession if play stopped
+    /// early, which must fit the tricks still to play, and otherwise those won in play.
+    pub fn declarer_total(&self, claim: Option<u8>) -> Result<u8, PlayError> {
+        match claim {
+            Some(claim)
+                if claim < self.declarer_tricks
+                    || claim > self.declarer_tricks + self.remaining() =>
+            {
+                Err(PlayError::ImpossibleClaim {
+                    claim,
+                    won: self.declarer_tricks,
+                    remaining: self.remaining(),
+                })
+            }
+            Some(claim) => Ok(claim),
+            None if self.remaining() == 0 => Ok(self.declarer_tricks),
+            None => Err(PlayError::IncompletePlay(
+                self.tricks.iter().map(|trick| trick.cards.len()).sum(),
+            )),
+        }
+    }
+}
+
+/// Replays the cards in the order they were played, with declarer's left-hand opponent
+/// on lead.
+pub fn replay_play(
+    deal: &Deal,
+    contract: &Contract,
+    declarer: Seat,
+    play: &[Card],
+) -> Result<PlayRecord, PlayError> {
+    let trump = contract.strain.trump();
+    let mut hands: [Hand; 4] = *deal.hands();
+    let mut record = PlayRecord {
+        tricks: Vec::new(),
+        declarer_tricks: 0,
+        defence_tricks: 0,
+    };
+    let mut leader = declarer.next();
+    for (trick_index, cards) in play.chunks(4).enumerate() {
+        for (i, card) in cards.iter().enumerate() {
+            let position = trick_index * 4 + i + 1;
+            let seat = Seat::from_index(leader.index() + i);
+            let hand = &mut hands[seat.index()];
+            if !hand.contains(*card) {
+                let holder = deal.holder(*card);
+                return Err(if play[..position - 1].contains(card) {
+                    PlayError::CardPlayedTwice {
+                        position,
+                        card: *card,
+                    }
+                } else {
+                    PlayError::WrongHand {
+                        position,
+                        seat,
+                        holder,
+                        card: *card,
+                    }
+                });
+            }
+            let led = cards[0].suit;
+            if i > 0 && card.suit != led && hand.suit_length(led) > 0 {
+                return Err(PlayError::DidNotFollowSuit {
+                    position,
+                    seat,
+                    card: *card,
+                    suit: led,
+                });
+            }
+            hand.remove(*card);
+        }
+        let winner = (cards.len() == 4).then(|| trick_winner(leader, cards, trump));
+        record.tricks.push(Trick {
+            leader,
+            cards: cards.to_vec(),
+            winner,
+        });
+        if let Some(winner) = winner {
+            if winner == declarer || winner == declarer.partner() {
+                record.declarer_tricks += 1;
+            } else {
+                record.defence_tricks += 1;
+            }
+            leader = winner;
+        }
+    }
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// North holds the top three of every suit and the jack of spades; South and West
+    /// have two suits each.
+    const DEAL: &str = "N:AKQJ.AKQ.AKQ.AKQ T98.JT9.JT9.JT98 765432.8765432.. ..8765432.765432";
+
+    fn replay(cards: &str) -> Result<PlayRecord, PlayError> {
+        let deal: Deal = DEAL.parse().unwrap();
+        let cards: Vec<Card> = cards
+            .split_whitespace()
+            .map(|card| card.parse().unwrap())
+            .collect();
+        replay_play(&deal, &"3NT".parse().unwrap(), Seat::North, &cards)
+    }
+
+    fn card(card: &str) -> Card {
+        card.parse().unwrap()
+    }
+
+    #[test]
+    fn winner_of_each_trick_leads_to_the_next() {
+        let record = replay("CJ S2 C7 CA SA S8 S3 D2 HA").unwrap();
+        assert_eq!(record.tricks.len(), 3);
+        assert_eq!(record.tricks[0].leader, Seat::East);
+        assert_eq!(record.tricks[0].winner, Some(Seat::North));
+        assert_eq!(record.tricks[1].leader, Seat::North);
+        assert_eq!(record.tricks[2].winner, None);
+        assert_eq!((record.declarer_tricks, record.defence_tricks), (2, 0));
+        assert_eq!(record.remaining(), 11);
+    }
+
+    #[test]
+    fn trumps_win_for_the_defence() {
+        let deal: Deal = DEAL.parse().unwrap();
+        let record = replay_play(
+            &deal,
+            &"4S".parse().unwrap(),
+            Seat::East,
+            &[card("S2"), card("D2"), card("SA"), card("S8")],
+        )
+        .unwrap();
+        assert_eq!(record.tricks[0].leader, Seat::South);
+        assert_eq!(record.tricks[0].winner, Some(Seat::North));
+        assert_eq!((record.declarer_tricks, record.defence_tricks), (0, 1));
+    }
+
+    #[test]
+    fn card_must_come_from_the_hand_on_play() {
+        match replay("S2") {
+            Err(PlayError::WrongHand {
+                position,
+                seat,
+                holder,
+                card: played,
+            }) => {
+                assert_eq!((position, seat, holder), (1, Seat::East, Seat::South));
+                assert_eq!(played, card("S2"));
+            }
+            other => panic!("{other:?}"),
+        }
+        assert!(matches!(
+            replay("CJ S2 C7 CA SA S8 S3 DA"),
+            Err(PlayError::WrongHand {
+                position: 8,
+                seat: Seat::West,
+                holder: Seat::North,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn players_follow_suit() {
+        match replay("CJ S2 D2") {
+            Err(PlayError::DidNotFollowSuit {
+                position,
+                seat,
+                card: played,
+                suit,
+            }) => {
+                assert_eq!((position, seat, suit), (3, Seat::West, Suit::Clubs));
+                assert_eq!(played, card("D2"));
+            }
+            other => panic!("{other:?}"),
+        }
+        // South has no clubs, so may discard.
+        assert!(replay("CJ S2 C7").is_ok());
+    }
+
+    #[test]
+    fn card_cannot_be_played_twice() {
+        assert!(matches!(
+            replay("CJ S2 C7 CA CA"),
+            Err(PlayError::CardPlayedTwice { position: 5, card: played }) if played == card("CA")
+        ));
+    }
+
+    #[test]
+    fn claim_must_fit_the_tricks_left() {
+        let record = replay("CJ S2 C7 CA SA S8 S3 D2 HA").unwrap();
+        assert_eq!(record.declarer_total(Some(13)).unwrap(), 13);
+        assert_eq!(record.declarer_total(Some(2)).unwrap(), 2);
+        assert!(matches!(
+            record.declarer_total(Some(1)),
+            Err(PlayError::ImpossibleClaim {
+                claim: 1,
+                won: 2,
+                remaining: 11
+            })
+        ));
+        assert!(matches!(
+            record.declarer_total(Some(14)),
+            Err(PlayError::ImpossibleClaim { claim: 14, .. })
+        ));
+        assert!(matches!(
+            record.declarer_total(None),
+            Err(PlayError::IncompletePlay(9))
+        ));
+    }
+
+    #[test]
+    fn finished_play_needs_no_claim() {
+        let record = PlayRecord {
+            tricks: Vec::new(),
+            declarer_tricks: 9,
+            defence_tricks: 4,
+        };
+        assert_eq!(record.declarer_total(None).unwrap(), 9);
+        assert!(matches!(
+            record.declarer_total(Some(10)),
+            Err(PlayError::ImpossibleClaim { .. })
+        ));
+    }
+}
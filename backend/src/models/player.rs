@@ -0,0 +1,847 @@
+use std::collections::{HashMap, HashSet};
+
+use async_graphql::{Enum, SimpleObject};
+use bson::{oid::ObjectId, Document};
+use futures::TryStreamExt;
+use mongodb::{bson::doc, Client, Collection};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    contract::Seat,
+    user::{find_user, UserError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PlayerError {
+    #[error("Player {0} not found")]
+    PlayerNotFound(String),
+    #[error("Partnership {0} not found")]
+    PartnershipNotFound(String),
+    #[error("User {0} not found")]
+    UserNotFound(String),
+    #[error("A partnership needs two different players")]
+    SamePlayerTwice,
+    #[error("Player {0} is in a partnership")]
+    PlayerInPartnership(String),
+    #[error("Player {0} is listed more than once in the session")]
+    RepeatedParticipant(String),
+    #[error("Number {0} is used by more than one participant")]
+    RepeatedNumber(u32),
+    #[error("Player {player} cannot sit {seat} for {direction}")]
+    SeatOutsideDirection {
+        player: String,
+        seat: Seat,
+        direction: Direction,
+    },
+    #[error("Player {player} is not in partnership {partnership}")]
+    NotInPartnership { player: String, partnership: String },
+    #[error("Participant {0} has no players")]
+    NoPlayers(u32),
+    #[error("User error: {0}")]
+    UserError(#[from] UserError),
+    #[error("Query error: {0}")]
+    QueryError(#[from] mongodb::error::Error),
+    #[error("Invalid player record: {0}")]
+    InvalidPlayerRecord(#[from] bson::de::Error),
+    #[error("Could not serialize player: {0}")]
+    SerializationError(#[from] bson::ser::Error),
+    #[error("Could not convert {0} to ObjectId")]
+    InvalidObjectId(#[from] bson::oid::Error),
+}
+
+/// Someone who plays in sessions. Players belong to the user keeping the scores and may
+/// be linked to the player's own user account.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerMongoDTO {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub owner: ObjectId,
+    pub name: String,
+    #[serde(default)]
+    pub user_id: Option<ObjectId>,
+    /// Membership number with a national body, for masterpoint reporting.
+    #[serde(default)]
+    pub member_number: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewPlayerDTO {
+    pub name: String,
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub member_number: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerUpdateDTO {
+    pub name: Option<String>,
+    pub user_id: Option<String>,
+    pub member_number: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerJsonDTO {
+    pub id: String,
+    pub owner: String,
+    pub name: String,
+    pub user_id: Option<String>,
+    pub member_number: Option<String>,
+}
+
+impl From<PlayerMongoDTO> for PlayerJsonDTO {
+    fn from(player: PlayerMongoDTO) -> Self {
+        PlayerJsonDTO {
+            id: player.id.to_string(),
+            owner: player.owner.to_string(),
+            name: player.name,
+            user_id: player.user_id.map(|id| id.to_string()),
+            member_number: player.member_number,
+        }
+    }
+}
+
+/// Two players who play together, optionally under a name of their own.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PartnershipMongoDTO {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub owner: ObjectId,
+    pub players: [ObjectId; 2],
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewPartnershipDTO {
+    pub players: [String; 2],
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PartnershipJsonDTO {
+    pub id: String,
+    pub owner: String,
+    pub players: [String; 2],
+    pub name: Option<String>,
+}
+
+impl From<PartnershipMongoDTO> for PartnershipJsonDTO {
+    fn from(partnership: PartnershipMongoDTO) -> Self {
+        PartnershipJsonDTO {
+            id: partnership.id.to_string(),
+            owner: partnership.owner.to_string(),
+            players: partnership.players.map(|id| id.to_string()),
+            name: partnership.name,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Direction {
+    NorthSouth,
+    EastWest,
+}
+
+impl Direction {
+    pub fn of(seat: Seat) -> Direction {
+        if seat.is_north_south() {
+            Direction::NorthSouth
+        } else {
+            Direction::EastWest
+        }
+    }
+
+    pub fn includes(&self, seat: Seat) -> bool {
+        seat.is_north_south() == (*self == Direction::NorthSouth)
+    }
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::NorthSouth => write!(f, "NS"),
+            Direction::EastWest => write!(f, "EW"),
+        }
+    }
+}
+
+/// A pair or team in a session, under the number its board results are recorded with.
+/// Pairs in a Mitchell have a direction; Howell pairs and teams change direction, so they
+/// have none.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Participant {
+    pub number: u32,
+    #[serde(default)]
+    pub direction: Option<Direction>,
+    #[serde(default)]
+    pub partnership_id: Option<ObjectId>,
+    pub players: Vec<SeatedPlayer>,
+    /// Partnership name, or the players' names, as they were when the list was saved.
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SeatedPlayer {
+    pub player_id: ObjectId,
+    #[serde(default)]
+    pub seat: Option<Seat>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewParticipantDTO {
+    pub number: u32,
+    #[serde(default)]
+    pub direction: Option<Direction>,
+    /// Seats the partnership's players when `players` is empty.
+    #[serde(default)]
+    pub partnership_id: Option<String>,
+    #[serde(default)]
+    pub players: Vec<NewSeatedPlayerDTO>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSeatedPlayerDTO {
+    pub player_id: String,
+    #[serde(default)]
+    pub seat: Option<Seat>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantJsonDTO {
+    pub number: u32,
+    pub direction: Option<Direction>,
+    pub partnership_id: Option<String>,
+    pub players: Vec<SeatedPlayerJsonDTO>,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct SeatedPlayerJsonDTO {
+    pub player_id: String,
+    pub seat: Option<Seat>,
+}
+
+impl From<Participant> for ParticipantJsonDTO {
+    fn from(participant: Participant) -> Self {
+        ParticipantJsonDTO {
+            number: participant.number,
+            direction: participant.direction,
+            partnership_id: participant.partnership_id.map(|id| id.to_string()),
+            players: participant
+                .players
+                .into_iter()
+                .map(|player| SeatedPlayerJsonDTO {
+                    player_id: player.player_id.to_string(),
+                    seat: player.seat,
+                })
+                .collect(),
+            name: participant.name,
+        }
+    }
+}
+
+fn players_collection(db: &Client) -> Collection<PlayerMongoDTO> {
+    db.database("bridge_scorecard_api").collection("players")
+}
+
+fn partnerships_collection(db: &Client) -> Collection<PartnershipMongoDTO> {
+    db.database("bridge_scorecard_api")
+        .collection("partnerships")
+}
+
+async fn ensure_user_exists(db: &Client, user_id: &str) -> Result<ObjectId, PlayerError> {
+    let id = ObjectId::parse_str(user_id)?;
+    let found: Vec<ObjectId> = find_user(db, Some(user_id), None, None, None)
+        .await?
+        .into_iter()
+        .map(|user| user.id)
+        .collect();
+    linked_user(id, &found)
+}
+
+/// The user account a player is linked to, which must be among the users `found`.
+fn linked_user(user_id: ObjectId, found: &[ObjectId]) -> Result<ObjectId, PlayerError> {
+    if !found.contains(&user_id) {
+        return Err(PlayerError::UserNotFound(user_id.to_string()));
+    }
+    Ok(user_id)
+}
+
+impl PlayerUpdateDTO {
+    /// The `$set` for the fields given, with the checked user account to link to, if any.
+    fn into_update(self, user_id: Option<ObjectId>) -> Document {
+        let mut updates = doc! {};
+        if let Some(name) = self.name {
+            updates.insert("name", name);
+        }
+        if let Some(user_id) = user_id {
+            updates.insert("userId", user_id);
+        }
+        if let Some(member_number) = self.member_number {
+            updates.insert("memberNumber", member_number);
+        }
+        doc! { "$set": updates }
+    }
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn get_players(
+    db: &Client,
+    owner: &ObjectId,
+) -> Result<Vec<PlayerMongoDTO>, PlayerError> {
+    let collection = players_collection(db);
+    let pipeline = vec![
+        doc! { "$match": { "owner": owner } },
+        doc! { "$sort": { "name": 1 } },
+    ];
+    let mut players: Vec<PlayerMongoDTO> = Vec::new();
+    let mut cursor = collection.aggregate(pipeline).await?;
+    while let Some(document) = cursor.try_next().await? {
+        let player = bson::from_document::<PlayerMongoDTO>(document).map_err(|e| {
+            tracing::error!("Error in from_document: {:?}", e);
+            e
+        })?;
+        players.push(player);
+    }
+    Ok(players)
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn get_player(
+    db: &Client,
+    owner: &ObjectId,
+    player_id: &str,
+) -> Result<PlayerMongoDTO, PlayerError> {
+    let collection = players_collection(db);
+    let id = ObjectId::parse_str(player_id)?;
+    collection
+        .find_one(doc! { "_id": id, "owner": owner })
+        .await?
+        .ok_or_else(|| PlayerError::PlayerNotFound(player_id.to_string()))
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn create_player(
+    db: &Client,
+    owner: &ObjectId,
+    player: NewPlayerDTO,
+) -> Result<String, PlayerError> {
+    let user_id = match &player.user_id {
+        Some(user_id) => Some(ensure_user_exists(db, user_id).await?),
+        None => None,
+    };
+    let new_player = PlayerMongoDTO {
+        id: ObjectId::new(),
+        owner: *owner,
+        name: player.name,
+        user_id,
+        member_number: player.member_number,
+    };
+    players_collection(db).insert_one(&new_player).await?;
+    let inserted_id = new_player.id.to_string();
+    tracing::info!("Created player id: {:?}", inserted_id);
+    Ok(inserted_id)
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn update_player(
+    db: &Client,
+    owner: &ObjectId,
+    player_id: &str,
+    player_update: PlayerUpdateDTO,
+) -> Result<(), PlayerError> {
+    let player = get_player(db, owner, player_id).await?;
+    let user_id = match &player_update.user_id {
+        Some(user_id) => Some(ensure_user_exists(db, user_id).await?),
+        None => None,
+    };
+    let update = player_update.into_update(user_id);
+    players_collection(db)
+        .update_one(doc! { "_id": player.id }, update)
+        .await?;
+    tracing::info!("Updated player id: {:?}", player.id);
+    Ok(())
+}
+
+/// Deletes a player who is not in any partnership. Sessions keep the names of the players
+/// who took part in them.
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn delete_player(
+    db: &Client,
+    owner: &ObjectId,
+    player_id: &str,
+) -> Result<(), PlayerError> {
+    let player = get_player(db, owner, player_id).await?;
+    if partnerships_collection(db)
+        .find_one(doc! { "players": player.id })
+        .await?
+        .is_some()
+    {
+        return Err(PlayerError::PlayerInPartnership(player_id.to_string()));
+    }
+    players_collection(db)
+        .delete_one(doc! { "_id": player.id })
+        .await?;
+    tracing::info!("Deleted player id: {:?}", player.id);
+    Ok(())
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn get_partnerships(
+    db: &Client,
+    owner: &ObjectId,
+) -> Result<Vec<PartnershipMongoDTO>, PlayerError> {
+    let collection = partnerships_collection(db);
+    let mut partnerships: Vec<PartnershipMongoDTO> = Vec::new();
+    let mut cursor = collection
+        .aggregate(vec![doc! { "$match": { "owner": owner } }])
+        .await?;
+    while let Some(document) = cursor.try_next().await? {
+        let partnership = bson::from_document::<PartnershipMongoDTO>(document).map_err(|e| {
+            tracing::error!("Error in from_document: {:?}", e);
+            e
+        })?;
+        partnerships.push(partnership);
+    }
+    Ok(partnerships)
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn get_partnership(
+    db: &Client,
+    owner: &ObjectId,
+    partnership_id: &str,
+) -> Result<PartnershipMongoDTO, PlayerError> {
+    let collection = partnerships_collection(db);
+    let id = ObjectId::parse_str(partnership_id)?;
+    collection
+        .find_one(doc! { "_id": id, "owner": owner })
+        .await?
+        .ok_or_else(|| PlayerError::PartnershipNotFound(partnership_id.to_string()))
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn create_partnership(
+    db: &Client,
+    owner: &ObjectId,
+    partnership: NewPartnershipDTO,
+) -> Result<String, PlayerError> {
+    let [first, second] = &partnership.players;
+    let first = get_player(db, owner, first).await?;
+    let second = get_player(db, owner, second).await?;
+    if first.id == second.id {
+        return Err(PlayerError::SamePlayerTwice);
+    }
+    let new_partnership = PartnershipMongoDTO {
+        id: ObjectId::new(),
+        owner: *owner,
+        players: [first.id, second.id],
+        name: partnership.name,
+    };
+    partnerships_collection(db)
+        .insert_one(&new_partnership)
+        .await?;
+    let inserted_id = new_partnership.id.to_string();
+    tracing::info!("Created partnership id: {:?}", inserted_id);
+    Ok(inserted_id)
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn delete_partnership(
+    db: &Client,
+    owner: &ObjectId,
+    partnership_id: &str,
+) -> Result<(), PlayerError> {
+    let id = ObjectId::parse_str(partnership_id)?;
+    let result = partnerships_collection(db)
+        .delete_one(doc! { "_id": id, "owner": owner })
+        .await?;
+    if result.deleted_count == 0 {
+        return Err(PlayerError::PartnershipNotFound(partnership_id.to_string()));
+    }
+    tracing::info!("Deleted partnership id: {:?}", id);
+    Ok(())
+}
+
+/// Checks a session's participants against the owner's players and partnerships. Each
+/// player takes part once, each number is used once per direction, and seats agree with
+/// the direction.
+#[tracing::instrument(target = "database", skip(db, participants))]
+pub async fn build_participants(
+    db: &Client,
+    owner: &ObjectId,
+    participants: Vec<NewParticipantDTO>,
+) -> Result<Vec<Participant>, PlayerError> {
+    let players: HashMap<ObjectId, PlayerMongoDTO> = get_players(db, owner)
+        .await?
+        .into_iter()
+        .map(|player| (player.id, player))
+        .collect();
+    let partnerships: HashMap<ObjectId, PartnershipMongoDTO> = get_partnerships(db, owner)
+        .await?
+        .into_iter()
+        .map(|partnership| (partnership.id, partnership))
+        .collect();
+    check_participants(participants, &players, &partnerships)
+}
+
+/// [`build_participants`] against the owner's players and partnerships.
+fn check_participants(
+    participants: Vec<NewParticipantDTO>,
+    players: &HashMap<ObjectId, PlayerMongoDTO>,
+    partnerships: &HashMap<ObjectId, PartnershipMongoDTO>,
+) -> Result<Vec<Participant>, PlayerError> {
+    let mut numbers = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut built = Vec::new();
+    for participant in participants {
+        if !numbers.insert((participant.number, participant.direction)) {
+            return Err(PlayerError::RepeatedNumber(participant.number));
+        }
+        let partnership = match &participant.partnership_id {
+            Some(id) => Some(
+                partnerships
+                    .get(&ObjectId::parse_str(id)?)
+                    .ok_or_else(|| PlayerError::PartnershipNotFound(id.to_string()))?,
+            ),
+            None => None,
+        };
+        let seated: Vec<(ObjectId, Option<Seat>)> =
+            match (&partnership, participant.players.is_empty()) {
+                (Some(partnership), true) => {
+                    partnership.players.iter().map(|id| (*id, None)).collect()
+                }
+                _ => participant
+                    .players
+                    .iter()
+                    .map(|player| Ok((ObjectId::parse_str(&player.player_id)?, player.seat)))
+                    .collect::<Result<_, PlayerError>>()?,
+            };
+        if seated.is_empty() {
+            return Err(PlayerError::NoPlayers(participant.number));
+        }
+        let mut names = Vec::new();
+        for (player_id, seat) in &seated {
+            let player = players
+                .get(player_id)
+                .ok_or_else(|| PlayerError::PlayerNotFound(player_id.to_string()))?;
+            if !seen.insert(*player_id) {
+                return Err(PlayerError::RepeatedParticipant(player.name.clone()));
+            }
+            if let (Some(seat), Some(direction)) = (seat, participant.direction) {
+                if !direction.includes(*seat) {
+                    return Err(PlayerError::SeatOutsideDirection {
+                        player: player.name.clone(),
+                        seat: *seat,
+                        direction,
+                    });
+                }
+            }
+            if let Some(partnership) = &partnership {
+                if !partnership.players.contains(player_id) {
+                    return Err(PlayerError::NotInPartnership {
+                        player: player.name.clone(),
+                        partnership: partnership.id.to_string(),
+                    });
+                }
+            }
+            names.push(player.name.clone());
+        }
+        let name = partnership
+            .as_ref()
+            .and_then(|partnership| partnership.name.clone())
+            .unwrap_or_else(|| names.join(" & "));
+        built.push(Participant {
+            number: participant.number,
+            direction: participant.direction,
+            partnership_id: partnership.map(|partnership| partnership.id),
+            players: seated
+                .into_iter()
+                .map(|(player_id, seat)| SeatedPlayer { player_id, seat })
+                .collect(),
+            name,
+        });
+    }
+    built.sort_by_key(|participant| (participant.number, participant.direction.map(|d| d as u8)));
+    Ok(built)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Club {
+        players: HashMap<ObjectId, PlayerMongoDTO>,
+        partnerships: HashMap<ObjectId, PartnershipMongoDTO>,
+    }
+
+    impl Club {
+        fn new() -> Self {
+            Club {
+                players: HashMap::new(),
+                partnerships: HashMap::new(),
+            }
+        }
+
+        fn player(&mut self, name: &str) -> String {
+            let player = PlayerMongoDTO {
+                id: ObjectId::new(),
+                owner: ObjectId::new(),
+                name: name.to_string(),
+                user_id: None,
+                member_number: None,
+            };
+            let id = player.id;
+            self.players.insert(id, player);
+            id.to_string()
+        }
+
+        fn partnership(&mut self, first: &str, second: &str, name: Option<&str>) -> String {
+            let partnership = PartnershipMongoDTO {
+                id: ObjectId::new(),
+                owner: ObjectId::new(),
+                players: [first.parse().unwrap(), second.parse().unwrap()],
+                name: name.map(str::to_string),
+            };
+            let id = partnership.id;
+            self.partnerships.insert(id, partnership);
+            id.to_string()
+        }
+
+        fn check(
+            &self,
+            participants: Vec<NewParticipantDTO>,
+        ) -> Result<Vec<Participant>, PlayerError> {
+            check_participants(participants, &self.players, &self.partnerships)
+        }
+    }
+
+    fn pair(
+        number: u32,
+        direction: Option<Direction>,
+        players: &[(&str, Option<Seat>)],
+    ) -> NewParticipantDTO {
+        NewParticipantDTO {
+            number,
+            direction,
+            partnership_id: None,
+            players: players
+                .iter()
+                .map(|(player_id, seat)| NewSeatedPlayerDTO {
+                    player_id: player_id.to_string(),
+                    seat: *seat,
+                })
+                .collect(),
+        }
+    }
+
+    fn partnership_pair(number: u32, partnership_id: &str) -> NewParticipantDTO {
+        NewParticipantDTO {
+            partnership_id: Some(partnership_id.to_string()),
+            ..pair(number, None, &[])
+        }
+    }
+
+    #[test]
+    fn builds_participants_in_number_order() {
+        let mut club = Club::new();
+        let (ann, bob) = (club.player("Ann"), club.player("Bob"));
+        let (cat, dan) = (club.player("Cat"), club.player("Dan"));
+        let named = club.partnership(&cat, &dan, Some("The Aces"));
+        let built = club
+            .check(vec![
+                partnership_pair(2, &named),
+                pair(
+                    1,
+                    Some(Direction::NorthSouth),
+                    &[(&ann, Some(Seat::North)), (&bob, Some(Seat::South))],
+                ),
+            ])
+            .unwrap();
+        assert_eq!(built.len(), 2);
+        assert_eq!(built[0].number, 1);
+        assert_eq!(built[0].name, "Ann & Bob");
+        assert_eq!(built[0].players[0].seat, Some(Seat::North));
+        assert_eq!(built[1].name, "The Aces");
+        assert_eq!(
+            built[1].partnership_id.map(|id| id.to_string()),
+            Some(named)
+        );
+        let seated: Vec<String> = built[1]
+            .players
+            .iter()
+            .map(|p| p.player_id.to_string())
+            .collect();
+        assert_eq!(seated, vec![cat, dan]);
+    }
+
+    #[test]
+    fn unnamed_partnership_uses_the_players_names() {
+        let mut club = Club::new();
+        let (ann, bob) = (club.player("Ann"), club.player("Bob"));
+        let unnamed = club.partnership(&ann, &bob, None);
+        let built = club.check(vec![partnership_pair(1, &unnamed)]).unwrap();
+        assert_eq!(built[0].name, "Ann & Bob");
+    }
+
+    #[test]
+    fn numbers_are_unique_per_direction() {
+        let mut club = Club::new();
+        let players: Vec<String> = ["Ann", "Bob", "Cat", "Dan"]
+            .iter()
+            .map(|name| club.player(name))
+            .collect();
+        let mitchell = club.check(vec![
+            pair(
+                1,
+                Some(Direction::NorthSouth),
+                &[(&players[0], None), (&players[1], None)],
+            ),
+            pair(
+                1,
+                Some(Direction::EastWest),
+                &[(&players[2], None), (&players[3], None)],
+            ),
+        ]);
+        assert!(mitchell.is_ok());
+        let repeated = club.check(vec![
+            pair(1, None, &[(&players[0], None), (&players[1], None)]),
+            pair(1, None, &[(&players[2], None), (&players[3], None)]),
+        ]);
+        assert!(matches!(repeated, Err(PlayerError::RepeatedNumber(1))));
+    }
+
+    #[test]
+    fn players_take_part_once() {
+        let mut club = Club::new();
+        let (ann, bob, cat) = (club.player("Ann"), club.player("Bob"), club.player("Cat"));
+        let repeated = club.check(vec![
+            pair(1, None, &[(&ann, None), (&bob, None)]),
+            pair(2, None, &[(&cat, None), (&ann, None)]),
+        ]);
+        assert!(matches!(repeated, Err(PlayerError::RepeatedParticipant(name)) if name == "Ann"));
+    }
+
+    #[test]
+    fn unknown_players_and_partnerships_are_refused() {
+        let mut club = Club::new();
+        let ann = club.player("Ann");
+        let stranger = ObjectId::new().to_string();
+        assert!(matches!(
+            club.check(vec![pair(1, None, &[(&ann, None), (&stranger, None)])]),
+            Err(PlayerError::PlayerNotFound(id)) if id == stranger
+        ));
+        assert!(matches!(
+            club.check(vec![partnership_pair(1, &stranger)]),
+            Err(PlayerError::PartnershipNotFound(id)) if id == stranger
+        ));
+        assert!(matches!(
+            club.check(vec![pair(1, None, &[(&ann, None), ("not-an-id", None)])]),
+            Err(PlayerError::InvalidObjectId(_))
+        ));
+    }
+
+    #[test]
+    fn seats_agree_with_the_direction() {
+        let mut club = Club::new();
+        let (ann, bob) = (club.player("Ann"), club.player("Bob"));
+        let wrong = club.check(vec![pair(
+            1,
+            Some(Direction::EastWest),
+            &[(&ann, Some(Seat::East)), (&bob, Some(Seat::North))],
+        )]);
+        assert!(matches!(
+            wrong,
+            Err(PlayerError::SeatOutsideDirection {
+                seat: Seat::North,
+                direction: Direction::EastWest,
+                ..
+            })
+        ));
+        // Without a direction, as in a Howell, any seat will do.
+        assert!(club
+            .check(vec![pair(
+                1,
+                None,
+                &[(&ann, Some(Seat::East)), (&bob, Some(Seat::North))]
+            )])
+            .is_ok());
+    }
+
+    #[test]
+    fn seated_players_belong_to_the_partnership() {
+        let mut club = Club::new();
+        let (ann, bob, cat) = (club.player("Ann"), club.player("Bob"), club.player("Cat"));
+        let partnership = club.partnership(&ann, &bob, None);
+        let outsider = NewParticipantDTO {
+            partnership_id: Some(partnership.clone()),
+            ..pair(1, None, &[(&ann, None), (&cat, None)])
+        };
+        assert!(matches!(
+            club.check(vec![outsider]),
+            Err(PlayerError::NotInPartnership { player, .. }) if player == "Cat"
+        ));
+    }
+
+    #[test]
+    fn participant_needs_players() {
+        let club = Club::new();
+        assert!(matches!(
+            club.check(vec![pair(3, None, &[])]),
+            Err(PlayerError::NoPlayers(3))
+        ));
+    }
+
+    #[test]
+    fn links_a_player_to_an_existing_user() {
+        let user = ObjectId::new();
+        assert_eq!(linked_user(user, &[user]).unwrap(), user);
+        assert!(matches!(
+            linked_user(user, &[]),
+            Err(PlayerError::UserNotFound(id)) if id == user.to_string()
+        ));
+        assert!(matches!(
+            linked_user(user, &[ObjectId::new()]),
+            Err(PlayerError::UserNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn link_is_stored_as_an_object_id() {
+        let user = ObjectId::new();
+        let update: PlayerUpdateDTO = serde_json::from_value(serde_json::json!({
+            "name": "Ann", "userId": user.to_string(), "memberNumber": null
+        }))
+        .unwrap();
+        let update = update.into_update(Some(user));
+        let set = update.get_document("$set").unwrap();
+        assert_eq!(set.get_object_id("userId").unwrap(), user);
+        assert_eq!(set.get_str("name").unwrap(), "Ann");
+        assert!(!set.contains_key("memberNumber"));
+
+        let player = PlayerMongoDTO {
+            id: ObjectId::new(),
+            owner: ObjectId::new(),
+            name: "Ann".to_string(),
+            user_id: Some(user),
+            member_number: None,
+        };
+        assert_eq!(PlayerJsonDTO::from(player).user_id, Some(user.to_string()));
+    }
+}
@@ -0,0 +1,430 @@
+//! Director rulings, kept as a log per session. A ruling concerns one board and may point
+//! at the result it adjusted; the adjustment itself lives on the board result. Amending a
+//! ruling keeps what it said before.
+use async_graphql::Enum;
+use bson::{oid::ObjectId, DateTime};
+use futures::TryStreamExt;
+use mongodb::{bson::doc, Client, Collection};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    board::{get_board, BoardError, BoardResult, Room},
+    contract::Seat,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RulingError {
+    #[error("Ruling {0} not found")]
+    RulingNotFound(String),
+    #[error("Board {0} has no result matching the adjusted score reference")]
+    ResultNotFound(u32),
+    #[error("Board error: {0}")]
+    BoardError(#[from] BoardError),
+    #[error("Query error: {0}")]
+    QueryError(#[from] mongodb::error::Error),
+    #[error("Invalid ruling record: {0}")]
+    InvalidRulingRecord(#[from] bson::de::Error),
+    #[error("Could not serialize ruling: {0}")]
+    SerializationError(#[from] bson::ser::Error),
+    #[error("Could not convert {0} to ObjectId")]
+    InvalidObjectId(#[from] bson::oid::Error),
+}
+
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AppealStatus {
+    #[default]
+    NotAppealed,
+    Lodged,
+    Upheld,
+    Overturned,
+    Withdrawn,
+}
+
+/// Identifies the board result a ruling adjusted, by whichever of room and pair numbers
+/// the result was recorded with.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultReference {
+    #[serde(default)]
+    pub room: Option<Room>,
+    #[serde(default)]
+    pub ns_pair: Option<u32>,
+    #[serde(default)]
+    pub ew_pair: Option<u32>,
+}
+
+impl ResultReference {
+    pub fn matches(&self, result: &BoardResult) -> bool {
+        (self.room.is_none() || self.room == result.room)
+            && (self.ns_pair.is_none() || self.ns_pair == result.ns_pair)
+            && (self.ew_pair.is_none() || self.ew_pair == result.ew_pair)
+    }
+}
+
+/// What a ruling said before an amendment, and who changed it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RulingAmendment {
+    pub amended_by: ObjectId,
+    pub amended_at: DateTime,
+    pub law: String,
+    pub facts: String,
+    pub decision: String,
+    pub adjusted_result: Option<ResultReference>,
+    pub appeal_status: AppealStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RulingMongoDTO {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub session_id: ObjectId,
+    pub board_id: ObjectId,
+    pub board_number: u32,
+    /// The user who made the ruling.
+    pub director: ObjectId,
+    /// Seat of the player who called the director.
+    pub called_by: Option<Seat>,
+    /// The law applied, e.g. "16B1".
+    pub law: String,
+    pub facts: String,
+    pub decision: String,
+    #[serde(default)]
+    pub adjusted_result: Option<ResultReference>,
+    #[serde(default)]
+    pub appeal_status: AppealStatus,
+    pub created_at: DateTime,
+    #[serde(default)]
+    pub amendments: Vec<RulingAmendment>,
+}
+
+impl RulingMongoDTO {
+    /// Applies an update, first recording what the ruling said in its amendments.
+    fn amend(&mut self, amended_by: &ObjectId, update: RulingUpdateDTO) {
+        self.amendments.push(RulingAmendment {
+            amended_by: *amended_by,
+            amended_at: DateTime::now(),
+            law: self.law.clone(),
+            facts: self.facts.clone(),
+            decision: self.decision.clone(),
+            adjusted_result: self.adjusted_result.clone(),
+            appeal_status: self.appeal_status,
+        });
+        if let Some(law) = update.law {
+            self.law = law;
+        }
+        if let Some(facts) = update.facts {
+            self.facts = facts;
+        }
+        if let Some(decision) = update.decision {
+            self.decision = decision;
+        }
+        if let Some(adjusted_result) = update.adjusted_result {
+            self.adjusted_result = Some(adjusted_result);
+        }
+        if let Some(appeal_status) = update.appeal_status {
+            self.appeal_status = appeal_status;
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewRulingDTO {
+    pub board_id: String,
+    #[serde(default)]
+    pub called_by: Option<Seat>,
+    pub law: String,
+    pub facts: String,
+    pub decision: String,
+    #[serde(default)]
+    pub adjusted_result: Option<ResultReference>,
+    #[serde(default)]
+    pub appeal_status: AppealStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RulingUpdateDTO {
+    pub law: Option<String>,
+    pub facts: Option<String>,
+    pub decision: Option<String>,
+    pub adjusted_result: Option<ResultReference>,
+    pub appeal_status: Option<AppealStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RulingAmendmentJsonDTO {
+    pub amended_by: String,
+    pub amended_at: String,
+    pub law: String,
+    pub facts: String,
+    pub decision: String,
+    pub adjusted_result: Option<ResultReference>,
+    pub appeal_status: AppealStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RulingJsonDTO {
+    pub id: String,
+    pub session_id: String,
+    pub board_id: String,
+    pub board_number: u32,
+    pub director: String,
+    pub called_by: Option<Seat>,
+    pub law: String,
+    pub facts: String,
+    pub decision: String,
+    pub adjusted_result: Option<ResultReference>,
+    pub appeal_status: AppealStatus,
+    pub created_at: String,
+    pub amendments: Vec<RulingAmendmentJsonDTO>,
+}
+
+impl From<RulingAmendment> for RulingAmendmentJsonDTO {
+    fn from(amendment: RulingAmendment) -> Self {
+        RulingAmendmentJsonDTO {
+            amended_by: amendment.amended_by.to_string(),
+            amended_at: amendment
+                .amended_at
+                .try_to_rfc3339_string()
+                .unwrap_or_default(),
+            law: amendment.law,
+            facts: amendment.facts,
+            decision: amendment.decision,
+            adjusted_result: amendment.adjusted_result,
+            appeal_status: amendment.appeal_status,
+        }
+    }
+}
+
+impl From<RulingMongoDTO> for RulingJsonDTO {
+    fn from(ruling: RulingMongoDTO) -> Self {
+        RulingJsonDTO {
+            id: ruling.id.to_string(),
+            session_id: ruling.session_id.to_string(),
+            board_id: ruling.board_id.to_string(),
+            board_number: ruling.board_number,
+            director: ruling.director.to_string(),
+            called_by: ruling.called_by,
+            law: ruling.law,
+            facts: ruling.facts,
+            decision: ruling.decision,
+            adjusted_result: ruling.adjusted_result,
+            appeal_status: ruling.appeal_status,
+            created_at: ruling
+                .created_at
+                .try_to_rfc3339_string()
+                .unwrap_or_default(),
+            amendments: ruling
+                .amendments
+                .into_iter()
+                .map(RulingAmendmentJsonDTO::from)
+                .collect(),
+        }
+    }
+}
+
+fn rulings_collection(db: &Client) -> Collection<RulingMongoDTO> {
+    db.database("bridge_scorecard_api").collection("rulings")
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn get_rulings_for_session(
+    db: &Client,
+    session_id: &ObjectId,
+) -> Result<Vec<RulingMongoDTO>, RulingError> {
+    let collection = rulings_collection(db);
+    let pipeline = vec![
+        doc! { "$match": { "sessionId": session_id } },
+        doc! { "$sort": { "boardNumber": 1, "createdAt": 1 } },
+    ];
+    let mut rulings: Vec<RulingMongoDTO> = Vec::new();
+    let mut cursor = collection.aggregate(pipeline).await?;
+    while let Some(document) = cursor.try_next().await? {
+        let ruling = bson::from_document::<RulingMongoDTO>(document).map_err(|e| {
+            tracing::error!("Error in from_document: {:?}", e);
+            e
+        })?;
+        rulings.push(ruling);
+    }
+    Ok(rulings)
+}
+
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn get_ruling(
+    db: &Client,
+    session_id: &ObjectId,
+    ruling_id: &str,
+) -> Result<RulingMongoDTO, RulingError> {
+    let id = ObjectId::parse_str(ruling_id)?;
+    rulings_collection(db)
+        .find_one(doc! { "_id": id, "sessionId": session_id })
+        .await?
+        .ok_or_else(|| RulingError::RulingNotFound(ruling_id.to_string()))
+}
+
+/// Records a ruling on one of the session's boards. A reference to an adjusted result
+/// must match a result on that board.
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn create_ruling(
+    db: &Client,
+    session_id: &ObjectId,
+    director: &ObjectId,
+    ruling: NewRulingDTO,
+) -> Result<RulingMongoDTO, RulingError> {
+    let board = get_board(db, session_id, &ruling.board_id).await?;
+    if let Some(reference) = &ruling.adjusted_result {
+        if !board.results.iter().any(|result| reference.matches(result)) {
+            return Err(RulingError::ResultNotFound(board.board_number));
+        }
+    }
+    let new_ruling = RulingMongoDTO {
+        id: ObjectId::new(),
+        session_id: *session_id,
+        board_id: board.id,
+        board_number: board.board_number,
+        director: *director,
+        called_by: ruling.called_by,
+        law: ruling.law,
+        facts: ruling.facts,
+        decision: ruling.decision,
+        adjusted_result: ruling.adjusted_result,
+        appeal_status: ruling.appeal_status,
+        created_at: DateTime::now(),
+        amendments: Vec::new(),
+    };
+    rulings_collection(db).insert_one(&new_ruling).await?;
+    tracing::info!("Created ruling id: {:?}", new_ruling.id);
+    Ok(new_ruling)
+}
+
+/// Amends a ruling, recording what it said before and who changed it.
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn amend_ruling(
+    db: &Client,
+    session_id: &ObjectId,
+    ruling_id: &str,
+    amended_by: &ObjectId,
+    ruling_update: RulingUpdateDTO,
+) -> Result<RulingMongoDTO, RulingError> {
+    let mut ruling = get_ruling(db, session_id, ruling_id).await?;
+    if let Some(reference) = &ruling_update.adjusted_result {
+        let board = get_board(db, session_id, &ruling.board_id.to_string()).await?;
+        if !board.results.iter().any(|result| reference.matches(result)) {
+            return Err(RulingError::ResultNotFound(board.board_number));
+        }
+    }
+    ruling.amend(amended_by, ruling_update);
+    rulings_collection(db)
+        .replace_one(doc! { "_id": ruling.id }, &ruling)
+        .await?;
+    tracing::info!("Amended ruling id: {:?}", ruling.id);
+    Ok(ruling)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ruling() -> RulingMongoDTO {
+        RulingMongoDTO {
+            id: ObjectId::new(),
+            session_id: ObjectId::new(),
+            board_id: ObjectId::new(),
+            board_number: 7,
+            director: ObjectId::new(),
+            called_by: Some(Seat::East),
+            law: "16B1".to_string(),
+            facts: "Slow pass before 3S".to_string(),
+            decision: "Result stands".to_string(),
+            adjusted_result: None,
+            appeal_status: AppealStatus::NotAppealed,
+            created_at: DateTime::now(),
+            amendments: Vec::new(),
+        }
+    }
+
+    fn update() -> RulingUpdateDTO {
+        RulingUpdateDTO {
+            law: None,
+            facts: None,
+            decision: None,
+            adjusted_result: None,
+            appeal_status: None,
+        }
+    }
+
+    #[test]
+    fn amending_keeps_what_the_ruling_said() {
+        let director = ObjectId::new();
+        let adjusted = ResultReference {
+            room: None,
+            ns_pair: Some(3),
+            ew_pair: Some(11),
+        };
+        let mut ruling = ruling();
+        ruling.amend(
+            &director,
+            RulingUpdateDTO {
+                decision: Some("Score adjusted to 4S-1".to_string()),
+                adjusted_result: Some(adjusted.clone()),
+                ..update()
+            },
+        );
+
+        assert_eq!(ruling.law, "16B1");
+        assert_eq!(ruling.facts, "Slow pass before 3S");
+        assert_eq!(ruling.decision, "Score adjusted to 4S-1");
+        assert_eq!(ruling.adjusted_result, Some(adjusted));
+        assert_eq!(ruling.amendments.len(), 1);
+        let before = &ruling.amendments[0];
+        assert_eq!(before.amended_by, director);
+        assert_eq!(before.decision, "Result stands");
+        assert_eq!(before.adjusted_result, None);
+        assert_eq!(before.appeal_status, AppealStatus::NotAppealed);
+    }
+
+    #[test]
+    fn amendments_are_kept_in_order() {
+        let first = ObjectId::new();
+        let second = ObjectId::new();
+        let mut ruling = ruling();
+        ruling.amend(
+            &first,
+            RulingUpdateDTO {
+                appeal_status: Some(AppealStatus::Lodged),
+                ..update()
+            },
+        );
+        ruling.amend(
+            &second,
+            RulingUpdateDTO {
+                appeal_status: Some(AppealStatus::Upheld),
+                ..update()
+            },
+        );
+
+        assert_eq!(ruling.appeal_status, AppealStatus::Upheld);
+        let history: Vec<_> = ruling
+            .amendments
+            .iter()
+            .map(|amendment| (amendment.amended_by, amendment.appeal_status))
+            .collect();
+        assert_eq!(
+            history,
+            vec![
+                (first, AppealStatus::NotAppealed),
+                (second, AppealStatus::Lodged),
+            ]
+        );
+
+        let json = RulingJsonDTO::from(ruling);
+        assert_eq!(json.amendments.len(), 2);
+        assert_eq!(json.amendments[1].amended_by, second.to_string());
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::board::BoardMongoDTO;
+
+use super::total_points::room_scores;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardBam {
+    pub board_number: u32,
+    pub open_room_score: Option<i32>,
+    pub closed_room_score: Option<i32>,
+    /// 1 for a board won, 1/2 for a tie and 0 for a loss; `None` until both rooms have a
+    /// result on the board.
+    pub points: Option<f64>,
+    pub running_total: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardAMatch {
+    pub boards: Vec<BoardBam>,
+    pub points_for: f64,
+    pub points_against: f64,
+}
+
+/// Scores a board-a-match team match, where each board is won, lost or tied on the
+/// total score across both rooms however large the difference. Points are from the
+/// point of view of the team sitting North-South in the open room.
+pub fn board_a_match(boards: &[BoardMongoDTO]) -> BoardAMatch {
+    let mut points_for = 0.0;
+    let mut points_against = 0.0;
+    let board_points = boards
+        .iter()
+        .map(|board| {
+            let (open_room_score, closed_room_score) = room_scores(board);
+            let points = match (open_room_score, closed_room_score) {
+                (Some(open), Some(closed)) => Some(match open.cmp(&closed) {
+                    std::cmp::Ordering::Greater => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Less => 0.0,
+                }),
+                _ => None,
+            };
+            if let Some(points) = points {
+                points_for += points;
+                points_against += 1.0 - points;
+            }
+            BoardBam {
+                board_number: board.board_number,
+                open_room_score,
+                closed_room_score,
+                points,
+                running_total: points_for,
+            }
+        })
+        .collect();
+    BoardAMatch {
+        boards: board_points,
+        points_for,
+        points_against,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        board::Room,
+        scoring::fixtures::{board, room_result},
+    };
+
+    #[test]
+    fn wins_ties_and_losses_count_the_same_whatever_the_margin() {
+        let boards = vec![
+            board(
+                1,
+                vec![room_result(Room::Open, 420), room_result(Room::Closed, 400)],
+            ),
+            board(
+                2,
+                vec![
+                    room_result(Room::Open, -100),
+                    room_result(Room::Closed, -100),
+                ],
+            ),
+            board(
+                3,
+                vec![room_result(Room::Open, 50), room_result(Room::Closed, 1430)],
+            ),
+            board(4, vec![room_result(Room::Open, 620)]),
+        ];
+        let result = board_a_match(&boards);
+        let points: Vec<Option<f64>> = result.boards.iter().map(|b| b.points).collect();
+        assert_eq!(points, [Some(1.0), Some(0.5), Some(0.0), None]);
+        let running: Vec<f64> = result.boards.iter().map(|b| b.running_total).collect();
+        assert_eq!(running, [1.0, 1.5, 1.5, 1.5]);
+        assert_eq!((result.points_for, result.points_against), (1.5, 1.5));
+        assert_eq!(result.boards[3].open_room_score, Some(620));
+        assert_eq!(result.boards[3].closed_room_score, None);
+    }
+
+    #[test]
+    fn results_are_matched_by_room() {
+        let boards = vec![board(
+            1,
+            vec![room_result(Room::Closed, 110), room_result(Room::Open, 140)],
+        )];
+        let result = board_a_match(&boards);
+        assert_eq!(result.boards[0].open_room_score, Some(140));
+        assert_eq!(result.boards[0].points, Some(1.0));
+    }
+}
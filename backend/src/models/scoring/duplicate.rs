@@ -0,0 +1,274 @@
+use crate::models::contract::{Contract, Doubled, Seat, Strain, Vulnerability};
+
+use super::ScoringError;
+
+/// Duplicate score of a played contract from the declaring side's point of view:
+/// positive when the contract makes, negative when it goes down.
+pub fn duplicate_score(
+    contract: &Contract,
+    declarer: Seat,
+    vulnerability: Vulnerability,
+    tricks_taken: u8,
+) -> Result<i32, ScoringError> {
+    if tricks_taken > 13 {
+        return Err(ScoringError::InvalidTricks(tricks_taken));
+    }
+    let vulnerable = vulnerability.is_vulnerable(declarer);
+    let required = contract.tricks_required();
+    if tricks_taken >= required {
+        Ok(made_score(contract, vulnerable, tricks_taken - required))
+    } else {
+        Ok(-undertrick_penalty(
+            contract.doubled,
+            vulnerable,
+            required - tricks_taken,
+        ))
+    }
+}
+
+/// Same as [`duplicate_score`], but from North-South's point of view, which is
+/// how results are written on a traveller.
+pub fn ns_duplicate_score(
+    contract: &Contract,
+    declarer: Seat,
+    vulnerability: Vulnerability,
+    tricks_taken: u8,
+) -> Result<i32, ScoringError> {
+    let score = duplicate_score(contract, declarer, vulnerability, tricks_taken)?;
+    if declarer.is_north_south() {
+        Ok(score)
+    } else {
+        Ok(-score)
+    }
+}
+
+/// Trick score for the contracted tricks only (the "below the line" score).
+pub fn contract_trick_score(contract: &Contract) -> i32 {
+    let level = contract.level as i32;
+    let undoubled = match contract.strain {
+        Strain::Clubs | Strain::Diamonds => 20 * level,
+        Strain::Hearts | Strain::Spades => 30 * level,
+        Strain::NoTrump => 40 + 30 * (level - 1),
+    };
+    undoubled * doubling_factor(contract.doubled)
+}
+
+fn made_score(contract: &Contract, vulnerable: bool, overtricks: u8) -> i32 {
+    let trick_score = contract_trick_score(contract);
+    let game_bonus = if trick_score >= 100 {
+        if vulnerable {
+            500
+        } else {
+            300
+        }
+    } else {
+        50
+    };
+    trick_score
+        + game_bonus
+        + slam_bonus(contract.level, vulnerable)
+        + insult_bonus(contract.doubled)
+        + overtrick_score(contract, vulnerable, overtricks)
+}
+
+pub fn slam_bonus(level: u8, vulnerable: bool) -> i32 {
+    match (level, vulnerable) {
+        (6, false) => 500,
+        (6, true) => 750,
+        (7, false) => 1000,
+        (7, true) => 1500,
+        _ => 0,
+    }
+}
+
+/// Bonus for making a doubled or redoubled contract.
+pub fn insult_bonus(doubled: Doubled) -> i32 {
+    match doubled {
+        Doubled::Undoubled => 0,
+        Doubled::Doubled => 50,
+        Doubled::Redoubled => 100,
+    }
+}
+
+pub fn overtrick_score(contract: &Contract, vulnerable: bool, overtricks: u8) -> i32 {
+    let overtrick_value = match (contract.doubled, vulnerable) {
+        (Doubled::Undoubled, _) => match contract.strain {
+            Strain::Clubs | Strain::Diamonds => 20,
+            _ => 30,
+        },
+        (Doubled::Doubled, false) => 100,
+        (Doubled::Doubled, true) => 200,
+        (Doubled::Redoubled, false) => 200,
+        (Doubled::Redoubled, true) => 400,
+    };
+    overtrick_value * overtricks as i32
+}
+
+/// What the defenders score for defeating a contract.
+pub fn undertrick_penalty(doubled: Doubled, vulnerable: bool, undertricks: u8) -> i32 {
+    let undertricks = undertricks as i32;
+    match doubled {
+        Doubled::Undoubled => undertricks * if vulnerable { 100 } else { 50 },
+        _ => {
+            let doubled_penalty = if vulnerable {
+                // 200 for the first undertrick, 300 for each after that.
+                200 + 300 * (undertricks - 1)
+            } else {
+                // 100 for the first, 200 for the second and third, 300 from the fourth on.
+                match undertricks {
+                    1 => 100,
+                    2 => 300,
+                    3 => 500,
+                    n => 500 + 300 * (n - 3),
+                }
+            };
+            doubled_penalty * doubling_factor(doubled) / 2
+        }
+    }
+}
+
+fn doubling_factor(doubled: Doubled) -> i32 {
+    match doubled {
+        Doubled::Undoubled => 1,
+        Doubled::Doubled => 2,
+        Doubled::Redoubled => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(contract: &str, vulnerable: bool, tricks: u8) -> i32 {
+        let vulnerability = if vulnerable {
+            Vulnerability::Both
+        } else {
+            Vulnerability::None
+        };
+        duplicate_score(
+            &contract.parse().unwrap(),
+            Seat::North,
+            vulnerability,
+            tricks,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn partscores() {
+        assert_eq!(score("1C", false, 7), 70);
+        assert_eq!(score("2D", true, 8), 90);
+        assert_eq!(score("2S", false, 8), 110);
+        assert_eq!(score("1NT", false, 7), 90);
+        assert_eq!(score("2NT", true, 8), 120);
+        assert_eq!(score("4D", false, 10), 130);
+        assert_eq!(score("3H", true, 9), 140);
+    }
+
+    #[test]
+    fn games() {
+        assert_eq!(score("3NT", false, 9), 400);
+        assert_eq!(score("3NT", true, 9), 600);
+        assert_eq!(score("4S", false, 10), 420);
+        assert_eq!(score("4H", true, 10), 620);
+        assert_eq!(score("5C", false, 11), 400);
+        assert_eq!(score("5D", true, 11), 600);
+        assert_eq!(score("4H", true, 11), 650);
+        assert_eq!(score("3NT", false, 13), 520);
+        assert_eq!(score("2C", false, 12), 170);
+    }
+
+    #[test]
+    fn slams() {
+        assert_eq!(score("6C", false, 12), 920);
+        assert_eq!(score("6S", true, 12), 1430);
+        assert_eq!(score("6NT", false, 12), 990);
+        assert_eq!(score("6NT", true, 12), 1440);
+        assert_eq!(score("7D", false, 13), 1440);
+        assert_eq!(score("7H", true, 13), 2210);
+        assert_eq!(score("7NT", false, 13), 1520);
+        assert_eq!(score("7NT", true, 13), 2220);
+        assert_eq!(score("6H", false, 13), 1010);
+    }
+
+    #[test]
+    fn doubled_and_redoubled_makes() {
+        // The insult bonus is 50 doubled and 100 redoubled.
+        assert_eq!(score("1NTX", false, 7), 180);
+        assert_eq!(score("1CX", true, 7), 140);
+        assert_eq!(score("2HX", false, 8), 470);
+        assert_eq!(score("2HX", true, 8), 670);
+        assert_eq!(score("1HXX", false, 7), 520);
+        assert_eq!(score("1HXX", true, 7), 720);
+        assert_eq!(score("4SX", true, 10), 790);
+        assert_eq!(score("3NTXX", false, 9), 800);
+        assert_eq!(score("6NTXX", true, 12), 2110);
+        assert_eq!(score("7NTXX", true, 13), 2980);
+    }
+
+    #[test]
+    fn doubled_and_redoubled_overtricks() {
+        assert_eq!(score("1SX", false, 9), 360);
+        assert_eq!(score("1SX", true, 9), 560);
+        assert_eq!(score("1HXX", false, 8), 720);
+        assert_eq!(score("1HXX", true, 8), 1120);
+        assert_eq!(score("2DX", false, 10), 380);
+        assert_eq!(score("4HX", true, 11), 990);
+        assert_eq!(score("3NTXX", true, 11), 1800);
+    }
+
+    #[test]
+    fn undoubled_undertricks() {
+        assert_eq!(score("4S", false, 9), -50);
+        assert_eq!(score("4S", true, 9), -100);
+        assert_eq!(score("3NT", false, 5), -200);
+        assert_eq!(score("7NT", true, 0), -1300);
+    }
+
+    #[test]
+    fn doubled_undertrick_ladder() {
+        let not_vulnerable = [-100, -300, -500, -800, -1100, -1400];
+        let vulnerable = [-200, -500, -800, -1100, -1400, -1700];
+        for down in 1..=6u8 {
+            let i = down as usize - 1;
+            assert_eq!(score("7SX", false, 13 - down), not_vulnerable[i]);
+            assert_eq!(score("7SX", true, 13 - down), vulnerable[i]);
+            assert_eq!(score("7SXX", false, 13 - down), 2 * not_vulnerable[i]);
+            assert_eq!(score("7SXX", true, 13 - down), 2 * vulnerable[i]);
+        }
+        assert_eq!(score("7NTX", false, 0), -3500);
+        assert_eq!(score("7NTX", true, 0), -3800);
+        assert_eq!(score("3NTXX", true, 0), -5200);
+        assert_eq!(score("3NTXX", false, 0), -4600);
+    }
+
+    #[test]
+    fn insult_bonus_only_for_doubled_contracts() {
+        assert_eq!(insult_bonus(Doubled::Undoubled), 0);
+        assert_eq!(insult_bonus(Doubled::Doubled), 50);
+        assert_eq!(insult_bonus(Doubled::Redoubled), 100);
+        // Not paid when the contract goes down.
+        assert_eq!(score("2CX", false, 7), -100);
+    }
+
+    #[test]
+    fn north_south_view() {
+        let contract: Contract = "4H".parse().unwrap();
+        let ns = |declarer, vulnerability, tricks| {
+            ns_duplicate_score(&contract, declarer, vulnerability, tricks).unwrap()
+        };
+        assert_eq!(ns(Seat::South, Vulnerability::Ns, 10), 620);
+        assert_eq!(ns(Seat::East, Vulnerability::Ns, 10), -420);
+        assert_eq!(ns(Seat::West, Vulnerability::Ew, 9), 100);
+        assert_eq!(ns(Seat::North, Vulnerability::Ew, 9), -50);
+    }
+
+    #[test]
+    fn rejects_more_than_thirteen_tricks() {
+        let contract: Contract = "1C".parse().unwrap();
+        assert!(matches!(
+            duplicate_score(&contract, Seat::North, Vulnerability::None, 14),
+            Err(ScoringError::InvalidTricks(14))
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::board::{BoardMongoDTO, BoardResult};
+
+use super::{total_points::room_results, victory_points::victory_points};
+
+/// Lower bound of the score difference for each step of the WBF IMP scale.
+const IMP_SCALE: [i32; 24] = [
+    20, 50, 90, 130, 170, 220, 270, 320, 370, 430, 500, 600, 750, 900, 1100, 1300, 1500, 1750,
+    2000, 2250, 2500, 3000, 3500, 4000,
+];
+
+/// Converts a score difference into IMPs, keeping its sign.
+pub fn imps(difference: i32) -> i32 {
+    let steps = IMP_SCALE
+        .iter()
+        .take_while(|threshold| difference.abs() >= **threshold)
+        .count() as i32;
+    steps * difference.signum()
+}
+
+/// IMPs for the pair sitting North-South at `first` and East-West at `second`, as the
+/// two halves of a team comparison. Artificial adjusted scores earn their IMPs whatever
+/// happened in the other room; assigned scores are compared outcome by outcome and
+/// weighted, rounded to the nearest IMP.
+pub fn compare_results(first: &BoardResult, second: &BoardResult) -> i32 {
+    let (first_artificial, second_artificial) = (first.artificial(), second.artificial());
+    if first_artificial.is_some() || second_artificial.is_some() {
+        return first_artificial.map_or(0, |(ns, _)| ns.imps())
+            + second_artificial.map_or(0, |(_, ew)| ew.imps());
+    }
+    let second_scores = second.weighted_scores();
+    let weighted: f64 = first
+        .weighted_scores()
+        .iter()
+        .flat_map(|(first_weight, first_score)| {
+            second_scores
+                .iter()
+                .map(move |(second_weight, second_score)| {
+                    first_weight * second_weight * imps(first_score - second_score) as f64
+                })
+        })
+        .sum();
+    weighted.round() as i32
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardImps {
+    pub board_number: u32,
+    pub open_room_score: Option<i32>,
+    pub closed_room_score: Option<i32>,
+    /// `None` until both rooms have a result on the board.
+    pub imps: Option<i32>,
+    pub running_total: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamMatch {
+    pub boards: Vec<BoardImps>,
+    pub imps_for: i32,
+    pub imps_against: i32,
+    pub net_imps: i32,
+    /// Only filled in for sessions that use victory points.
+    pub vps_for: Option<f64>,
+    pub vps_against: Option<f64>,
+}
+
+/// Scores a team match from the point of view of the team sitting North-South in the
+/// open room (and so East-West in the closed room). Boards are expected in board order.
+pub fn team_match(boards: &[BoardMongoDTO]) -> TeamMatch {
+    let mut imps_for = 0;
+    let mut imps_against = 0;
+    let board_imps = boards
+        .iter()
+        .map(|board| {
+            let (open, closed) = room_results(board);
+            let board_imps = match (open, closed) {
+                (Some(open), Some(closed)) => Some(compare_results(open, closed)),
+                _ => None,
+            };
+            match board_imps {
+                Some(imps) if imps > 0 => imps_for += imps,
+                Some(imps) => imps_against -= imps,
+                None => {}
+            }
+            BoardImps {
+                board_number: board.board_number,
+                open_room_score: open.map(|result| result.score),
+                closed_room_score: closed.map(|result| result.score),
+                imps: board_imps,
+                running_total: imps_for - imps_against,
+            }
+        })
+        .collect();
+    TeamMatch {
+        boards: board_imps,
+        imps_for,
+        imps_against,
+        net_imps: imps_for - imps_against,
+        vps_for: None,
+        vps_against: None,
+    }
+}
+
+impl TeamMatch {
+    /// Converts the IMP margin to victory points. The scale follows the number of boards
+    /// compared, so boards missing a room's result do not count towards the match length.
+    pub fn with_victory_points(mut self) -> Self {
+        let compared = self
+            .boards
+            .iter()
+            .filter(|board| board.imps.is_some())
+            .count();
+        let (vps_for, vps_against) = victory_points(self.net_imps, compared as u32);
+        self.vps_for = Some(vps_for);
+        self.vps_against = Some(vps_against);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        adjustment::{Adjustment, ArtificialScore, WeightedScore},
+        board::Room,
+        scoring::fixtures::{board, room_result},
+    };
+
+    #[test]
+    fn imp_scale_boundaries() {
+        let cases = [
+            (0, 0),
+            (10, 0),
+            (19, 0),
+            (20, 1),
+            (40, 1),
+            (49, 1),
+            (50, 2),
+            (89, 2),
+            (90, 3),
+            (130, 4),
+            (420, 9),
+            (430, 10),
+            (500, 11),
+            (620, 12),
+            (750, 13),
+            (1430, 16),
+            (2210, 19),
+            (3990, 23),
+            (4000, 24),
+            (7600, 24),
+        ];
+        for (difference, expected) in cases {
+            assert_eq!(imps(difference), expected, "{difference}");
+            assert_eq!(imps(-difference), -expected, "-{difference}");
+        }
+    }
+
+    #[test]
+    fn team_match_totals() {
+        let boards = [
+            board(
+                1,
+                vec![room_result(Room::Open, 620), room_result(Room::Closed, 170)],
+            ),
+            board(
+                2,
+                vec![
+                    room_result(Room::Open, -100),
+                    room_result(Room::Closed, 140),
+                ],
+            ),
+            board(3, vec![room_result(Room::Open, 400)]),
+            board(
+                4,
+                vec![room_result(Room::Open, 50), room_result(Room::Closed, 50)],
+            ),
+        ];
+        let team_match = team_match(&boards);
+        let imps: Vec<Option<i32>> = team_match.boards.iter().map(|b| b.imps).collect();
+        assert_eq!(imps, vec![Some(10), Some(-6), None, Some(0)]);
+        assert_eq!(team_match.imps_for, 10);
+        assert_eq!(team_match.imps_against, 6);
+        assert_eq!(team_match.net_imps, 4);
+        assert_eq!(team_match.boards[1].running_total, 4);
+        assert!(team_match.vps_for.is_none());
+    }
+
+    #[test]
+    fn victory_points_count_only_compared_boards() {
+        let boards = [
+            board(
+                1,
+                vec![room_result(Room::Open, 420), room_result(Room::Closed, 170)],
+            ),
+            board(2, vec![room_result(Room::Open, 620)]),
+        ];
+        let team_match = team_match(&boards).with_victory_points();
+        assert_eq!(team_match.net_imps, 6);
+        assert_eq!(
+            (team_match.vps_for, team_match.vps_against),
+            (Some(15.74), Some(4.26))
+        );
+    }
+
+    #[test]
+    fn artificial_scores_ignore_the_other_room() {
+        let mut open = room_result(Room::Open, 0);
+        open.adjustment = Some(Adjustment::Artificial {
+            ns: ArtificialScore::AveragePlus,
+            ew: ArtificialScore::AverageMinus,
+        });
+        let closed = room_result(Room::Closed, -1430);
+        assert_eq!(compare_results(&open, &closed), 3);
+        assert_eq!(compare_results(&closed, &open), -3);
+    }
+
+    #[test]
+    fn assigned_scores_are_weighted() {
+        let weighted = |weight, score| WeightedScore {
+            weight,
+            contract: None,
+            declarer: None,
+            tricks: None,
+            score,
+        };
+        let mut open = room_result(Room::Open, 0);
+        open.adjustment = Some(Adjustment::Assigned {
+            scores: vec![weighted(60.0, 620), weighted(40.0, -100)],
+        });
+        let closed = room_result(Room::Closed, 170);
+        // 60% of +10 and 40% of -7.
+        assert_eq!(compare_results(&open, &closed), 3);
+    }
+}
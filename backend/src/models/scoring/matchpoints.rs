@@ -0,0 +1,363 @@
+use std::collections::BTreeMap;
+
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+use crate::models::board::BoardMongoDTO;
+
+/// ACBL awards 1 matchpoint for each score beaten and 1/2 for each tie; the EBU awards 2 and 1.
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq, Default)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum MatchpointConvention {
+    #[default]
+    Acbl,
+    Ebu,
+}
+
+impl MatchpointConvention {
+    pub fn per_beat(&self) -> f64 {
+        match self {
+            MatchpointConvention::Acbl => 1.0,
+            MatchpointConvention::Ebu => 2.0,
+        }
+    }
+
+    /// Top on a board played `times_played` times.
+    pub fn top(&self, times_played: usize) -> f64 {
+        times_played.saturating_sub(1) as f64 * self.per_beat()
+    }
+}
+
+/// Matchpoints for each score on a board, in the same order, from the point of view of
+/// the side the scores belong to.
+pub fn matchpoint_scores(scores: &[i32], convention: MatchpointConvention) -> Vec<f64> {
+    let per_beat = convention.per_beat();
+    scores
+        .iter()
+        .map(|score| {
+            let beaten = scores.iter().filter(|other| *other < score).count() as f64;
+            let tied = scores.iter().filter(|other| *other == score).count() as f64 - 1.0;
+            beaten * per_beat + tied * per_beat / 2.0
+        })
+        .collect()
+}
+
+/// Like [`matchpoint_scores`], for results that count as several scores with weights
+/// adding up to 1, as assigned adjusted scores do. Each score earns its weight's share of
+/// what it would earn against every score of the other results, themselves weighted.
+pub fn weighted_matchpoint_scores(
+    results: &[Vec<(f64, i32)>],
+    convention: MatchpointConvention,
+) -> Vec<f64> {
+    let per_beat = convention.per_beat();
+    results
+        .iter()
+        .enumerate()
+        .map(|(index, result)| {
+            result
+                .iter()
+                .map(|(weight, score)| {
+                    let earned: f64 = results
+                        .iter()
+                        .enumerate()
+                        .filter(|(other, _)| *other != index)
+                        .flat_map(|(_, other)| other.iter())
+                        .map(|(other_weight, other_score)| {
+                            other_weight
+                                * match score.cmp(other_score) {
+                                    std::cmp::Ordering::Greater => per_beat,
+                                    std::cmp::Ordering::Equal => per_beat / 2.0,
+                                    std::cmp::Ordering::Less => 0.0,
+                                }
+                        })
+                        .sum();
+                    weight * earned
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Neuberg's formula: rescales matchpoints earned on a board played `times_played` times
+/// to what they would be worth had it been played `expected` times.
+pub fn neuberg(
+    matchpoints: f64,
+    times_played: usize,
+    expected: usize,
+    convention: MatchpointConvention,
+) -> f64 {
+    if times_played == 0 || times_played == expected {
+        return matchpoints;
+    }
+    let half = convention.per_beat() / 2.0;
+    (matchpoints + half) * expected as f64 / times_played as f64 - half
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultMatchpoints {
+    pub ns_pair: Option<u32>,
+    pub ew_pair: Option<u32>,
+    pub score: i32,
+    /// The director adjusted the score.
+    pub adjusted: bool,
+    pub ns_matchpoints: f64,
+    pub ew_matchpoints: f64,
+    pub ns_percentage: f64,
+    pub ew_percentage: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardMatchpoints {
+    pub board_number: u32,
+    pub times_played: usize,
+    /// Top after factoring, i.e. the session top.
+    pub top: f64,
+    pub results: Vec<ResultMatchpoints>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PairMatchpoints {
+    pub pair_number: u32,
+    pub boards_played: usize,
+    pub matchpoints: f64,
+    pub maximum: f64,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PairsMatchpoints {
+    pub convention: MatchpointConvention,
+    pub top: f64,
+    pub boards: Vec<BoardMatchpoints>,
+    pub pairs: Vec<PairMatchpoints>,
+}
+
+/// Matchpoints every board of a pairs session. Boards played fewer times than the most
+/// played board are factored up with Neuberg's formula, so every board carries the same
+/// top. Pairs are identified by their pair number and must be numbered uniquely across
+/// both directions.
+///
+/// Assigned adjusted scores are matchpointed outcome by outcome and weighted. Artificial
+/// adjusted scores earn their percentage of the top and take no part in the comparisons,
+/// so the board is factored as if played that many fewer times.
+pub fn pairs_matchpoints(
+    boards: &[BoardMongoDTO],
+    convention: MatchpointConvention,
+) -> PairsMatchpoints {
+    let expected = boards
+        .iter()
+        .map(|board| board.results.len())
+        .max()
+        .unwrap_or(0);
+    let top = convention.top(expected);
+    let mut pair_totals: BTreeMap<u32, (usize, f64)> = BTreeMap::new();
+
+    let board_matchpoints = boards
+        .iter()
+        .map(|board| {
+            let times_played = board.results.len();
+            let compared: Vec<Vec<(f64, i32)>> = board
+                .results
+                .iter()
+                .filter(|result| result.artificial().is_none())
+                .map(|result| result.weighted_scores())
+                .collect();
+            let times_compared = compared.len();
+            let mut ns_raw = weighted_matchpoint_scores(&compared, convention).into_iter();
+            let board_top = convention.top(times_compared);
+            let results = board
+                .results
+                .iter()
+                .map(|result| {
+                    let (ns_matchpoints, ew_matchpoints) = match result.artificial() {
+                        Some((ns, ew)) => {
+                            (top * ns.percentage() / 100.0, top * ew.percentage() / 100.0)
+                        }
+                        None => {
+                            let ns_raw = ns_raw.next().unwrap_or(0.0);
+                            let ew_raw = board_top - ns_raw;
+                            (
+                                neuberg(ns_raw, times_compared, expected, convention),
+                                neuberg(ew_raw, times_compared, expected, convention),
+                            )
+                        }
+                    };
+                    for (pair, matchpoints) in [
+                        (result.ns_pair, ns_matchpoints),
+                        (result.ew_pair, ew_matchpoints),
+                    ] {
+                        if let Some(pair) = pair {
+                            let entry = pair_totals.entry(pair).or_insert((0, 0.0));
+                            entry.0 += 1;
+                            entry.1 += matchpoints;
+                        }
+                    }
+                    ResultMatchpoints {
+                        ns_pair: result.ns_pair,
+                        ew_pair: result.ew_pair,
+                        score: result.score,
+                        adjusted: result.adjustment.is_some(),
+                        ns_matchpoints: round2(ns_matchpoints),
+                        ew_matchpoints: round2(ew_matchpoints),
+                        ns_percentage: percentage(ns_matchpoints, top),
+                        ew_percentage: percentage(ew_matchpoints, top),
+                    }
+                })
+                .collect();
+            BoardMatchpoints {
+                board_number: board.board_number,
+                times_played,
+                top,
+                results,
+            }
+        })
+        .collect();
+
+    let pairs = pair_totals
+        .into_iter()
+        .map(|(pair_number, (boards_played, matchpoints))| {
+            let maximum = top * boards_played as f64;
+            PairMatchpoints {
+                pair_number,
+                boards_played,
+                matchpoints: round2(matchpoints),
+                maximum,
+                percentage: percentage(matchpoints, maximum),
+            }
+        })
+        .collect();
+
+    PairsMatchpoints {
+        convention,
+        top,
+        boards: board_matchpoints,
+        pairs,
+    }
+}
+
+fn percentage(matchpoints: f64, maximum: f64) -> f64 {
+    if maximum > 0.0 {
+        round2(matchpoints / maximum * 100.0)
+    } else {
+        50.0
+    }
+}
+
+pub(crate) fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        adjustment::{Adjustment, ArtificialScore},
+        scoring::fixtures::{board, pair_result},
+    };
+
+    #[test]
+    fn matchpoints_with_ties() {
+        let scores = [400, 420, 420, -50];
+        assert_eq!(
+            matchpoint_scores(&scores, MatchpointConvention::Acbl),
+            vec![1.0, 2.5, 2.5, 0.0]
+        );
+        assert_eq!(
+            matchpoint_scores(&scores, MatchpointConvention::Ebu),
+            vec![2.0, 5.0, 5.0, 0.0]
+        );
+        assert_eq!(MatchpointConvention::Acbl.top(4), 3.0);
+        assert_eq!(MatchpointConvention::Ebu.top(4), 6.0);
+    }
+
+    #[test]
+    fn neuberg_factors_up_to_the_full_field() {
+        let acbl = MatchpointConvention::Acbl;
+        assert_eq!(round2(neuberg(2.0, 3, 4, acbl)), 2.83);
+        assert_eq!(round2(neuberg(1.0, 3, 4, acbl)), 1.5);
+        assert_eq!(round2(neuberg(0.0, 3, 4, acbl)), 0.17);
+        assert_eq!(round2(neuberg(4.0, 3, 4, MatchpointConvention::Ebu)), 5.67);
+        assert_eq!(neuberg(2.0, 4, 4, acbl), 2.0);
+    }
+
+    #[test]
+    fn unequal_field_has_one_top() {
+        let boards = [
+            board(
+                1,
+                vec![
+                    pair_result(1, 5, 420),
+                    pair_result(2, 6, 420),
+                    pair_result(3, 7, 170),
+                    pair_result(4, 8, -50),
+                ],
+            ),
+            board(
+                2,
+                vec![
+                    pair_result(1, 6, 100),
+                    pair_result(2, 7, 200),
+                    pair_result(3, 8, 300),
+                ],
+            ),
+        ];
+        let summary = pairs_matchpoints(&boards, MatchpointConvention::Acbl);
+        assert_eq!(summary.top, 3.0);
+        let first: Vec<f64> = summary.boards[0]
+            .results
+            .iter()
+            .map(|result| result.ns_matchpoints)
+            .collect();
+        assert_eq!(first, vec![2.5, 2.5, 1.0, 0.0]);
+        let second: Vec<(f64, f64)> = summary.boards[1]
+            .results
+            .iter()
+            .map(|result| (result.ns_matchpoints, result.ew_matchpoints))
+            .collect();
+        assert_eq!(second, vec![(0.17, 2.83), (1.5, 1.5), (2.83, 0.17)]);
+        assert!(summary.boards.iter().all(|board| board.top == 3.0));
+
+        let pair = |number| {
+            summary
+                .pairs
+                .iter()
+                .find(|pair| pair.pair_number == number)
+                .unwrap()
+        };
+        assert_eq!(pair(1).boards_played, 2);
+        assert_eq!(pair(1).maximum, 6.0);
+        assert_eq!(pair(3).matchpoints, 3.83);
+        assert_eq!(pair(4).percentage, 0.0);
+    }
+
+    #[test]
+    fn artificial_scores_are_left_out_of_the_comparison() {
+        let mut average_plus = pair_result(3, 7, 0);
+        average_plus.adjustment = Some(Adjustment::Artificial {
+            ns: ArtificialScore::AveragePlus,
+            ew: ArtificialScore::AverageMinus,
+        });
+        let boards = [board(
+            1,
+            vec![
+                pair_result(1, 5, 420),
+                pair_result(2, 6, 450),
+                average_plus,
+                pair_result(4, 8, 400),
+            ],
+        )];
+        let summary = pairs_matchpoints(&boards, MatchpointConvention::Acbl);
+        let results = &summary.boards[0].results;
+        assert_eq!(results[2].ns_matchpoints, 1.8);
+        assert_eq!(results[2].ew_matchpoints, 1.2);
+        assert!(results[2].adjusted);
+        // The other three are factored from a top of 2 to a top of 3.
+        assert_eq!(results[1].ns_matchpoints, 2.83);
+        assert_eq!(results[0].ns_matchpoints, 1.5);
+        assert_eq!(results[3].ns_matchpoints, 0.17);
+    }
+}
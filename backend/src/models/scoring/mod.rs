@@ -0,0 +1,69 @@
+pub mod board_a_match;
+pub mod duplicate;
+pub mod imp;
+pub mod matchpoints;
+pub mod pairs_imps;
+pub mod rubber;
+pub mod total_points;
+pub mod victory_points;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScoringError {
+    #[error("Invalid number of tricks taken: {0}")]
+    InvalidTricks(u8),
+}
+
+/// Boards and results for the scoring tests, with only the scores filled in.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use bson::oid::ObjectId;
+
+    use crate::models::board::{
+        dealer_for_board, vulnerability_for_board, BoardMongoDTO, BoardResult, Room,
+    };
+
+    fn result(
+        room: Option<Room>,
+        ns_pair: Option<u32>,
+        ew_pair: Option<u32>,
+        score: i32,
+    ) -> BoardResult {
+        BoardResult {
+            room,
+            ns_pair,
+            ew_pair,
+            contract: None,
+            declarer: None,
+            opening_lead: None,
+            tricks: None,
+            auction: None,
+            play: None,
+            claim: None,
+            adjustment: None,
+            score,
+        }
+    }
+
+    /// A team match result, North-South's score in one room.
+    pub fn room_result(room: Room, score: i32) -> BoardResult {
+        result(Some(room), None, None, score)
+    }
+
+    /// A pairs result, North-South's score for pair `ns_pair` against `ew_pair`.
+    pub fn pair_result(ns_pair: u32, ew_pair: u32, score: i32) -> BoardResult {
+        result(None, Some(ns_pair), Some(ew_pair), score)
+    }
+
+    pub fn board(board_number: u32, results: Vec<BoardResult>) -> BoardMongoDTO {
+        BoardMongoDTO {
+            id: ObjectId::new(),
+            session_id: ObjectId::new(),
+            board_number,
+            dealer: dealer_for_board(board_number).unwrap(),
+            vulnerability: vulnerability_for_board(board_number).unwrap(),
+            deal: None,
+            results,
+            dd_table: None,
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! IMP scoring for pairs events. Butler scores each result in IMPs against a datum, the
+//! mean of the board's results with the highest and lowest left out. Cross-IMPs score
+//! each result against every other result on the board and average them.
+//!
+//! Artificial adjusted scores earn their IMPs directly and are left out of the datum and
+//! the comparisons. Assigned scores count at their weighted mean towards the datum and
+//! are otherwise scored outcome by outcome and weighted.
+use std::collections::BTreeMap;
+
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+use crate::models::board::BoardMongoDTO;
+
+use super::{imp::imps, matchpoints::round2};
+
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PairsImpMethod {
+    Butler,
+    CrossImp,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultImps {
+    pub ns_pair: Option<u32>,
+    pub ew_pair: Option<u32>,
+    pub score: i32,
+    /// The director adjusted the score.
+    pub adjusted: bool,
+    pub ns_imps: f64,
+    pub ew_imps: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardPairsImps {
+    pub board_number: u32,
+    pub times_played: usize,
+    /// Butler only.
+    pub datum: Option<i32>,
+    pub results: Vec<ResultImps>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PairImps {
+    pub pair_number: u32,
+    pub boards_played: usize,
+    pub imps: f64,
+    pub imps_per_board: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PairsImps {
+    pub method: PairsImpMethod,
+    pub boards: Vec<BoardPairsImps>,
+    pub pairs: Vec<PairImps>,
+}
+
+/// Butler datum: the mean North-South score, leaving out the top and bottom tenth of the
+/// results (at least one each way once there are three), rounded to the nearest 10.
+pub fn butler_datum(scores: &[i32]) -> Option<i32> {
+    if scores.is_empty() {
+        return None;
+    }
+    let mut sorted = scores.to_vec();
+    sorted.sort_unstable();
+    let trim = if sorted.len() >= 3 {
+        (sorted.len() / 10).max(1)
+    } else {
+        0
+    };
+    let kept = &sorted[trim..sorted.len() - trim];
+    let mean = kept.iter().sum::<i32>() as f64 / kept.len() as f64;
+    Some(((mean / 10.0).round() * 10.0) as i32)
+}
+
+/// North-South IMPs for each score on a board, in the same order.
+pub fn cross_imp_scores(scores: &[i32]) -> Vec<f64> {
+    if scores.len() < 2 {
+        return vec![0.0; scores.len()];
+    }
+    let comparisons = (scores.len() - 1) as f64;
+    scores
+        .iter()
+        .map(|score| {
+            let total: i32 = scores.iter().map(|other| imps(score - other)).sum();
+            total as f64 / comparisons
+        })
+        .collect()
+}
+
+/// Like [`cross_imp_scores`], for results that count as several weighted scores adding
+/// up to 1.
+pub fn weighted_cross_imp_scores(results: &[Vec<(f64, i32)>]) -> Vec<f64> {
+    if results.len() < 2 {
+        return vec![0.0; results.len()];
+    }
+    let comparisons = (results.len() - 1) as f64;
+    results
+        .iter()
+        .enumerate()
+        .map(|(index, result)| {
+            let total: f64 = result
+                .iter()
+                .map(|(weight, score)| {
+                    let against: f64 = results
+                        .iter()
+                        .enumerate()
+                        .filter(|(other, _)| *other != index)
+                        .flat_map(|(_, other)| other.iter())
+                        .map(|(other_weight, other_score)| {
+                            other_weight * imps(score - other_score) as f64
+                        })
+                        .sum();
+                    weight * against
+                })
+                .sum();
+            total / comparisons
+        })
+        .collect()
+}
+
+/// Scores every board of a pairs session in IMPs. Pairs are identified by their pair
+/// number, as for matchpoints.
+pub fn pairs_imps(boards: &[BoardMongoDTO], method: PairsImpMethod) -> PairsImps {
+    let mut pair_totals: BTreeMap<u32, (usize, f64)> = BTreeMap::new();
+    let board_imps = boards
+        .iter()
+        .map(|board| {
+            let compared: Vec<Vec<(f64, i32)>> = board
+                .results
+                .iter()
+                .filter(|result| result.artificial().is_none())
+                .map(|result| result.weighted_scores())
+                .collect();
+            let (datum, ns_imps) = match method {
+                PairsImpMethod::Butler => {
+                    let means: Vec<i32> = compared
+                        .iter()
+                        .map(|scores| {
+                            let mean: f64 = scores
+                                .iter()
+                                .map(|(weight, score)| weight * *score as f64)
+                                .sum();
+                            mean.round() as i32
+                        })
+                        .collect();
+                    let datum = butler_datum(&means);
+                    let ns_imps = compared
+                        .iter()
+                        .map(|scores| {
+                            scores
+                                .iter()
+                                .map(|(weight, score)| {
+                                    weight * imps(score - datum.unwrap_or(0)) as f64
+                                })
+                                .sum()
+                        })
+                        .collect();
+                    (datum, ns_imps)
+                }
+                PairsImpMethod::CrossImp => (None, weighted_cross_imp_scores(&compared)),
+            };
+            let mut ns_imps = ns_imps.into_iter();
+            let results = board
+                .results
+                .iter()
+                .map(|result| {
+                    let (ns_imps, ew_imps) = match result.artificial() {
+                        Some((ns, ew)) => (ns.imps() as f64, ew.imps() as f64),
+                        None => {
+                            let ns_imps = ns_imps.next().unwrap_or(0.0);
+                            (ns_imps, -ns_imps)
+                        }
+                    };
+                    for (pair, imps) in [(result.ns_pair, ns_imps), (result.ew_pair, ew_imps)] {
+                        if let Some(pair) = pair {
+                            let entry = pair_totals.entry(pair).or_insert((0, 0.0));
+                            entry.0 += 1;
+                            entry.1 += imps;
+                        }
+                    }
+                    ResultImps {
+                        ns_pair: result.ns_pair,
+                        ew_pair: result.ew_pair,
+                        score: result.score,
+                        adjusted: result.adjustment.is_some(),
+                        ns_imps: round2(ns_imps),
+                        ew_imps: round2(ew_imps),
+                    }
+                })
+                .collect();
+            BoardPairsImps {
+                board_number: board.board_number,
+                times_played: board.results.len(),
+                datum,
+                results,
+            }
+        })
+        .collect();
+
+    let pairs = pair_totals
+        .into_iter()
+        .map(|(pair_number, (boards_played, imps))| PairImps {
+            pair_number,
+            boards_played,
+            imps: round2(imps),
+            imps_per_board: if boards_played > 0 {
+                round2(imps / boards_played as f64)
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    PairsImps {
+        method,
+        boards: board_imps,
+        pairs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::adjustment::{Adjustment, ArtificialScore, WeightedScore};
+    use crate::models::board::BoardResult;
+    use crate::models::scoring::fixtures::{board, pair_result};
+
+    fn artificial(
+        ns_pair: u32,
+        ew_pair: u32,
+        ns: ArtificialScore,
+        ew: ArtificialScore,
+    ) -> BoardResult {
+        let mut result = pair_result(ns_pair, ew_pair, 0);
+        result.adjustment = Some(Adjustment::Artificial { ns, ew });
+        result
+    }
+
+    fn assigned(ns_pair: u32, ew_pair: u32, scores: &[(f64, i32)]) -> BoardResult {
+        let mut result = pair_result(ns_pair, ew_pair, scores[0].1);
+        result.adjustment = Some(Adjustment::Assigned {
+            scores: scores
+                .iter()
+                .map(|&(weight, score)| WeightedScore {
+                    weight,
+                    contract: None,
+                    declarer: None,
+                    tricks: None,
+                    score,
+                })
+                .collect(),
+        });
+        result
+    }
+
+    #[test]
+    fn butler_datum_trims_the_extremes() {
+        assert_eq!(butler_datum(&[420, 450, 420, -50, 420]), Some(420));
+        assert_eq!(butler_datum(&[100, 200]), Some(150));
+        assert_eq!(butler_datum(&[100, 110, 140]), Some(110));
+        assert_eq!(butler_datum(&[-620, 100, 170, 140]), Some(120));
+        assert_eq!(butler_datum(&[]), None);
+    }
+
+    #[test]
+    fn butler_datum_trims_a_tenth_each_way() {
+        let mut scores = vec![100; 18];
+        scores.extend([2000, 2000, -2000, -2000]);
+        assert_eq!(butler_datum(&scores), Some(100));
+    }
+
+    #[test]
+    fn cross_imps_average_every_comparison() {
+        assert_eq!(cross_imp_scores(&[420, 450, -50]), vec![4.5, 6.0, -10.5]);
+        assert_eq!(cross_imp_scores(&[100]), vec![0.0]);
+        assert!(cross_imp_scores(&[]).is_empty());
+    }
+
+    #[test]
+    fn weighted_cross_imps_match_unweighted_ones() {
+        let scores = [420, 450, -50, 170];
+        let weighted: Vec<Vec<(f64, i32)>> =
+            scores.iter().map(|&score| vec![(1.0, score)]).collect();
+        assert_eq!(
+            weighted_cross_imp_scores(&weighted),
+            cross_imp_scores(&scores)
+        );
+    }
+
+    #[test]
+    fn weighted_cross_imps_weigh_each_outcome() {
+        let results = vec![vec![(1.0, 420)], vec![(0.3, 420), (0.7, -50)]];
+        let imps = weighted_cross_imp_scores(&results);
+        assert!((imps[0] - 7.0).abs() < 1e-9);
+        assert!((imps[1] + 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn butler_scores_against_the_datum() {
+        let boards = [board(
+            1,
+            vec![
+                pair_result(1, 2, 420),
+                pair_result(3, 4, 450),
+                pair_result(5, 6, 420),
+                pair_result(7, 8, -50),
+                pair_result(9, 10, 420),
+            ],
+        )];
+        let scored = pairs_imps(&boards, PairsImpMethod::Butler);
+        assert_eq!(scored.method, PairsImpMethod::Butler);
+        assert_eq!(scored.boards[0].datum, Some(420));
+        assert_eq!(scored.boards[0].times_played, 5);
+        let ns: Vec<f64> = scored.boards[0].results.iter().map(|r| r.ns_imps).collect();
+        assert_eq!(ns, vec![0.0, 1.0, 0.0, -10.0, 0.0]);
+        let ew: Vec<f64> = scored.boards[0].results.iter().map(|r| r.ew_imps).collect();
+        assert_eq!(ew, vec![0.0, -1.0, 0.0, 10.0, 0.0]);
+    }
+
+    #[test]
+    fn artificial_scores_stay_out_of_the_datum() {
+        let boards = [board(
+            1,
+            vec![
+                pair_result(1, 2, 420),
+                pair_result(3, 4, 420),
+                pair_result(5, 6, -50),
+                artificial(
+                    7,
+                    8,
+                    ArtificialScore::AveragePlus,
+                    ArtificialScore::AverageMinus,
+                ),
+            ],
+        )];
+        for method in [PairsImpMethod::Butler, PairsImpMethod::CrossImp] {
+            let scored = pairs_imps(&boards, method);
+            let result = &scored.boards[0].results[3];
+            assert!(result.adjusted);
+            assert_eq!((result.ns_imps, result.ew_imps), (3.0, -3.0));
+        }
+        let butler = pairs_imps(&boards, PairsImpMethod::Butler);
+        assert_eq!(butler.boards[0].datum, Some(420));
+        assert_eq!(butler.boards[0].results[2].ns_imps, -10.0);
+        let cross = pairs_imps(&boards, PairsImpMethod::CrossImp);
+        assert_eq!(cross.boards[0].datum, None);
+        assert_eq!(cross.boards[0].results[0].ns_imps, 5.0);
+        assert_eq!(cross.boards[0].results[2].ns_imps, -10.0);
+    }
+
+    #[test]
+    fn assigned_scores_are_weighted() {
+        let boards = [
+            board(
+                1,
+                vec![
+                    assigned(1, 2, &[(30.0, 420), (70.0, -50)]),
+                    pair_result(3, 4, 420),
+                    pair_result(5, 6, 420),
+                ],
+            ),
+            board(2, vec![pair_result(1, 2, 100), pair_result(3, 4, 100)]),
+        ];
+        let scored = pairs_imps(&boards, PairsImpMethod::Butler);
+        assert_eq!(scored.boards[0].datum, Some(420));
+        assert!(scored.boards[0].results[0].adjusted);
+        assert_eq!(scored.boards[0].results[0].ns_imps, -7.0);
+        assert_eq!(scored.boards[0].results[0].ew_imps, 7.0);
+
+        let pair_one = &scored.pairs[0];
+        assert_eq!(pair_one.pair_number, 1);
+        assert_eq!(pair_one.boards_played, 2);
+        assert_eq!(pair_one.imps, -7.0);
+        assert_eq!(pair_one.imps_per_board, -3.5);
+        let pair_two = &scored.pairs[1];
+        assert_eq!((pair_two.pair_number, pair_two.imps), (2, 7.0));
+    }
+}
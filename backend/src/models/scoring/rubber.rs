@@ -0,0 +1,524 @@
+//! Rubber bridge and Chicago, scored on a running scorecard from a session's boards in
+//! order, one result per board.
+//!
+//! Neither form uses the boards' duplicate vulnerability. In rubber bridge a side is
+//! vulnerable once it has won a game, and a rubber ends when a side wins two. Chicago is
+//! played in sets of four deals: nobody is vulnerable on the first, the dealer's side on
+//! the second and third, and both sides on the fourth. A passed-out deal in Chicago is
+//! redealt, so it does not count towards the four.
+//!
+//! Honors are scored when the board has its deal recorded.
+use serde::Serialize;
+
+use crate::models::{
+    board::BoardMongoDTO,
+    card::{Card, Rank, Suit},
+    contract::{Contract, Seat, Strain, Vulnerability},
+    deal::Deal,
+    player::Direction,
+};
+
+use super::duplicate::{
+    contract_trick_score, insult_bonus, overtrick_score, slam_bonus, undertrick_penalty,
+};
+
+/// Points below the line that make a game.
+pub const GAME: i32 = 100;
+
+#[derive(Debug, Serialize, Copy, Clone, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SideScores {
+    pub ns: i32,
+    pub ew: i32,
+}
+
+impl SideScores {
+    fn add(&mut self, side: Direction, points: i32) {
+        match side {
+            Direction::NorthSouth => self.ns += points,
+            Direction::EastWest => self.ew += points,
+        }
+    }
+
+    fn get(&self, side: Direction) -> i32 {
+        match side {
+            Direction::NorthSouth => self.ns,
+            Direction::EastWest => self.ew,
+        }
+    }
+
+    fn plus(self, other: SideScores) -> SideScores {
+        SideScores {
+            ns: self.ns + other.ns,
+            ew: self.ew + other.ew,
+        }
+    }
+}
+
+/// One line of the scorecard.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScorecardEntry {
+    pub board_number: u32,
+    pub vulnerability: Vulnerability,
+    pub contract: Option<Contract>,
+    pub declarer: Option<Seat>,
+    pub tricks: Option<u8>,
+    pub below_the_line: SideScores,
+    /// Overtricks, bonuses, penalties and honors, plus game and part-score bonuses in
+    /// Chicago.
+    pub above_the_line: SideScores,
+    /// The side that completed a game with this deal.
+    pub game: Option<Direction>,
+    pub running_total: SideScores,
+}
+
+/// A rubber, or one set of four deals in Chicago.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Rubber {
+    pub number: u32,
+    pub entries: Vec<ScorecardEntry>,
+    pub ns_games: u32,
+    pub ew_games: u32,
+    /// The rubber bonus, or what is scored for a rubber left unfinished.
+    pub bonus: SideScores,
+    pub totals: SideScores,
+    pub finished: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RubberScorecard {
+    pub rubbers: Vec<Rubber>,
+    pub totals: SideScores,
+}
+
+/// Vulnerability on a Chicago deal, counting from 0 within the set of four.
+pub fn chicago_vulnerability(deal_index: usize, dealer: Seat) -> Vulnerability {
+    match deal_index % 4 {
+        0 => Vulnerability::None,
+        3 => Vulnerability::Both,
+        _ if dealer.is_north_south() => Vulnerability::Ns,
+        _ => Vulnerability::Ew,
+    }
+}
+
+pub fn rubber_scorecard(boards: &[BoardMongoDTO]) -> RubberScorecard {
+    let mut rubbers = Vec::new();
+    let mut rubber = Rubber::new(1);
+    let mut below = SideScores::default();
+    for board in boards.iter().filter(|board| !board.results.is_empty()) {
+        let vulnerability = match (rubber.ns_games > 0, rubber.ew_games > 0) {
+            (false, false) => Vulnerability::None,
+            (true, false) => Vulnerability::Ns,
+            (false, true) => Vulnerability::Ew,
+            (true, true) => Vulnerability::Both,
+        };
+        let mut entry = score_deal(board, vulnerability);
+        below = below.plus(entry.below_the_line);
+        if let Some(side) = game_made(&entry, below) {
+            entry.game = Some(side);
+            below = SideScores::default();
+            rubber.win_game(side);
+        }
+        rubber.push(entry);
+        if rubber.ns_games == 2 || rubber.ew_games == 2 {
+            let (winner, loser_games) = if rubber.ns_games == 2 {
+                (Direction::NorthSouth, rubber.ew_games)
+            } else {
+                (Direction::EastWest, rubber.ns_games)
+            };
+            rubber
+                .bonus
+                .add(winner, if loser_games == 0 { 700 } else { 500 });
+            let next = Rubber::new(rubber.number + 1);
+            rubbers.push(std::mem::replace(&mut rubber, next).finish(true));
+        }
+    }
+    if !rubber.entries.is_empty() {
+        // Unfinished: 300 for the only side with a game, 100 for a part score.
+        match (rubber.ns_games, rubber.ew_games) {
+            (1, 0) => rubber.bonus.add(Direction::NorthSouth, 300),
+            (0, 1) => rubber.bonus.add(Direction::EastWest, 300),
+            _ => {}
+        }
+        for side in [Direction::NorthSouth, Direction::EastWest] {
+            if below.get(side) > 0 {
+                rubber.bonus.add(side, 100);
+            }
+        }
+        rubbers.push(rubber.finish(false));
+    }
+    RubberScorecard::new(rubbers)
+}
+
+pub fn chicago_scorecard(boards: &[BoardMongoDTO]) -> RubberScorecard {
+    let mut rubbers = Vec::new();
+    let mut rubber = Rubber::new(1);
+    let mut below = SideScores::default();
+    let mut deal_index = 0;
+    for board in boards {
+        let Some(result) = board.results.first() else {
+            continue;
+        };
+        let vulnerability = chicago_vulnerability(deal_index, board.dealer);
+        let mut entry = score_deal(board, vulnerability);
+        if result.contract.is_none() {
+            rubber.push(entry);
+            continue;
+        }
+        below = below.plus(entry.below_the_line);
+        if let Some(side) = game_made(&entry, below) {
+            let vulnerable = vulnerability.is_vulnerable(seat_of(side));
+            entry.game = Some(side);
+            entry
+                .above_the_line
+                .add(side, if vulnerable { 500 } else { 300 });
+            below = SideScores::default();
+            rubber.win_game(side);
+        } else if deal_index == 3 {
+            for side in [Direction::NorthSouth, Direction::EastWest] {
+                if entry.below_the_line.get(side) > 0 {
+                    entry.above_the_line.add(side, 100);
+                }
+            }
+        }
+        rubber.push(entry);
+        deal_index += 1;
+        if deal_index == 4 {
+            deal_index = 0;
+            below = SideScores::default();
+            let next = Rubber::new(rubber.number + 1);
+            rubbers.push(std::mem::replace(&mut rubber, next).finish(true));
+        }
+    }
+    if !rubber.entries.is_empty() {
+        rubbers.push(rubber.finish(false));
+    }
+    RubberScorecard::new(rubbers)
+}
+
+impl Rubber {
+    fn new(number: u32) -> Self {
+        Rubber {
+            number,
+            entries: Vec::new(),
+            ns_games: 0,
+            ew_games: 0,
+            bonus: SideScores::default(),
+            totals: SideScores::default(),
+            finished: false,
+        }
+    }
+
+    fn win_game(&mut self, side: Direction) {
+        match side {
+            Direction::NorthSouth => self.ns_games += 1,
+            Direction::EastWest => self.ew_games += 1,
+        }
+    }
+
+    fn push(&mut self, mut entry: ScorecardEntry) {
+        self.totals = self
+            .totals
+            .plus(entry.below_the_line)
+            .plus(entry.above_the_line);
+        entry.running_total = self.totals;
+        self.entries.push(entry);
+    }
+
+    fn finish(mut self, finished: bool) -> Self {
+        self.totals = self.totals.plus(self.bonus);
+        self.finished = finished;
+        self
+    }
+}
+
+impl RubberScorecard {
+    fn new(rubbers: Vec<Rubber>) -> Self {
+        let totals = rubbers
+            .iter()
+            .fold(SideScores::default(), |totals, rubber| {
+                totals.plus(rubber.totals)
+            });
+        RubberScorecard { rubbers, totals }
+    }
+}
+
+fn seat_of(side: Direction) -> Seat {
+    match side {
+        Direction::NorthSouth => Seat::North,
+        Direction::EastWest => Seat::East,
+    }
+}
+
+/// The side whose contract just took its below-the-line score to a game.
+fn game_made(entry: &ScorecardEntry, below: SideScores) -> Option<Direction> {
+    let side = Direction::of(entry.declarer?);
+    (entry.below_the_line.get(side) > 0 && below.get(side) >= GAME).then_some(side)
+}
+
+/// Scores one deal without regard to games: trick score below the line, everything
+/// else above it.
+fn score_deal(board: &BoardMongoDTO, vulnerability: Vulnerability) -> ScorecardEntry {
+    let result = &board.results[0];
+    let mut entry = ScorecardEntry {
+        board_number: board.board_number,
+        vulnerability,
+        contract: result.contract,
+        declarer: result.declarer,
+        tricks: result.tricks,
+        below_the_line: SideScores::default(),
+        above_the_line: SideScores::default(),
+        game: None,
+        running_total: SideScores::default(),
+    };
+    let (Some(contract), Some(declarer), Some(tricks)) =
+        (&result.contract, result.declarer, result.tricks)
+    else {
+        return entry;
+    };
+    let side = Direction::of(declarer);
+    let vulnerable = vulnerability.is_vulnerable(declarer);
+    let required = contract.tricks_required();
+    if tricks >= required {
+        entry
+            .below_the_line
+            .add(side, contract_trick_score(contract));
+        entry.above_the_line.add(
+            side,
+            overtrick_score(contract, vulnerable, tricks - required)
+                + slam_bonus(contract.level, vulnerable)
+                + insult_bonus(contract.doubled),
+        );
+    } else {
+        entry.above_the_line.add(
+            Direction::of(declarer.next()),
+            undertrick_penalty(contract.doubled, vulnerable, required - tricks),
+        );
+    }
+    if let Some((holder, points)) = board
+        .deal
+        .as_ref()
+        .and_then(|deal| honors(deal, contract.strain))
+    {
+        entry.above_the_line.add(Direction::of(holder), points);
+    }
+    entry
+}
+
+/// Honors held in one hand: four or five of the top trumps, or all four aces at no trump.
+fn honors(deal: &Deal, strain: Strain) -> Option<(Seat, i32)> {
+    Seat::ALL.into_iter().find_map(|seat| {
+        let hand = deal.hand(seat);
+        let points = match strain.trump() {
+            Some(trump) => {
+                let held = [Rank::ACE, Rank::KING, Rank::QUEEN, Rank::JACK, Rank::TEN]
+                    .into_iter()
+                    .filter(|rank| hand.contains(Card::new(trump, *rank)))
+                    .count();
+                match held {
+                    5 => 150,
+                    4 => 100,
+                    _ => 0,
+                }
+            }
+            None if Suit::ALL
+                .into_iter()
+                .all(|suit| hand.contains(Card::new(suit, Rank::ACE))) =>
+            {
+                150
+            }
+            None => 0,
+        };
+        (points > 0).then_some((seat, points))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::scoring::fixtures::{board, pair_result};
+
+    /// A board with one result; `None` for a passed-out deal.
+    fn played(board_number: u32, result: Option<(&str, Seat, u8)>) -> BoardMongoDTO {
+        let mut entry = pair_result(1, 2, 0);
+        if let Some((contract, declarer, tricks)) = result {
+            entry.contract = Some(contract.parse().unwrap());
+            entry.declarer = Some(declarer);
+            entry.tricks = Some(tricks);
+        }
+        board(board_number, vec![entry])
+    }
+
+    fn scores(ns: i32, ew: i32) -> SideScores {
+        SideScores { ns, ew }
+    }
+
+    #[test]
+    fn rubber_won_two_games_to_one() {
+        let boards = vec![
+            played(1, Some(("2S", Seat::North, 9))),
+            played(2, Some(("2H", Seat::East, 8))),
+            played(3, Some(("2D", Seat::South, 8))),
+            played(4, Some(("3NT", Seat::West, 7))),
+            played(5, Some(("4H", Seat::East, 10))),
+            played(6, Some(("1NT", Seat::North, 7))),
+            played(7, Some(("6C", Seat::South, 12))),
+        ];
+        let scorecard = rubber_scorecard(&boards);
+        assert_eq!(scorecard.rubbers.len(), 1);
+        let rubber = &scorecard.rubbers[0];
+        assert!(rubber.finished);
+        assert_eq!((rubber.ns_games, rubber.ew_games), (2, 1));
+        let games: Vec<Option<Direction>> = rubber.entries.iter().map(|e| e.game).collect();
+        assert_eq!(
+            games,
+            [
+                None,
+                None,
+                Some(Direction::NorthSouth),
+                None,
+                Some(Direction::EastWest),
+                None,
+                Some(Direction::NorthSouth)
+            ]
+        );
+        // A side is vulnerable once it has a game.
+        let vulnerability: Vec<Vulnerability> =
+            rubber.entries.iter().map(|e| e.vulnerability).collect();
+        assert_eq!(
+            vulnerability,
+            [
+                Vulnerability::None,
+                Vulnerability::None,
+                Vulnerability::None,
+                Vulnerability::Ns,
+                Vulnerability::Ns,
+                Vulnerability::Both,
+                Vulnerability::Both
+            ]
+        );
+        // 3NT two down, non-vulnerable, is 100 to North-South; 6C vulnerable earns 750.
+        assert_eq!(rubber.entries[3].above_the_line, scores(100, 0));
+        assert_eq!(rubber.entries[6].above_the_line, scores(750, 0));
+        assert_eq!(rubber.bonus, scores(500, 0));
+        assert_eq!(
+            rubber.totals,
+            scores(60 + 30 + 40 + 100 + 40 + 120 + 750 + 500, 60 + 120)
+        );
+        assert_eq!(scorecard.totals, rubber.totals);
+    }
+
+    #[test]
+    fn two_straight_games_earn_700() {
+        let boards = vec![
+            played(1, Some(("4S", Seat::North, 10))),
+            played(2, Some(("3NT", Seat::South, 9))),
+            played(3, Some(("1C", Seat::East, 7))),
+        ];
+        let scorecard = rubber_scorecard(&boards);
+        assert_eq!(scorecard.rubbers.len(), 2);
+        assert_eq!(scorecard.rubbers[0].bonus, scores(700, 0));
+        assert_eq!(scorecard.rubbers[0].totals, scores(120 + 100 + 700, 0));
+        // The second rubber stops with a part score: 100 for it.
+        let unfinished = &scorecard.rubbers[1];
+        assert!(!unfinished.finished);
+        assert_eq!(unfinished.bonus, scores(0, 100));
+        assert_eq!(unfinished.totals, scores(0, 20 + 100));
+    }
+
+    #[test]
+    fn unfinished_rubber_with_one_game() {
+        let boards = vec![
+            played(1, Some(("5D", Seat::West, 11))),
+            played(2, Some(("2NT", Seat::North, 8))),
+            played(3, Some(("4SX", Seat::East, 8))),
+        ];
+        let rubber = &rubber_scorecard(&boards).rubbers[0];
+        assert!(!rubber.finished);
+        assert_eq!(rubber.entries[1].above_the_line, scores(0, 0));
+        // Vulnerable, doubled, two down.
+        assert_eq!(rubber.entries[2].above_the_line, scores(500, 0));
+        assert_eq!(rubber.bonus, scores(100, 300));
+        assert_eq!(rubber.totals, scores(70 + 500 + 100, 100 + 300));
+    }
+
+    #[test]
+    fn chicago_sets_of_four() {
+        let boards = vec![
+            played(1, Some(("4S", Seat::North, 10))),
+            played(2, None),
+            played(3, Some(("3NT", Seat::South, 9))),
+            played(4, Some(("2H", Seat::West, 8))),
+            played(5, Some(("2S", Seat::West, 8))),
+            played(6, Some(("1NT", Seat::North, 7))),
+        ];
+        let scorecard = chicago_scorecard(&boards);
+        assert_eq!(scorecard.rubbers.len(), 2);
+        let set = &scorecard.rubbers[0];
+        assert!(set.finished);
+        // The passed-out deal is on the card but is not one of the four.
+        assert_eq!(set.entries.len(), 5);
+        let vulnerability: Vec<Vulnerability> =
+            set.entries.iter().map(|e| e.vulnerability).collect();
+        assert_eq!(
+            vulnerability,
+            [
+                Vulnerability::None,
+                Vulnerability::Ew,
+                Vulnerability::Ns,
+                Vulnerability::Ew,
+                Vulnerability::Both
+            ]
+        );
+        assert_eq!(set.entries[0].above_the_line, scores(300, 0));
+        assert_eq!(set.entries[2].above_the_line, scores(500, 0));
+        // 2S on top of 2H makes a vulnerable game on the fourth deal.
+        assert_eq!(set.entries[4].game, Some(Direction::EastWest));
+        assert_eq!(set.totals, scores(420 + 600, 60 + 60 + 500));
+        let next = &scorecard.rubbers[1];
+        assert!(!next.finished);
+        assert_eq!(next.entries[0].vulnerability, Vulnerability::None);
+    }
+
+    #[test]
+    fn chicago_part_score_on_the_fourth_deal() {
+        let boards = vec![
+            played(1, Some(("1NT", Seat::North, 6))),
+            played(2, Some(("1NT", Seat::East, 6))),
+            played(3, Some(("1NT", Seat::South, 6))),
+            played(4, Some(("2C", Seat::West, 8))),
+        ];
+        let set = &chicago_scorecard(&boards).rubbers[0];
+        assert_eq!(set.entries[3].below_the_line, scores(0, 40));
+        assert_eq!(set.entries[3].above_the_line, scores(0, 100));
+    }
+
+    #[test]
+    fn chicago_vulnerability_follows_the_deal() {
+        assert_eq!(chicago_vulnerability(0, Seat::East), Vulnerability::None);
+        assert_eq!(chicago_vulnerability(1, Seat::East), Vulnerability::Ew);
+        assert_eq!(chicago_vulnerability(2, Seat::South), Vulnerability::Ns);
+        assert_eq!(chicago_vulnerability(3, Seat::West), Vulnerability::Both);
+        assert_eq!(chicago_vulnerability(4, Seat::North), Vulnerability::None);
+    }
+
+    #[test]
+    fn honors_in_one_hand() {
+        let deal: Deal = "N:AKQJT.AKQJ.AK.AK 98765.T98.QJT.QJ 432.765.98765.T9 .432.432.8765432"
+            .parse()
+            .unwrap();
+        assert_eq!(honors(&deal, Strain::Spades), Some((Seat::North, 150)));
+        assert_eq!(honors(&deal, Strain::Hearts), Some((Seat::North, 100)));
+        assert_eq!(honors(&deal, Strain::NoTrump), Some((Seat::North, 150)));
+        assert_eq!(honors(&deal, Strain::Diamonds), None);
+
+        // Honors count for whoever holds them, declarer or not.
+        let mut board = played(1, Some(("4S", Seat::East, 8)));
+        board.deal = Some(deal);
+        let entry = score_deal(&board, Vulnerability::None);
+        assert_eq!(entry.above_the_line, scores(100 + 150, 0));
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::board::{BoardMongoDTO, BoardResult, Room};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardTotalPoints {
+    pub board_number: u32,
+    pub open_room_score: Option<i32>,
+    pub closed_room_score: Option<i32>,
+    /// `None` until both rooms have a result on the board.
+    pub net_points: Option<i32>,
+    pub running_total: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TotalPointsMatch {
+    pub boards: Vec<BoardTotalPoints>,
+    pub points_for: i32,
+    pub points_against: i32,
+    pub net_points: i32,
+}
+
+/// The results in the open and closed rooms.
+pub fn room_results(board: &BoardMongoDTO) -> (Option<&BoardResult>, Option<&BoardResult>) {
+    let room_result = |room: Room| {
+        board
+            .results
+            .iter()
+            .find(|result| result.room == Some(room))
+    };
+    (room_result(Room::Open), room_result(Room::Closed))
+}
+
+/// North-South's scores in the open and closed rooms.
+pub fn room_scores(board: &BoardMongoDTO) -> (Option<i32>, Option<i32>) {
+    let (open, closed) = room_results(board);
+    (
+        open.map(|result| result.score),
+        closed.map(|result| result.score),
+    )
+}
+
+/// Scores a team match on the raw difference between the rooms, board by board, from
+/// the point of view of the team sitting North-South in the open room.
+pub fn total_points_match(boards: &[BoardMongoDTO]) -> TotalPointsMatch {
+    let mut points_for = 0;
+    let mut points_against = 0;
+    let board_points = boards
+        .iter()
+        .map(|board| {
+            let (open_room_score, closed_room_score) = room_scores(board);
+            let net_points = match (open_room_score, closed_room_score) {
+                (Some(open), Some(closed)) => Some(open - closed),
+                _ => None,
+            };
+            match net_points {
+                Some(points) if points > 0 => points_for += points,
+                Some(points) => points_against -= points,
+                None => {}
+            }
+            BoardTotalPoints {
+                board_number: board.board_number,
+                open_room_score,
+                closed_room_score,
+                net_points,
+                running_total: points_for - points_against,
+            }
+        })
+        .collect();
+    TotalPointsMatch {
+        boards: board_points,
+        points_for,
+        points_against,
+        net_points: points_for - points_against,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::scoring::fixtures::{board, room_result};
+
+    #[test]
+    fn adds_up_the_differences() {
+        let boards = vec![
+            board(
+                1,
+                vec![room_result(Room::Open, 620), room_result(Room::Closed, 170)],
+            ),
+            board(
+                2,
+                vec![room_result(Room::Open, -50), room_result(Room::Closed, 100)],
+            ),
+            board(3, vec![room_result(Room::Closed, 400)]),
+            board(
+                4,
+                vec![room_result(Room::Closed, -90), room_result(Room::Open, -90)],
+            ),
+        ];
+        let result = total_points_match(&boards);
+        let net: Vec<Option<i32>> = result.boards.iter().map(|b| b.net_points).collect();
+        assert_eq!(net, [Some(450), Some(-150), None, Some(0)]);
+        let running: Vec<i32> = result.boards.iter().map(|b| b.running_total).collect();
+        assert_eq!(running, [450, 300, 300, 300]);
+        assert_eq!(
+            (result.points_for, result.points_against, result.net_points),
+            (450, 150, 300)
+        );
+    }
+
+    #[test]
+    fn finds_each_room() {
+        let played = board(
+            1,
+            vec![
+                room_result(Room::Closed, -200),
+                room_result(Room::Open, 140),
+            ],
+        );
+        let (open, closed) = room_results(&played);
+        assert_eq!(open.map(|r| r.score), Some(140));
+        assert_eq!(closed.map(|r| r.score), Some(-200));
+        assert_eq!(room_scores(&played), (Some(140), Some(-200)));
+        let empty = board(2, Vec::new());
+        assert_eq!(room_scores(&empty), (None, None));
+    }
+}
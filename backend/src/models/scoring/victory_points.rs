@@ -0,0 +1,65 @@
+use super::matchpoints::round2;
+
+/// Converts an IMP margin into victory points on the WBF continuous 20-point scale for a
+/// match of `boards` boards. Returns the VPs for the side the margin belongs to and for
+/// its opponents; the two always add up to 20.
+pub fn victory_points(imp_margin: i32, boards: u32) -> (f64, f64) {
+    if boards == 0 {
+        return (10.0, 10.0);
+    }
+    let tau = (5f64.sqrt() - 1.0) / 2.0;
+    let blitz = 15.0 * (boards as f64).sqrt();
+    let margin = imp_margin.unsigned_abs() as f64;
+    let winner = (10.0 + 10.0 * (1.0 - tau.powf(3.0 * margin / blitz)) / (1.0 - tau.powi(3)))
+        .min(20.0);
+    let winner = round2(winner);
+    let loser = round2(20.0 - winner);
+    if imp_margin >= 0 {
+        (winner, loser)
+    } else {
+        (loser, winner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_match_is_ten_all() {
+        assert_eq!(victory_points(0, 16), (10.0, 10.0));
+        assert_eq!(victory_points(12, 0), (10.0, 10.0));
+    }
+
+    #[test]
+    fn one_imp_margin() {
+        assert_eq!(victory_points(1, 16), (10.31, 9.69));
+        assert_eq!(victory_points(-1, 16), (9.69, 10.31));
+        assert_eq!(victory_points(1, 8), (10.44, 9.56));
+    }
+
+    #[test]
+    fn blitz_margin() {
+        // The blitz for 16 boards is 15 * sqrt(16) = 60 IMPs.
+        assert_eq!(victory_points(59, 16), (19.92, 0.08));
+        assert_eq!(victory_points(60, 16), (20.0, 0.0));
+        assert_eq!(victory_points(75, 16), (20.0, 0.0));
+        assert_eq!(victory_points(-60, 16), (0.0, 20.0));
+    }
+
+    #[test]
+    fn sides_always_share_twenty() {
+        for margin in -80..=80 {
+            for boards in [1, 7, 12, 16, 24, 32] {
+                let (winner, loser) = victory_points(margin, boards);
+                assert!(
+                    (winner + loser - 20.0).abs() < 1e-9,
+                    "{margin} over {boards}"
+                );
+                assert_eq!(winner >= loser, margin >= 0);
+            }
+        }
+        assert_eq!(victory_points(30, 16), (16.73, 3.27));
+        assert_eq!(victory_points(20, 12), (15.58, 4.42));
+    }
+}
@@ -7,6 +7,13 @@ use futures::TryStreamExt;
 use mongodb::{bson::doc, Client, Collection};
 use serde::{Deserialize, Serialize};
 
+use crate::tournament::{movement::Movement, teams::TeamEvent};
+
+use super::{
+    player::{Participant, ParticipantJsonDTO},
+    scoring::matchpoints::MatchpointConvention,
+};
+
 #[derive(Debug, thiserror::Error)]
 pub enum SessionError {
     #[error("Invalid scoring type string: {0}")]
@@ -19,6 +26,85 @@ pub enum SessionError {
     InvalidSessionRecord(#[from] bson::de::Error),
     #[error("Could not convert {0} to ObjectId")]
     InvalidObjectId(#[from] bson::oid::Error),
+    #[error("Could not serialize session: {0}")]
+    SerializationError(#[from] bson::ser::Error),
+    #[error("Session {0} not found")]
+    SessionNotFound(String),
+    #[error("Session {0} is final and cannot be changed unless its owner reopens it")]
+    SessionFinal(String),
+    #[error("A session cannot go from {from} to {to}")]
+    InvalidStatusTransition { from: SessionStatus, to: SessionStatus },
+    #[error("Reopening a session needs a reason")]
+    MissingReopenReason,
+    #[error("Session {0} was changed by another request; reload it and try again")]
+    ConcurrentUpdate(String),
+}
+
+/// Where a session is in its life. Boards and session details can be edited until the
+/// session is final; after that only its owner can reopen it, which puts it back to
+/// scoring.
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SessionStatus {
+    #[default]
+    Draft,
+    InProgress,
+    Scoring,
+    Final,
+}
+
+impl SessionStatus {
+    /// Whether a session may move straight from this status to `next`. A final session
+    /// only leaves that status by being reopened.
+    pub fn can_move_to(&self, next: SessionStatus) -> bool {
+        use SessionStatus::*;
+        matches!(
+            (self, next),
+            (Draft, InProgress)
+                | (InProgress, Draft)
+                | (InProgress, Scoring)
+                | (Scoring, InProgress)
+                | (Scoring, Final)
+        )
+    }
+}
+
+impl std::fmt::Display for SessionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionStatus::Draft => write!(f, "DRAFT"),
+            SessionStatus::InProgress => write!(f, "IN_PROGRESS"),
+            SessionStatus::Scoring => write!(f, "SCORING"),
+            SessionStatus::Final => write!(f, "FINAL"),
+        }
+    }
+}
+
+/// Who reopened a final session, when and why.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Reopening {
+    pub reopened_by: ObjectId,
+    pub reopened_at: DateTime,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct ReopeningJsonDTO {
+    pub reopened_by: String,
+    pub reopened_at: String,
+    pub reason: String,
+}
+
+impl From<Reopening> for ReopeningJsonDTO {
+    fn from(reopening: Reopening) -> Self {
+        ReopeningJsonDTO {
+            reopened_by: reopening.reopened_by.to_string(),
+            reopened_at: reopening.reopened_at.to_string(),
+            reason: reopening.reason,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -32,6 +118,40 @@ pub struct SessionMongoDTO {
     pub owner: ObjectId,
     pub scoring_type: ScoringType,
     pub should_use_victory_points: bool,
+    #[serde(default)]
+    pub matchpoint_convention: MatchpointConvention,
+    #[serde(default)]
+    pub board_count: Option<u32>,
+    /// Seed the session's hand records were dealt from, if they were generated.
+    #[serde(default)]
+    pub deal_seed: Option<i64>,
+    /// Constraints the deals had to satisfy, for practice sessions dealt to order.
+    #[serde(default)]
+    pub deal_constraints: Option<String>,
+    /// Table and round assignments for pairs sessions.
+    #[serde(default)]
+    pub movement: Option<Movement>,
+    /// Teams, rounds and matches for team events.
+    #[serde(default)]
+    pub team_event: Option<TeamEvent>,
+    /// Pairs or teams who played, under the numbers their results are recorded with.
+    #[serde(default)]
+    pub participants: Vec<Participant>,
+    #[serde(default)]
+    pub status: SessionStatus,
+    /// Every time the session was reopened after being made final.
+    #[serde(default)]
+    pub reopenings: Vec<Reopening>,
+}
+
+impl SessionMongoDTO {
+    /// Fails for a final session, which must be reopened before anything in it changes.
+    pub fn ensure_editable(&self) -> Result<(), SessionError> {
+        if self.status == SessionStatus::Final {
+            return Err(SessionError::SessionFinal(self.id.to_string()));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -43,6 +163,13 @@ pub struct NewSessionDTO {
     pub owner: String,
     pub scoring_type: ScoringType,
     pub should_use_victory_points: bool,
+    #[serde(default)]
+    pub matchpoint_convention: MatchpointConvention,
+    #[serde(default)]
+    pub board_count: Option<u32>,
+    /// Always starts as a draft.
+    #[serde(default, skip_deserializing)]
+    pub status: SessionStatus,
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -52,6 +179,8 @@ pub struct SessionUpdateDTO {
     pub date: Option<String>,
     pub scoring_type: Option<ScoringType>,
     pub should_use_victory_points: Option<bool>,
+    pub matchpoint_convention: Option<MatchpointConvention>,
+    pub board_count: Option<u32>,
 }
 
 impl From<SessionUpdateDTO> for Document {
@@ -74,6 +203,12 @@ impl From<SessionUpdateDTO> for Document {
         if let Some(should_use_victory_points) = session_update.should_use_victory_points {
             updates.insert("shouldUseVictoryPoints", should_use_victory_points);
         }
+        if let Some(matchpoint_convention) = session_update.matchpoint_convention {
+            updates.insert("matchpointConvention", bson::to_bson(&matchpoint_convention).unwrap());
+        }
+        if let Some(board_count) = session_update.board_count {
+            updates.insert("boardCount", board_count);
+        }
         doc! {
             "$set": updates
         }
@@ -90,6 +225,15 @@ pub struct SessionJsonDTO {
     pub owner: String,
     pub scoring_type: ScoringType,
     pub should_use_victory_points: bool,
+    pub matchpoint_convention: MatchpointConvention,
+    pub board_count: Option<u32>,
+    pub deal_seed: Option<i64>,
+    pub deal_constraints: Option<String>,
+    pub movement: Option<Movement>,
+    pub team_event: Option<TeamEvent>,
+    pub participants: Vec<ParticipantJsonDTO>,
+    pub status: SessionStatus,
+    pub reopenings: Vec<ReopeningJsonDTO>,
 }
 
 impl From<SessionMongoDTO> for SessionJsonDTO {
@@ -102,6 +246,23 @@ impl From<SessionMongoDTO> for SessionJsonDTO {
             owner: session.owner.to_string(),
             scoring_type: session.scoring_type,
             should_use_victory_points: session.should_use_victory_points,
+            matchpoint_convention: session.matchpoint_convention,
+            board_count: session.board_count,
+            deal_seed: session.deal_seed,
+            deal_constraints: session.deal_constraints,
+            movement: session.movement,
+            team_event: session.team_event,
+            participants: session
+                .participants
+                .into_iter()
+                .map(ParticipantJsonDTO::from)
+                .collect(),
+            status: session.status,
+            reopenings: session
+                .reopenings
+                .into_iter()
+                .map(ReopeningJsonDTO::from)
+                .collect(),
         }
     }
 }
@@ -110,6 +271,21 @@ impl From<SessionMongoDTO> for SessionJsonDTO {
 pub enum ScoringType {
     Imp,
     Mp,
+    Rubber,
+    Chicago,
+    Bam,
+    #[serde(rename = "TOTAL_POINTS")]
+    TotalPoints,
+    Butler,
+    #[serde(rename = "CROSS_IMP")]
+    CrossImp,
+}
+
+impl ScoringType {
+    /// Whether results are compared across a field of pairs rather than between teams.
+    pub fn is_pairs_event(&self) -> bool {
+        matches!(self, ScoringType::Mp | ScoringType::Butler | ScoringType::CrossImp)
+    }
 }
 
 impl std::fmt::Display for ScoringType {
@@ -117,6 +293,12 @@ impl std::fmt::Display for ScoringType {
         match self {
             ScoringType::Imp => write!(f, "IMP"),
             ScoringType::Mp => write!(f, "MP"),
+            ScoringType::Rubber => write!(f, "RUBBER"),
+            ScoringType::Chicago => write!(f, "CHICAGO"),
+            ScoringType::Bam => write!(f, "BAM"),
+            ScoringType::TotalPoints => write!(f, "TOTAL_POINTS"),
+            ScoringType::Butler => write!(f, "BUTLER"),
+            ScoringType::CrossImp => write!(f, "CROSS_IMP"),
         }
     }
 }
@@ -128,6 +310,12 @@ impl std::str::FromStr for ScoringType {
         match s {
             "IMP" => Ok(ScoringType::Imp),
             "MP" => Ok(ScoringType::Mp),
+            "RUBBER" => Ok(ScoringType::Rubber),
+            "CHICAGO" => Ok(ScoringType::Chicago),
+            "BAM" => Ok(ScoringType::Bam),
+            "TOTAL_POINTS" => Ok(ScoringType::TotalPoints),
+            "BUTLER" => Ok(ScoringType::Butler),
+            "CROSS_IMP" => Ok(ScoringType::CrossImp),
             _ => Err(SessionError::InvalidScoringTypeString(s.to_string())),
         }
     }
@@ -176,6 +364,15 @@ pub async fn get_sessions_for_user_id(
 
     Ok(sessions)
 }
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn get_session(db: &Client, session_id: &str) -> Result<Option<SessionMongoDTO>, SessionError> {
+    let collection: Collection<SessionMongoDTO> =
+        db.database("bridge_scorecard_api").collection("sessions");
+    let session_id = ObjectId::parse_str(session_id)?;
+    let session = collection.find_one(doc! { "_id": session_id }).await?;
+    Ok(session)
+}
+
 #[tracing::instrument(target = "database", skip(db))]
 pub async fn create_session(db: &Client, session: NewSessionDTO) -> Result<String, SessionError> {
     let collection: Collection<NewSessionDTO> =
@@ -190,6 +387,28 @@ pub async fn create_session(db: &Client, session: NewSessionDTO) -> Result<Strin
     Ok(inserted_id)
 }
 
+/// Saves a session built on the server, such as a practice session dealt to order.
+#[tracing::instrument(target = "database", skip(db, session), fields(session_id = %session.id))]
+pub async fn insert_session(db: &Client, session: &SessionMongoDTO) -> Result<String, SessionError> {
+    let collection: Collection<SessionMongoDTO> =
+        db.database("bridge_scorecard_api").collection("sessions");
+    collection.insert_one(session).await?;
+    tracing::info!("Created session id: {:?}", session.id);
+    Ok(session.id.to_string())
+}
+
+/// Removes a session that could not be set up completely, such as a practice session
+/// whose boards failed to save.
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn delete_session(db: &Client, session_id: &ObjectId) -> Result<(), SessionError> {
+    let collection: Collection<SessionMongoDTO> =
+        db.database("bridge_scorecard_api").collection("sessions");
+    collection.delete_one(doc! { "_id": session_id }).await?;
+    tracing::info!("Deleted session id: {:?}", session_id);
+    Ok(())
+}
+
+/// Updates a session's details, unless it is final.
 #[tracing::instrument(target = "database", skip(db))]
 pub async fn update_session(
     db: &Client,
@@ -198,15 +417,197 @@ pub async fn update_session(
 ) -> Result<(), SessionError> {
     let collection: Collection<SessionMongoDTO> =
         db.database("bridge_scorecard_api").collection("sessions");
+    get_session(db, session_id)
+        .await?
+        .ok_or_else(|| SessionError::SessionNotFound(session_id.to_string()))?
+        .ensure_editable()?;
     let session_id = ObjectId::parse_str(session_id)?;
     let update: Document = session_update.into();
-    collection
-        .update_one(doc! { "_id": session_id }, update)
+    // The status is checked again in the filter, in case the session was made final
+    // since it was read.
+    let result = collection
+        .update_one(unlocked_filter(&session_id)?, update)
         .await?;
+    if result.matched_count == 0 {
+        return Err(SessionError::SessionFinal(session_id.to_string()));
+    }
     tracing::info!("Updated session id: {:?}", session_id);
     Ok(())
 }
 
+/// Records the seed a session's hand records were dealt from, how many boards, and the
+/// constraints they were dealt to, if any. The seed is only replaced while it is still
+/// `previous_seed`, so two requests dealing the session at once cannot both record theirs.
+#[tracing::instrument(target = "database", skip(db))]
+pub async fn set_deal_seed(
+    db: &Client,
+    session_id: &ObjectId,
+    previous_seed: Option<i64>,
+    deal_seed: Option<i64>,
+    board_count: Option<u32>,
+    deal_constraints: Option<&str>,
+) -> Result<(), SessionError> {
+    let collection: Collection<SessionMongoDTO> =
+        db.database("bridge_scorecard_api").collection("sessions");
+    let result = collection
+        .update_one(
+            doc! { "_id": session_id, "dealSeed": previous_seed },
+            doc! { "$set": {
+                "dealSeed": deal_seed,
+                "boardCount": board_count,
+                "dealConstraints": deal_constraints,
+            } },
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(SessionError::ConcurrentUpdate(session_id.to_string()));
+    }
+    tracing::info!("Set deal seed for session id: {:?}", session_id);
+    Ok(())
+}
+
+/// Stores a session's movement, along with the number of boards it needs.
+#[tracing::instrument(target = "database", skip(db, movement))]
+pub async fn set_movement(
+    db: &Client,
+    session_id: &ObjectId,
+    movement: &Movement,
+) -> Result<(), SessionError> {
+    let collection: Collection<SessionMongoDTO> =
+        db.database("bridge_scorecard_api").collection("sessions");
+    collection
+        .update_one(
+            doc! { "_id": session_id },
+            doc! { "$set": {
+                "movement": bson::to_bson(movement)?,
+                "boardCount": movement.board_count,
+            } },
+        )
+        .await?;
+    tracing::info!("Set movement for session id: {:?}", session_id);
+    Ok(())
+}
+
+/// Stores a session's team event, along with the number of boards its rounds so far use.
+#[tracing::instrument(target = "database", skip(db, team_event))]
+pub async fn set_team_event(
+    db: &Client,
+    session_id: &ObjectId,
+    team_event: &TeamEvent,
+) -> Result<(), SessionError> {
+    let collection: Collection<SessionMongoDTO> =
+        db.database("bridge_scorecard_api").collection("sessions");
+    collection
+        .update_one(
+            doc! { "_id": session_id },
+            doc! { "$set": {
+                "teamEvent": bson::to_bson(team_event)?,
+                "boardCount": team_event.board_count(),
+            } },
+        )
+        .await?;
+    tracing::info!("Set team event for session id: {:?}", session_id);
+    Ok(())
+}
+
+#[tracing::instrument(target = "database", skip(db, participants))]
+pub async fn set_participants(
+    db: &Client,
+    session_id: &ObjectId,
+    participants: &[Participant],
+) -> Result<(), SessionError> {
+    let collection: Collection<SessionMongoDTO> =
+        db.database("bridge_scorecard_api").collection("sessions");
+    collection
+        .update_one(
+            doc! { "_id": session_id },
+            doc! { "$set": { "participants": bson::to_bson(participants)? } },
+        )
+        .await?;
+    tracing::info!("Set participants for session id: {:?}", session_id);
+    Ok(())
+}
+
+/// Moves a session to another status, if the transition is allowed.
+#[tracing::instrument(target = "database", skip(db, session), fields(session_id = %session.id))]
+pub async fn set_session_status(
+    db: &Client,
+    session: &SessionMongoDTO,
+    status: SessionStatus,
+) -> Result<(), SessionError> {
+    if session.status == status {
+        return Ok(());
+    }
+    session.ensure_editable()?;
+    if !session.status.can_move_to(status) {
+        return Err(SessionError::InvalidStatusTransition { from: session.status, to: status });
+    }
+    let collection: Collection<SessionMongoDTO> =
+        db.database("bridge_scorecard_api").collection("sessions");
+    // Only moves the session on from the status it was checked in, so two requests
+    // racing to change it cannot both succeed.
+    let result = collection
+        .update_one(
+            doc! { "_id": session.id, "status": bson::to_bson(&session.status)? },
+            doc! { "$set": { "status": bson::to_bson(&status)? } },
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(SessionError::ConcurrentUpdate(session.id.to_string()));
+    }
+    tracing::info!("Session id {:?} is now {}", session.id, status);
+    Ok(())
+}
+
+/// Matches the session only while it is not final, so an update racing with the session
+/// being made final matches nothing.
+fn unlocked_filter(session_id: &ObjectId) -> Result<Document, SessionError> {
+    Ok(doc! {
+        "_id": session_id,
+        "status": { "$ne": bson::to_bson(&SessionStatus::Final)? },
+    })
+}
+
+/// Reopens a final session for scoring, recording who reopened it and why.
+#[tracing::instrument(target = "database", skip(db, session), fields(session_id = %session.id))]
+pub async fn reopen_session(
+    db: &Client,
+    session: &SessionMongoDTO,
+    reopened_by: &ObjectId,
+    reason: &str,
+) -> Result<Reopening, SessionError> {
+    if session.status != SessionStatus::Final {
+        return Err(SessionError::InvalidStatusTransition {
+            from: session.status,
+            to: SessionStatus::Scoring,
+        });
+    }
+    if reason.trim().is_empty() {
+        return Err(SessionError::MissingReopenReason);
+    }
+    let reopening = Reopening {
+        reopened_by: *reopened_by,
+        reopened_at: DateTime::now(),
+        reason: reason.trim().to_string(),
+    };
+    let collection: Collection<SessionMongoDTO> =
+        db.database("bridge_scorecard_api").collection("sessions");
+    let result = collection
+        .update_one(
+            doc! { "_id": session.id, "status": bson::to_bson(&SessionStatus::Final)? },
+            doc! {
+                "$set": { "status": bson::to_bson(&SessionStatus::Scoring)? },
+                "$push": { "reopenings": bson::to_bson(&reopening)? },
+            },
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(SessionError::ConcurrentUpdate(session.id.to_string()));
+    }
+    tracing::info!("Reopened session id: {:?}", session.id);
+    Ok(reopening)
+}
+
 fn stage_lookup_session(user_id: Option<&ObjectId>, scoring_type: Option<ScoringType>) -> Document {
     let mut filter = doc! {};
     if let Some(user_id) = user_id {
@@ -220,4 +621,74 @@ fn stage_lookup_session(user_id: Option<&ObjectId>, scoring_type: Option<Scoring
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use SessionStatus::*;
+
+    const STATUSES: [SessionStatus; 4] = [Draft, InProgress, Scoring, Final];
+
+    fn session(status: SessionStatus) -> SessionMongoDTO {
+        SessionMongoDTO {
+            id: ObjectId::new(),
+            name: "Club pairs".to_string(),
+            location: "Town hall".to_string(),
+            date: DateTime::from_millis(1_700_000_000_000),
+            owner: ObjectId::new(),
+            scoring_type: ScoringType::Mp,
+            should_use_victory_points: false,
+            matchpoint_convention: Default::default(),
+            board_count: None,
+            deal_seed: None,
+            deal_constraints: None,
+            movement: None,
+            team_event: None,
+            participants: Vec::new(),
+            status,
+            reopenings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn allowed_transitions() {
+        let allowed = [
+            (Draft, InProgress),
+            (InProgress, Draft),
+            (InProgress, Scoring),
+            (Scoring, InProgress),
+            (Scoring, Final),
+        ];
+        for from in STATUSES {
+            for to in STATUSES {
+                assert_eq!(
+                    from.can_move_to(to),
+                    allowed.contains(&(from, to)),
+                    "{} to {}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
 
+    #[test]
+    fn final_is_only_left_by_reopening() {
+        for to in STATUSES {
+            assert!(!Final.can_move_to(to), "FINAL to {}", to);
+        }
+        assert!(!Draft.can_move_to(Final));
+        assert!(!InProgress.can_move_to(Final));
+    }
+
+    #[test]
+    fn only_a_final_session_is_locked() {
+        for status in [Draft, InProgress, Scoring] {
+            assert!(session(status).ensure_editable().is_ok(), "{}", status);
+        }
+        let session = session(Final);
+        match session.ensure_editable() {
+            Err(SessionError::SessionFinal(id)) => assert_eq!(id, session.id.to_string()),
+            other => panic!("expected SessionFinal, got {:?}", other),
+        }
+    }
+}
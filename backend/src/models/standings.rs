@@ -0,0 +1,459 @@
+use async_graphql::{Enum, SimpleObject};
+use mongodb::Client;
+use serde::Serialize;
+
+use crate::tournament::movement::MovementKind;
+
+use super::{
+    board::{get_boards_for_session, BoardError},
+    player::Direction,
+    scoring::matchpoints::round2,
+    session::{ScoringType, SessionMongoDTO},
+    summary::{summarize, SessionSummary},
+};
+
+/// Who is ranked: pairs in pairs events, teams in team events and the two sides at the
+/// table in rubber bridge and Chicago.
+#[derive(Debug, Serialize, Enum, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Competitor {
+    Pair,
+    Team,
+    Side,
+}
+
+/// What the totals count.
+#[derive(Debug, Serialize, Enum, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StandingsMeasure {
+    Matchpoints,
+    Imps,
+    VictoryPoints,
+    BoardPoints,
+    Points,
+}
+
+#[derive(Debug, Serialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct Standing {
+    pub rank: u32,
+    /// Shares its rank with another pair or team.
+    pub tied: bool,
+    pub number: u32,
+    pub name: Option<String>,
+    pub direction: Option<Direction>,
+    pub boards_played: u32,
+    pub total: f64,
+    pub percentage: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct Standings {
+    pub scoring_type: ScoringType,
+    pub competitor: Competitor,
+    pub measure: StandingsMeasure,
+    pub overall: Vec<Standing>,
+    /// Rankings within each direction, for pairs that kept one direction all session as
+    /// in a Mitchell.
+    pub north_south: Option<Vec<Standing>>,
+    pub east_west: Option<Vec<Standing>>,
+}
+
+#[tracing::instrument(target = "database", skip(db, session), fields(session_id = %session.id))]
+pub async fn get_session_standings(
+    db: &Client,
+    session: &SessionMongoDTO,
+) -> Result<Standings, BoardError> {
+    let boards = get_boards_for_session(db, &session.id).await?;
+    if let (ScoringType::Imp, Some(event)) = (session.scoring_type, &session.team_event) {
+        let mut event = event.clone();
+        event.record_results(&boards);
+        let overall = event
+            .standings()
+            .into_iter()
+            .map(|standing| {
+                let mut entry = unranked(standing.team, standing.victory_points);
+                entry.name = Some(standing.name);
+                entry.boards_played = standing.played * event.boards_per_match;
+                entry
+            })
+            .collect();
+        return Ok(standings(
+            session,
+            Competitor::Team,
+            StandingsMeasure::VictoryPoints,
+            overall,
+        ));
+    }
+    Ok(from_summary(session, summarize(session, &boards)))
+}
+
+fn from_summary(session: &SessionMongoDTO, summary: SessionSummary) -> Standings {
+    let (competitor, measure, overall) = match summary {
+        SessionSummary::Mp(matchpoints) => {
+            let pairs = matchpoints
+                .pairs
+                .into_iter()
+                .map(|pair| Standing {
+                    boards_played: pair.boards_played as u32,
+                    percentage: Some(pair.percentage),
+                    ..unranked(pair.pair_number, pair.matchpoints)
+                })
+                .collect();
+            (Competitor::Pair, StandingsMeasure::Matchpoints, pairs)
+        }
+        SessionSummary::Butler(imps) | SessionSummary::CrossImp(imps) => {
+            let pairs = imps
+                .pairs
+                .into_iter()
+                .map(|pair| Standing {
+                    boards_played: pair.boards_played as u32,
+                    ..unranked(pair.pair_number, pair.imps)
+                })
+                .collect();
+            (Competitor::Pair, StandingsMeasure::Imps, pairs)
+        }
+        SessionSummary::Imp(team_match) => {
+            let played = team_match
+                .boards
+                .iter()
+                .filter(|board| board.imps.is_some())
+                .count() as u32;
+            let (measure, home, away) = match (team_match.vps_for, team_match.vps_against) {
+                (Some(vps_for), Some(vps_against)) => {
+                    (StandingsMeasure::VictoryPoints, vps_for, vps_against)
+                }
+                _ => (
+                    StandingsMeasure::Imps,
+                    team_match.imps_for as f64,
+                    team_match.imps_against as f64,
+                ),
+            };
+            (
+                Competitor::Team,
+                measure,
+                two_teams(home, away, played, None),
+            )
+        }
+        SessionSummary::Bam(bam) => {
+            let played = bam
+                .boards
+                .iter()
+                .filter(|board| board.points.is_some())
+                .count() as u32;
+            let teams = two_teams(
+                bam.points_for,
+                bam.points_against,
+                played,
+                Some(played as f64),
+            );
+            (Competitor::Team, StandingsMeasure::BoardPoints, teams)
+        }
+        SessionSummary::TotalPoints(total_points) => {
+            let played = total_points
+                .boards
+                .iter()
+                .filter(|board| board.net_points.is_some())
+                .count() as u32;
+            let net = total_points.net_points as f64;
+            (
+                Competitor::Team,
+                StandingsMeasure::Points,
+                two_teams(net, -net, played, None),
+            )
+        }
+        SessionSummary::Rubber(scorecard) | SessionSummary::Chicago(scorecard) => {
+            let played = scorecard
+                .rubbers
+                .iter()
+                .map(|rubber| rubber.entries.len() as u32)
+                .sum();
+            let sides = [
+                (1, Direction::NorthSouth, scorecard.totals.ns),
+                (2, Direction::EastWest, scorecard.totals.ew),
+            ]
+            .into_iter()
+            .map(|(number, direction, total)| Standing {
+                direction: Some(direction),
+                boards_played: played,
+                ..unranked(number, total as f64)
+            })
+            .collect();
+            (Competitor::Side, StandingsMeasure::Points, sides)
+        }
+    };
+    standings(session, competitor, measure, overall)
+}
+
+fn unranked(number: u32, total: f64) -> Standing {
+    Standing {
+        rank: 0,
+        tied: false,
+        number,
+        name: None,
+        direction: None,
+        boards_played: 0,
+        total: round2(total),
+        percentage: None,
+    }
+}
+
+/// The two teams of a match: 1 sat North-South in the open room, 2 in the closed room.
+/// With `out_of`, percentages are of that many points.
+fn two_teams(home: f64, away: f64, played: u32, out_of: Option<f64>) -> Vec<Standing> {
+    [(1, home), (2, away)]
+        .into_iter()
+        .map(|(number, total)| Standing {
+            boards_played: played,
+            percentage: out_of
+                .filter(|out_of| *out_of > 0.0)
+                .map(|out_of| round2(total / out_of * 100.0)),
+            ..unranked(number, total)
+        })
+        .collect()
+}
+
+/// Names and directions the entries from the session's participants and movement, then
+/// ranks them.
+fn standings(
+    session: &SessionMongoDTO,
+    competitor: Competitor,
+    measure: StandingsMeasure,
+    mut overall: Vec<Standing>,
+) -> Standings {
+    let mitchell_tables = session
+        .movement
+        .as_ref()
+        .filter(|movement| movement.kind == MovementKind::Mitchell)
+        .map(|movement| movement.tables);
+    for standing in &mut overall {
+        if competitor == Competitor::Pair {
+            standing.direction = match mitchell_tables {
+                Some(tables) if standing.number <= tables => Some(Direction::NorthSouth),
+                Some(_) => Some(Direction::EastWest),
+                None => session
+                    .participants
+                    .iter()
+                    .find(|participant| participant.number == standing.number)
+                    .and_then(|participant| participant.direction),
+            };
+        }
+        if standing.name.is_none() {
+            standing.name = session
+                .participants
+                .iter()
+                .find(|participant| {
+                    participant.number == standing.number
+                        && (participant.direction.is_none()
+                            || standing.direction.is_none()
+                            || participant.direction == standing.direction)
+                })
+                .map(|participant| participant.name.clone());
+        }
+    }
+    let by_direction = competitor == Competitor::Pair
+        && !overall.is_empty()
+        && overall.iter().all(|standing| standing.direction.is_some());
+    let in_direction = |direction: Direction| {
+        by_direction.then(|| {
+            rank(
+                overall
+                    .iter()
+                    .filter(|standing| standing.direction == Some(direction))
+                    .cloned()
+                    .collect(),
+            )
+        })
+    };
+    let north_south = in_direction(Direction::NorthSouth);
+    let east_west = in_direction(Direction::EastWest);
+    Standings {
+        scoring_type: session.scoring_type,
+        competitor,
+        measure,
+        overall: rank(overall),
+        north_south,
+        east_west,
+    }
+}
+
+/// Orders by percentage where there is one and by total otherwise. Entries that are
+/// level share the higher rank.
+fn rank(mut standings: Vec<Standing>) -> Vec<Standing> {
+    let key = |standing: &Standing| standing.percentage.unwrap_or(standing.total);
+    standings.sort_by(|a, b| key(b).total_cmp(&key(a)).then(a.number.cmp(&b.number)));
+    let keys: Vec<f64> = standings.iter().map(key).collect();
+    for (index, standing) in standings.iter_mut().enumerate() {
+        let first = keys.iter().position(|k| *k == keys[index]).unwrap_or(index);
+        standing.rank = first as u32 + 1;
+        standing.tied = keys.iter().filter(|k| **k == keys[index]).count() > 1;
+    }
+    standings
+}
+
+#[cfg(test)]
+mod tests {
+    use bson::{oid::ObjectId, DateTime};
+
+    use super::*;
+    use crate::{
+        models::{
+            board::Room,
+            player::Participant,
+            scoring::{
+                fixtures::{board, room_result},
+                imp::team_match,
+            },
+        },
+        tournament::movement::{howell, mitchell, EvenTables},
+    };
+
+    fn session(scoring_type: ScoringType) -> SessionMongoDTO {
+        SessionMongoDTO {
+            id: ObjectId::new(),
+            name: "Club pairs".to_string(),
+            location: "Town hall".to_string(),
+            date: DateTime::from_millis(1_700_000_000_000),
+            owner: ObjectId::new(),
+            scoring_type,
+            should_use_victory_points: false,
+            matchpoint_convention: Default::default(),
+            board_count: None,
+            deal_seed: None,
+            deal_constraints: None,
+            movement: None,
+            team_event: None,
+            participants: Vec::new(),
+            status: Default::default(),
+            reopenings: Vec::new(),
+        }
+    }
+
+    fn pair(number: u32, total: f64, percentage: Option<f64>) -> Standing {
+        Standing {
+            percentage,
+            ..unranked(number, total)
+        }
+    }
+
+    fn places(standings: &[Standing]) -> Vec<(u32, u32, bool)> {
+        standings
+            .iter()
+            .map(|standing| (standing.number, standing.rank, standing.tied))
+            .collect()
+    }
+
+    #[test]
+    fn ties_share_the_higher_place() {
+        let ranked = rank(vec![
+            pair(1, 40.0, None),
+            pair(2, 55.0, None),
+            pair(3, 60.0, None),
+            pair(4, 55.0, None),
+        ]);
+        assert_eq!(
+            places(&ranked),
+            vec![(3, 1, false), (2, 2, true), (4, 2, true), (1, 4, false)]
+        );
+    }
+
+    #[test]
+    fn percentage_ranks_ahead_of_total() {
+        // Pair 1 has more matchpoints from more boards, pair 2 the better percentage.
+        let ranked = rank(vec![pair(1, 30.0, Some(55.0)), pair(2, 24.0, Some(60.0))]);
+        assert_eq!(places(&ranked), vec![(2, 1, false), (1, 2, false)]);
+        let ranked = rank(vec![pair(1, 3.5, None), pair(2, 12.0, None)]);
+        assert_eq!(places(&ranked), vec![(2, 1, false), (1, 2, false)]);
+    }
+
+    #[test]
+    fn mitchell_field_splits_at_the_table_count() {
+        let mut session = session(ScoringType::Mp);
+        session.movement = Some(mitchell(8, 2, None, EvenTables::Skip).unwrap());
+        assert_eq!(session.movement.as_ref().unwrap().tables, 4);
+        let overall = (1..=8)
+            .map(|number| pair(number, number as f64, None))
+            .collect();
+        let standings = standings(
+            &session,
+            Competitor::Pair,
+            StandingsMeasure::Matchpoints,
+            overall,
+        );
+        let numbers = |standings: &[Standing]| -> Vec<u32> {
+            standings.iter().map(|standing| standing.number).collect()
+        };
+        let north_south = standings.north_south.unwrap();
+        let east_west = standings.east_west.unwrap();
+        assert_eq!(numbers(&north_south), vec![4, 3, 2, 1]);
+        assert_eq!(numbers(&east_west), vec![8, 7, 6, 5]);
+        assert_eq!((north_south[0].rank, east_west[0].rank), (1, 1));
+        assert!(north_south
+            .iter()
+            .all(|standing| standing.direction == Some(Direction::NorthSouth)));
+        assert_eq!(standings.overall[0].number, 8);
+        assert_eq!(standings.overall[0].direction, Some(Direction::EastWest));
+    }
+
+    #[test]
+    fn howell_field_is_ranked_as_one() {
+        let mut session = session(ScoringType::Mp);
+        session.movement = Some(howell(8, 2, None).unwrap());
+        session.participants = vec![Participant {
+            number: 3,
+            direction: None,
+            partnership_id: None,
+            players: Vec::new(),
+            name: "Ann & Bob".to_string(),
+        }];
+        let overall = (1..=8)
+            .map(|number| pair(number, 50.0, Some(50.0)))
+            .collect();
+        let standings = standings(
+            &session,
+            Competitor::Pair,
+            StandingsMeasure::Matchpoints,
+            overall,
+        );
+        assert!(standings.north_south.is_none() && standings.east_west.is_none());
+        assert!(standings.overall.iter().all(|standing| standing.rank == 1));
+        assert_eq!(standings.overall[2].name.as_deref(), Some("Ann & Bob"));
+        assert_eq!(standings.overall[0].name, None);
+    }
+
+    #[test]
+    fn team_match_ranks_on_victory_points() {
+        let boards = [
+            board(
+                1,
+                vec![room_result(Room::Open, 420), room_result(Room::Closed, 170)],
+            ),
+            board(
+                2,
+                vec![room_result(Room::Open, -50), room_result(Room::Closed, 100)],
+            ),
+        ];
+        let session = session(ScoringType::Imp);
+        let summary = SessionSummary::Imp(team_match(&boards).with_victory_points());
+        let standings = from_summary(&session, summary);
+        assert_eq!(standings.competitor, Competitor::Team);
+        assert_eq!(standings.measure, StandingsMeasure::VictoryPoints);
+        assert_eq!(standings.overall[0].number, 1);
+        assert!(standings.overall[0].total > standings.overall[1].total);
+        assert_eq!(
+            standings.overall[0].total + standings.overall[1].total,
+            20.0
+        );
+        assert!(standings.overall.iter().all(|team| team.boards_played == 2));
+        assert!(standings.north_south.is_none());
+
+        let without_vps = from_summary(&session, SessionSummary::Imp(team_match(&boards)));
+        assert_eq!(without_vps.measure, StandingsMeasure::Imps);
+        assert_eq!(
+            places(&without_vps.overall),
+            vec![(1, 1, false), (2, 2, false)]
+        );
+        assert_eq!(without_vps.overall[0].total, 6.0);
+    }
+}
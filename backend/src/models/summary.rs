@@ -0,0 +1,70 @@
+use mongodb::Client;
+use serde::Serialize;
+
+use super::{
+    board::{get_boards_for_session, BoardError, BoardMongoDTO},
+    scoring::{
+        board_a_match::{board_a_match, BoardAMatch},
+        imp::{team_match, TeamMatch},
+        matchpoints::{pairs_matchpoints, PairsMatchpoints},
+        pairs_imps::{pairs_imps, PairsImpMethod, PairsImps},
+        rubber::{chicago_scorecard, rubber_scorecard, RubberScorecard},
+        total_points::{total_points_match, TotalPointsMatch},
+    },
+    session::{ScoringType, SessionMongoDTO},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SummaryError {
+    #[error("Board error: {0}")]
+    BoardError(#[from] BoardError),
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "scoringType", rename_all = "UPPERCASE")]
+pub enum SessionSummary {
+    Imp(TeamMatch),
+    Mp(PairsMatchpoints),
+    Rubber(RubberScorecard),
+    Chicago(RubberScorecard),
+    Bam(BoardAMatch),
+    #[serde(rename = "TOTAL_POINTS")]
+    TotalPoints(TotalPointsMatch),
+    Butler(PairsImps),
+    #[serde(rename = "CROSS_IMP")]
+    CrossImp(PairsImps),
+}
+
+#[tracing::instrument(target = "database", skip(db, session), fields(session_id = %session.id))]
+pub async fn get_session_summary(
+    db: &Client,
+    session: &SessionMongoDTO,
+) -> Result<SessionSummary, SummaryError> {
+    let boards = get_boards_for_session(db, &session.id).await?;
+    Ok(summarize(session, &boards))
+}
+
+/// Scores the session's boards according to its scoring type.
+pub fn summarize(session: &SessionMongoDTO, boards: &[BoardMongoDTO]) -> SessionSummary {
+    match session.scoring_type {
+        ScoringType::Imp => {
+            let team_match = team_match(boards);
+            if session.should_use_victory_points {
+                SessionSummary::Imp(team_match.with_victory_points())
+            } else {
+                SessionSummary::Imp(team_match)
+            }
+        }
+        ScoringType::Mp => {
+            SessionSummary::Mp(pairs_matchpoints(boards, session.matchpoint_convention))
+        }
+        ScoringType::Rubber => SessionSummary::Rubber(rubber_scorecard(boards)),
+        ScoringType::Chicago => SessionSummary::Chicago(chicago_scorecard(boards)),
+        ScoringType::Bam => SessionSummary::Bam(board_a_match(boards)),
+        ScoringType::TotalPoints => SessionSummary::TotalPoints(total_points_match(boards)),
+        ScoringType::Butler => SessionSummary::Butler(pairs_imps(boards, PairsImpMethod::Butler)),
+        ScoringType::CrossImp => {
+            SessionSummary::CrossImp(pairs_imps(boards, PairsImpMethod::CrossImp))
+        }
+    }
+}
@@ -93,6 +93,13 @@ impl Display for Role {
     }
 }
 
+impl User {
+    /// Whether the user holds the named role, ignoring case.
+    pub fn has_role(&self, name: &str) -> bool {
+        self.roles.iter().any(|role| role.name.eq_ignore_ascii_case(name))
+    }
+}
+
 impl Display for User {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         write!(
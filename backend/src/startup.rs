@@ -5,9 +5,10 @@ use mongodb::Client;
 use secrecy::{ExposeSecret, Secret};
 
 use crate::middlewares::request_id::add_session_id;
+use crate::models::board::ensure_board_indexes;
 
 
-use crate::web::{routes_session, routes_user_session};
+use crate::web::{routes_board, routes_dealing, routes_import, routes_player, routes_ruling, routes_session, routes_tournament, routes_user_session};
 use crate::{ auth::jwt::Keys, configuration::{DatabaseSettings, Settings}, state::AppState, telemetry::add_trace_layer, web::{routes_hello, routes_login, routes_user, routes_graphql, routes_logout} };
 
 
@@ -28,6 +29,7 @@ impl Application {
     }
     pub async fn build(configuration: Settings) -> Result<Self, anyhow::Error> {
         let db_conn = get_db_conn(&configuration.database).await;
+        ensure_board_indexes(&db_conn).await?;
 
         let address = format!("{}:{}", configuration.application.host, configuration.application.port);
         let jwt_secret = configuration.application.jwt_secret.clone();
@@ -68,6 +70,12 @@ async fn run(
     .merge(routes_logout::routes(&state))
     .merge(routes_user_session::routes(&state))
     .merge(routes_session::routes())
+    .merge(routes_board::routes(&state))
+    .merge(routes_import::routes(&state))
+    .merge(routes_dealing::routes(&state))
+    .merge(routes_tournament::routes(&state))
+    .merge(routes_player::routes(&state))
+    .merge(routes_ruling::routes(&state))
     .with_state(state);
 
     add_trace_layer(router)
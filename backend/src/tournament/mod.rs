@@ -0,0 +1,4 @@
+pub mod movement;
+pub mod round_robin;
+pub mod swiss;
+pub mod teams;
\ No newline at end of file
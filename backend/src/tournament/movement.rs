@@ -0,0 +1,601 @@
+//! Pairs movements: where each pair sits and which boards each table plays, round by
+//! round.
+//!
+//! - Mitchell: North-South pairs stay put while East-West pairs move up a table and the
+//!   boards move down one each round. North-South pairs are numbered 1 to the number of
+//!   tables and East-West pairs carry on from there. With an even number of tables the
+//!   East-West pairs would meet boards they have already played halfway through, so
+//!   either they skip a table after half the rounds (one round fewer), or a bye-stand
+//!   is put in the middle of the room with tables 1 and the last sharing a set (the
+//!   relay).
+//! - Howell: every pair meets every other pair once. One pair stays at the last table
+//!   and the rest rotate through the others. Board sets follow the round with a fixed
+//!   offset per table, chosen so no pair meets a set twice; for the few sizes where no
+//!   offsets keep every table on its own set, tables share sets by relaying.
+//!
+//! With an odd number of pairs, a phantom pair makes the numbers even and whoever it
+//! would play sits out.
+use std::collections::HashSet;
+
+use async_graphql::{Enum, SimpleObject};
+use serde::{Deserialize, Serialize};
+
+/// Largest Howell generated: 16 tables, 31 rounds.
+pub const MAX_HOWELL_PAIRS: u32 = 32;
+pub const MAX_MITCHELL_PAIRS: u32 = 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MovementError {
+    #[error("A {kind} movement needs between {min} and {max} pairs")]
+    InvalidPairCount {
+        kind: MovementKind,
+        min: u32,
+        max: u32,
+    },
+    #[error("Boards per round must be at least 1")]
+    InvalidBoardsPerRound,
+    #[error("This movement has at most {0} rounds")]
+    TooManyRounds(u32),
+    #[error("Invalid movement: {0}")]
+    InvalidMovement(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum MovementKind {
+    Mitchell,
+    Howell,
+}
+
+impl std::fmt::Display for MovementKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MovementKind::Mitchell => write!(f, "Mitchell"),
+            MovementKind::Howell => write!(f, "Howell"),
+        }
+    }
+}
+
+/// How a Mitchell copes with an even number of tables.
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EvenTables {
+    #[default]
+    Skip,
+    RelayByeStand,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct Movement {
+    pub kind: MovementKind,
+    pub pairs: u32,
+    pub tables: u32,
+    /// Only set for Mitchells with an even number of tables.
+    pub even_tables: Option<EvenTables>,
+    pub boards_per_round: u32,
+    pub board_count: u32,
+    pub rounds: Vec<MovementRound>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct MovementRound {
+    pub round: u32,
+    pub tables: Vec<TableAssignment>,
+    pub sitting_out: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct TableAssignment {
+    pub table: u32,
+    pub ns_pair: u32,
+    pub ew_pair: u32,
+    pub first_board: u32,
+    pub last_board: u32,
+}
+
+/// A seating for one table in one round, before pair and board numbers are worked out.
+struct Seating {
+    table: u32,
+    ns: u32,
+    ew: u32,
+    set: u32,
+}
+
+/// A Mitchell for `pairs` pairs, playing every round unless `rounds` asks for fewer.
+pub fn mitchell(
+    pairs: u32,
+    boards_per_round: u32,
+    rounds: Option<u32>,
+    even_tables: EvenTables,
+) -> Result<Movement, MovementError> {
+    if !(5..=MAX_MITCHELL_PAIRS).contains(&pairs) {
+        return Err(MovementError::InvalidPairCount {
+            kind: MovementKind::Mitchell,
+            min: 5,
+            max: MAX_MITCHELL_PAIRS,
+        });
+    }
+    let tables = pairs.div_ceil(2);
+    let even = tables.is_multiple_of(2);
+    let skip = even && even_tables == EvenTables::Skip;
+    let max_rounds = if skip { tables - 1 } else { tables };
+    let rounds = played_rounds(rounds, max_rounds)?;
+
+    let half = tables / 2;
+    // Where each table's boards sit in the cycle they move round. With a relay and
+    // bye-stand, tables 1 and the last share a place and the stand takes one in the
+    // middle.
+    let station = |table: u32| match table {
+        _ if !even || skip => table,
+        0 => 0,
+        _ if table == tables - 1 => 0,
+        _ if table < half => table,
+        _ => table + 1,
+    };
+    let seatings = (0..rounds)
+        .map(|round| {
+            (0..tables)
+                .map(|table| {
+                    let moved = round + u32::from(skip && round >= half);
+                    Seating {
+                        table: table + 1,
+                        ns: table + 1,
+                        ew: tables + (table + tables - moved % tables) % tables + 1,
+                        set: (station(table) + round) % tables,
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let movement = build(
+        MovementKind::Mitchell,
+        pairs,
+        tables,
+        even.then_some(even_tables),
+        boards_per_round,
+        tables,
+        seatings,
+    )?;
+    validate(&movement)?;
+    Ok(movement)
+}
+
+/// A complete Howell for `pairs` pairs, or its first `rounds` rounds.
+pub fn howell(
+    pairs: u32,
+    boards_per_round: u32,
+    rounds: Option<u32>,
+) -> Result<Movement, MovementError> {
+    if !(4..=MAX_HOWELL_PAIRS).contains(&pairs) {
+        return Err(MovementError::InvalidPairCount {
+            kind: MovementKind::Howell,
+            min: 4,
+            max: MAX_HOWELL_PAIRS,
+        });
+    }
+    let seated = pairs + pairs % 2;
+    let tables = seated / 2;
+    let cycle = seated - 1;
+    let rounds = played_rounds(rounds, cycle)?;
+    let offsets = howell_offsets(tables as usize);
+
+    let seatings = (0..rounds)
+        .map(|round| {
+            // The stationary pair, number `seated`, meets the pair whose turn it is at
+            // the last table; the others face each other across the circle.
+            let stationary = if round.is_multiple_of(2) {
+                (seated, round + 1)
+            } else {
+                (round + 1, seated)
+            };
+            let mut seatings = vec![Seating {
+                table: tables,
+                ns: stationary.0,
+                ew: stationary.1,
+                set: (round + offsets[0]) % cycle,
+            }];
+            for table in 1..tables {
+                seatings.push(Seating {
+                    table,
+                    ns: (round + table) % cycle + 1,
+                    ew: (round + cycle - table) % cycle + 1,
+                    set: (round + offsets[table as usize]) % cycle,
+                });
+            }
+            seatings
+        })
+        .collect();
+    let movement = build(
+        MovementKind::Howell,
+        pairs,
+        tables,
+        None,
+        boards_per_round,
+        cycle,
+        seatings,
+    )?;
+    validate(&movement)?;
+    Ok(movement)
+}
+
+fn played_rounds(rounds: Option<u32>, max_rounds: u32) -> Result<u32, MovementError> {
+    match rounds {
+        None => Ok(max_rounds),
+        Some(rounds) if (1..=max_rounds).contains(&rounds) => Ok(rounds),
+        Some(_) => Err(MovementError::TooManyRounds(max_rounds)),
+    }
+}
+
+/// Numbers the boards and takes the phantom pair (the one numbered past `pairs`) out.
+fn build(
+    kind: MovementKind,
+    pairs: u32,
+    tables: u32,
+    even_tables: Option<EvenTables>,
+    boards_per_round: u32,
+    sets: u32,
+    seatings: Vec<Vec<Seating>>,
+) -> Result<Movement, MovementError> {
+    if boards_per_round == 0 {
+        return Err(MovementError::InvalidBoardsPerRound);
+    }
+    let rounds = seatings
+        .into_iter()
+        .enumerate()
+        .map(|(round, seatings)| {
+            let mut sitting_out = None;
+            let mut tables: Vec<TableAssignment> = Vec::new();
+            for seating in seatings {
+                if seating.ns > pairs {
+                    sitting_out = Some(seating.ew);
+                } else if seating.ew > pairs {
+                    sitting_out = Some(seating.ns);
+                } else {
+                    tables.push(TableAssignment {
+                        table: seating.table,
+                        ns_pair: seating.ns,
+                        ew_pair: seating.ew,
+                        first_board: seating.set * boards_per_round + 1,
+                        last_board: (seating.set + 1) * boards_per_round,
+                    });
+                }
+            }
+            tables.sort_by_key(|table| table.table);
+            MovementRound {
+                round: round as u32 + 1,
+                tables,
+                sitting_out,
+            }
+        })
+        .collect();
+    Ok(Movement {
+        kind,
+        pairs,
+        tables,
+        even_tables,
+        boards_per_round,
+        board_count: sets * boards_per_round,
+        rounds,
+    })
+}
+
+/// Offset of each table's board set from the round number, table 0 being the
+/// stationary pair's. A pair meets table `k`'s set at rounds `p - k` and `p + k`, so
+/// the sets a pair plays are distinct when the `offset ± k` are; among the offsets that
+/// manage that, prefer those that give every table a different set.
+fn howell_offsets(tables: usize) -> Vec<u32> {
+    let cycle = 2 * tables - 1;
+    for shared in 0..tables {
+        let mut offsets = vec![0; tables];
+        if place_offsets(tables - 1, cycle, 1, 1, shared, &mut offsets) {
+            return offsets.into_iter().map(|offset| offset as u32).collect();
+        }
+    }
+    // Every table on the same set always works.
+    vec![0; tables]
+}
+
+fn place_offsets(
+    table: usize,
+    cycle: usize,
+    met: u64,
+    used: u64,
+    shared: usize,
+    offsets: &mut [usize],
+) -> bool {
+    if table == 0 {
+        return true;
+    }
+    for offset in 0..cycle {
+        let sharing = used & (1 << offset) != 0;
+        if sharing && shared == 0 {
+            continue;
+        }
+        let before = (offset + cycle - table) % cycle;
+        let after = (offset + table) % cycle;
+        if met & (1 << before | 1 << after) != 0 {
+            continue;
+        }
+        offsets[table] = offset;
+        if place_offsets(
+            table - 1,
+            cycle,
+            met | 1 << before | 1 << after,
+            used | 1 << offset,
+            shared - usize::from(sharing),
+            offsets,
+        ) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Checks that every pair is placed once a round, plays each board at most once and
+/// meets each opponent at most once; that Mitchell pairs only meet pairs from the other
+/// direction; and that a complete movement has every pair meet all the opponents it
+/// should.
+pub fn validate(movement: &Movement) -> Result<(), MovementError> {
+    let invalid = |message: String| Err(MovementError::InvalidMovement(message));
+    let mut boards_played: HashSet<(u32, u32)> = HashSet::new();
+    let mut meetings: HashSet<(u32, u32)> = HashSet::new();
+    for round in &movement.rounds {
+        let mut placed = HashSet::new();
+        let seated = round
+            .tables
+            .iter()
+            .flat_map(|table| [table.ns_pair, table.ew_pair]);
+        for pair in seated.chain(round.sitting_out) {
+            if !(1..=movement.pairs).contains(&pair) || !placed.insert(pair) {
+                return invalid(format!("pair {} misplaced in round {}", pair, round.round));
+            }
+        }
+        if placed.len() as u32 != movement.pairs {
+            return invalid(format!("round {} leaves pairs out", round.round));
+        }
+        for table in &round.tables {
+            if movement.kind == MovementKind::Mitchell
+                && (table.ns_pair > movement.tables || table.ew_pair <= movement.tables)
+            {
+                return invalid(format!(
+                    "pairs {} and {} sit the wrong way in round {}",
+                    table.ns_pair, table.ew_pair, round.round
+                ));
+            }
+            let pair = (
+                table.ns_pair.min(table.ew_pair),
+                table.ns_pair.max(table.ew_pair),
+            );
+            if !meetings.insert(pair) {
+                return invalid(format!("pairs {} and {} meet twice", pair.0, pair.1));
+            }
+            for board in table.first_board..=table.last_board {
+                for pair in [table.ns_pair, table.ew_pair] {
+                    if !boards_played.insert((pair, board)) {
+                        return invalid(format!("pair {} plays board {} twice", pair, board));
+                    }
+                }
+            }
+        }
+    }
+    let rounds = movement.rounds.len() as u32;
+    let complete = match (movement.kind, movement.even_tables) {
+        (MovementKind::Howell, _) => rounds == movement.tables * 2 - 1,
+        (MovementKind::Mitchell, Some(EvenTables::Skip)) => false,
+        (MovementKind::Mitchell, _) => rounds == movement.tables,
+    };
+    if complete {
+        let seats = movement.tables * 2;
+        let expected = match movement.kind {
+            MovementKind::Howell => seats * (seats - 1) / 2,
+            MovementKind::Mitchell => movement.tables * movement.tables,
+        };
+        // Less the meetings with the phantom pair.
+        let phantom = if movement.pairs < seats {
+            match movement.kind {
+                MovementKind::Howell => seats - 1,
+                MovementKind::Mitchell => movement.tables,
+            }
+        } else {
+            0
+        };
+        if meetings.len() as u32 != expected - phantom {
+            return invalid("some pairs never meet".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the movement without `validate`: each pair is placed once a round and
+    /// plays each board once, and returns who met whom.
+    fn meetings(movement: &Movement) -> Vec<(u32, u32)> {
+        let mut boards = HashSet::new();
+        let mut meetings = Vec::new();
+        for round in &movement.rounds {
+            let mut placed: Vec<u32> = round.sitting_out.into_iter().collect();
+            for table in &round.tables {
+                placed.extend([table.ns_pair, table.ew_pair]);
+                meetings.push((
+                    table.ns_pair.min(table.ew_pair),
+                    table.ns_pair.max(table.ew_pair),
+                ));
+                for board in table.first_board..=table.last_board {
+                    assert!(boards.insert((table.ns_pair, board)));
+                    assert!(boards.insert((table.ew_pair, board)));
+                }
+            }
+            placed.sort_unstable();
+            assert_eq!(placed, (1..=movement.pairs).collect::<Vec<_>>());
+        }
+        meetings.sort_unstable();
+        meetings
+    }
+
+    fn sets(round: &MovementRound) -> Vec<u32> {
+        round.tables.iter().map(|table| table.first_board).collect()
+    }
+
+    #[test]
+    fn mitchell_with_odd_tables() {
+        let movement = mitchell(14, 2, None, EvenTables::Skip).unwrap();
+        assert_eq!((movement.tables, movement.board_count), (7, 14));
+        assert_eq!(movement.even_tables, None);
+        assert_eq!(movement.rounds.len(), 7);
+        let expected: Vec<(u32, u32)> = (1..=7)
+            .flat_map(|ns| (8..=14).map(move |ew| (ns, ew)))
+            .collect();
+        assert_eq!(meetings(&movement), expected);
+        for round in &movement.rounds {
+            let mut sets = sets(round);
+            sets.sort_unstable();
+            sets.dedup();
+            assert_eq!(sets.len(), 7);
+        }
+    }
+
+    #[test]
+    fn mitchell_with_odd_pairs() {
+        let movement = mitchell(13, 3, None, EvenTables::Skip).unwrap();
+        assert_eq!(movement.tables, 7);
+        assert_eq!(meetings(&movement).len(), 7 * 7 - 7);
+        let mut sitting_out: Vec<u32> = movement
+            .rounds
+            .iter()
+            .map(|round| round.sitting_out.unwrap())
+            .collect();
+        sitting_out.sort_unstable();
+        assert_eq!(sitting_out, (1..=7).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn mitchell_with_even_tables_skips() {
+        let movement = mitchell(16, 2, None, EvenTables::Skip).unwrap();
+        assert_eq!(movement.even_tables, Some(EvenTables::Skip));
+        assert_eq!(movement.rounds.len(), 7);
+        let meetings = meetings(&movement);
+        assert_eq!(meetings.len(), 8 * 7);
+        assert!(meetings.iter().all(|(ns, ew)| *ns <= 8 && *ew > 8));
+    }
+
+    #[test]
+    fn mitchell_with_even_tables_relays() {
+        let movement = mitchell(16, 2, None, EvenTables::RelayByeStand).unwrap();
+        assert_eq!(movement.rounds.len(), 8);
+        assert_eq!(meetings(&movement).len(), 8 * 8);
+        for round in &movement.rounds {
+            let sets = sets(round);
+            assert_eq!(sets[0], sets[7]);
+            let mut others = sets[..7].to_vec();
+            others.sort_unstable();
+            others.dedup();
+            assert_eq!(others.len(), 7);
+        }
+    }
+
+    #[test]
+    fn mitchells_of_every_size_are_valid() {
+        for pairs in 5..=MAX_MITCHELL_PAIRS {
+            for even_tables in [EvenTables::Skip, EvenTables::RelayByeStand] {
+                let movement = mitchell(pairs, 2, None, even_tables).unwrap();
+                let mut all = meetings(&movement);
+                all.dedup();
+                assert_eq!(all.len(), meetings(&movement).len(), "{pairs} pairs");
+            }
+        }
+    }
+
+    #[test]
+    fn howells_meet_everyone_once() {
+        for pairs in 4..=MAX_HOWELL_PAIRS {
+            let movement = howell(pairs, 2, None).unwrap();
+            let seated = pairs + pairs % 2;
+            assert_eq!(movement.rounds.len() as u32, seated - 1, "{pairs} pairs");
+            let expected: Vec<(u32, u32)> = (1..=pairs)
+                .flat_map(|a| (a + 1..=pairs).map(move |b| (a, b)))
+                .collect();
+            assert_eq!(meetings(&movement), expected, "{pairs} pairs");
+            let sitting_out = movement
+                .rounds
+                .iter()
+                .filter_map(|round| round.sitting_out)
+                .count() as u32;
+            assert_eq!(sitting_out, if pairs % 2 == 1 { pairs } else { 0 });
+        }
+    }
+
+    #[test]
+    fn truncated_movements() {
+        let movement = mitchell(9, 2, Some(3), EvenTables::Skip).unwrap();
+        assert_eq!(movement.rounds.len(), 3);
+        let movement = howell(10, 3, Some(4)).unwrap();
+        assert_eq!(movement.rounds.len(), 4);
+        assert_eq!(movement.board_count, 27);
+    }
+
+    #[test]
+    fn rejects_bad_parameters() {
+        assert!(matches!(
+            mitchell(4, 2, None, EvenTables::Skip),
+            Err(MovementError::InvalidPairCount { .. })
+        ));
+        assert!(matches!(
+            mitchell(MAX_MITCHELL_PAIRS + 1, 2, None, EvenTables::Skip),
+            Err(MovementError::InvalidPairCount { .. })
+        ));
+        assert!(matches!(
+            howell(3, 2, None),
+            Err(MovementError::InvalidPairCount { .. })
+        ));
+        assert!(matches!(
+            howell(MAX_HOWELL_PAIRS + 1, 2, None),
+            Err(MovementError::InvalidPairCount { .. })
+        ));
+        assert!(matches!(
+            mitchell(9, 0, None, EvenTables::Skip),
+            Err(MovementError::InvalidBoardsPerRound)
+        ));
+        // Eight tables skipping play seven rounds.
+        assert!(matches!(
+            mitchell(16, 2, Some(8), EvenTables::Skip),
+            Err(MovementError::TooManyRounds(7))
+        ));
+        assert!(matches!(
+            howell(8, 2, Some(0)),
+            Err(MovementError::TooManyRounds(7))
+        ));
+    }
+
+    #[test]
+    fn validate_catches_broken_movements() {
+        let mut movement = howell(8, 2, None).unwrap();
+        movement.rounds[1].tables[0].ns_pair = movement.rounds[0].tables[0].ns_pair;
+        movement.rounds[1].tables[0].ew_pair = movement.rounds[0].tables[0].ew_pair;
+        assert!(matches!(
+            validate(&movement),
+            Err(MovementError::InvalidMovement(_))
+        ));
+
+        let mut movement = mitchell(10, 2, None, EvenTables::Skip).unwrap();
+        let table = &mut movement.rounds[0].tables[0];
+        std::mem::swap(&mut table.ns_pair, &mut table.ew_pair);
+        assert!(matches!(
+            validate(&movement),
+            Err(MovementError::InvalidMovement(_))
+        ));
+
+        // A pair left out of a round.
+        let mut movement = mitchell(10, 2, None, EvenTables::Skip).unwrap();
+        movement.rounds[2].tables.pop();
+        assert!(matches!(
+            validate(&movement),
+            Err(MovementError::InvalidMovement(_))
+        ));
+    }
+}
@@ -0,0 +1,185 @@
+//! Round-robin schedules for team events, by the circle method: the last team stays put
+//! while the others rotate, so every team meets every other once (twice in a double
+//! round robin, with home and away swapped the second time). With an odd number of
+//! teams, whoever the missing last team would play has a bye.
+//!
+//! A schedule depends only on the number of teams, boards per match and whether it is
+//! double, so it can always be generated again.
+use super::teams::{EventRound, TeamEvent, TeamEventError, TeamEventFormat, TeamMatchup};
+
+pub fn round_robin(
+    names: Vec<String>,
+    boards_per_match: u32,
+    double: bool,
+) -> Result<TeamEvent, TeamEventError> {
+    let format = if double {
+        TeamEventFormat::DoubleRoundRobin
+    } else {
+        TeamEventFormat::RoundRobin
+    };
+    let mut event = TeamEvent::new(format, names, boards_per_match)?;
+    let teams = event.teams.len() as u32;
+    let seated = teams + teams % 2;
+    let cycle = seated - 1;
+    let legs = if double { 2 } else { 1 };
+
+    for leg in 0..legs {
+        for rotation in 0..cycle {
+            let round = leg * cycle + rotation + 1;
+            // The fixed team alternates home and away; the rest are home as often as
+            // away over a cycle.
+            let mut pairings = vec![if rotation.is_multiple_of(2) {
+                (seated, rotation + 1)
+            } else {
+                (rotation + 1, seated)
+            }];
+            for offset in 1..seated / 2 {
+                pairings.push((
+                    (rotation + offset) % cycle + 1,
+                    (rotation + cycle - offset) % cycle + 1,
+                ));
+            }
+            let mut bye = None;
+            let mut matches = Vec::new();
+            for (home, away) in pairings {
+                let (home, away) = if leg == 0 { (home, away) } else { (away, home) };
+                if home > teams {
+                    bye = Some(away);
+                } else if away > teams {
+                    bye = Some(home);
+                } else {
+                    matches.push(TeamMatchup {
+                        table: matches.len() as u32 + 1,
+                        home,
+                        away,
+                        result: None,
+                    });
+                }
+            }
+            let (first_board, last_board) = event.board_range(round);
+            event.rounds.push(EventRound {
+                round,
+                first_board,
+                last_board,
+                matches,
+                bye,
+            });
+        }
+    }
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn names(teams: usize) -> Vec<String> {
+        (1..=teams).map(|team| format!("Team {team}")).collect()
+    }
+
+    /// How often each ordered (home, away) pair plays, checking every team is placed
+    /// once a round.
+    fn fixtures(event: &TeamEvent) -> HashMap<(u32, u32), u32> {
+        let mut fixtures = HashMap::new();
+        for round in &event.rounds {
+            let mut placed: Vec<u32> = round.bye.into_iter().collect();
+            for matchup in &round.matches {
+                placed.extend([matchup.home, matchup.away]);
+                *fixtures.entry((matchup.home, matchup.away)).or_default() += 1;
+            }
+            placed.sort_unstable();
+            assert_eq!(placed, (1..=event.teams.len() as u32).collect::<Vec<_>>());
+        }
+        fixtures
+    }
+
+    #[test]
+    fn everyone_meets_once() {
+        for teams in 2..=12u32 {
+            let event = round_robin(names(teams as usize), 6, false).unwrap();
+            assert_eq!(event.format, TeamEventFormat::RoundRobin);
+            assert_eq!(event.rounds.len() as u32, teams + teams % 2 - 1);
+            let fixtures = fixtures(&event);
+            assert_eq!(
+                fixtures.len() as u32,
+                teams * (teams - 1) / 2,
+                "{teams} teams"
+            );
+            for a in 1..=teams {
+                for b in a + 1..=teams {
+                    let meetings =
+                        fixtures.get(&(a, b)).unwrap_or(&0) + fixtures.get(&(b, a)).unwrap_or(&0);
+                    assert_eq!(meetings, 1, "{teams} teams: {a} and {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn double_round_robin_swaps_home_and_away() {
+        for teams in [4, 5, 8] {
+            let event = round_robin(names(teams), 6, true).unwrap();
+            assert_eq!(event.format, TeamEventFormat::DoubleRoundRobin);
+            let fixtures = fixtures(&event);
+            assert_eq!(fixtures.len(), teams * (teams - 1));
+            assert!(fixtures.values().all(|count| *count == 1));
+            let cycle = event.rounds.len() / 2;
+            for (first, second) in event.rounds[..cycle].iter().zip(&event.rounds[cycle..]) {
+                let swapped: Vec<(u32, u32)> =
+                    second.matches.iter().map(|m| (m.away, m.home)).collect();
+                let original: Vec<(u32, u32)> =
+                    first.matches.iter().map(|m| (m.home, m.away)).collect();
+                assert_eq!(swapped, original);
+                assert_eq!(first.bye, second.bye);
+            }
+        }
+    }
+
+    #[test]
+    fn home_and_away_are_balanced() {
+        let event = round_robin(names(8), 6, false).unwrap();
+        let mut balance: HashMap<u32, i32> = HashMap::new();
+        for matchup in event.rounds.iter().flat_map(|round| &round.matches) {
+            *balance.entry(matchup.home).or_default() += 1;
+            *balance.entry(matchup.away).or_default() -= 1;
+        }
+        assert!(balance.values().all(|b| b.abs() <= 1), "{balance:?}");
+    }
+
+    #[test]
+    fn odd_teams_each_have_one_bye() {
+        let event = round_robin(names(7), 6, false).unwrap();
+        let mut byes: Vec<u32> = event.rounds.iter().map(|r| r.bye.unwrap()).collect();
+        byes.sort_unstable();
+        assert_eq!(byes, [1, 2, 3, 4, 5, 6, 7]);
+        assert!(event.rounds.iter().all(|round| round.matches.len() == 3));
+    }
+
+    #[test]
+    fn rounds_play_fresh_boards() {
+        let event = round_robin(names(4), 6, false).unwrap();
+        let ranges: Vec<(u32, u32, u32)> = event
+            .rounds
+            .iter()
+            .map(|round| (round.round, round.first_board, round.last_board))
+            .collect();
+        assert_eq!(ranges, [(1, 1, 6), (2, 7, 12), (3, 13, 18)]);
+        assert_eq!(event.board_count(), 18);
+        let tables: Vec<u32> = event.rounds[0].matches.iter().map(|m| m.table).collect();
+        assert_eq!(tables, [1, 2]);
+    }
+
+    #[test]
+    fn rejects_bad_events() {
+        assert!(matches!(
+            round_robin(names(1), 6, false),
+            Err(TeamEventError::TooFewTeams(2))
+        ));
+        assert!(matches!(
+            round_robin(names(4), 0, false),
+            Err(TeamEventError::InvalidBoardsPerMatch)
+        ));
+    }
+}
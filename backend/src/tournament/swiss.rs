@@ -0,0 +1,241 @@
+//! Swiss pairing: each round, teams play others on similar victory points.
+//!
+//! Teams are ranked by the standings so far and paired from the top down, each with the
+//! highest-placed team it has not met yet, backtracking when that leaves teams further
+//! down with nobody new to play. Only when no pairing avoids every rematch are repeats
+//! allowed. With an odd number of teams, the lowest-placed team that has not had a bye
+//! sits out.
+use std::collections::HashSet;
+
+use super::teams::{EventRound, TeamEvent, TeamEventFormat, TeamMatchup};
+
+/// Positions tried while looking for a pairing without rematches before giving up.
+const SEARCH_LIMIT: u32 = 100_000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SwissError {
+    #[error("Not a Swiss event")]
+    NotSwiss,
+    #[error("Round {0} is not finished")]
+    RoundNotFinished(u32),
+}
+
+/// Pairs the event's next round. Every match so far needs a result.
+pub fn pair_next_round(event: &TeamEvent) -> Result<EventRound, SwissError> {
+    if event.format != TeamEventFormat::Swiss {
+        return Err(SwissError::NotSwiss);
+    }
+    if let Some(unfinished) = event
+        .rounds
+        .iter()
+        .find(|round| round.matches.iter().any(|m| m.result.is_none()))
+    {
+        return Err(SwissError::RoundNotFinished(unfinished.round));
+    }
+    let mut met: HashSet<(u32, u32)> = HashSet::new();
+    let mut byes: HashSet<u32> = HashSet::new();
+    for round in &event.rounds {
+        for matchup in &round.matches {
+            met.insert(meeting(matchup.home, matchup.away));
+        }
+        byes.extend(round.bye);
+    }
+    let ranked: Vec<u32> = event
+        .standings()
+        .into_iter()
+        .map(|standing| standing.team)
+        .collect();
+
+    let (bye, pairs) = if ranked.len().is_multiple_of(2) {
+        (None, pair_teams(&ranked, &met))
+    } else {
+        // Lowest-placed first, preferring teams that have not had a bye.
+        let mut candidates: Vec<u32> = ranked.iter().rev().copied().collect();
+        candidates.sort_by_key(|team| byes.contains(team));
+        let strict = candidates.iter().find_map(|bye| {
+            let rest: Vec<u32> = ranked.iter().copied().filter(|t| t != bye).collect();
+            let mut budget = SEARCH_LIMIT;
+            pair_without_rematches(&rest, &met, &mut budget).map(|pairs| (Some(*bye), pairs))
+        });
+        strict.unwrap_or_else(|| {
+            let bye = candidates[0];
+            let rest: Vec<u32> = ranked.iter().copied().filter(|t| *t != bye).collect();
+            (Some(bye), pair_teams(&rest, &met))
+        })
+    };
+
+    let round = event.rounds.len() as u32 + 1;
+    let (first_board, last_board) = event.board_range(round);
+    let matches = pairs
+        .into_iter()
+        .enumerate()
+        .map(|(index, (home, away))| TeamMatchup {
+            table: index as u32 + 1,
+            home,
+            away,
+            result: None,
+        })
+        .collect();
+    Ok(EventRound {
+        round,
+        first_board,
+        last_board,
+        matches,
+        bye,
+    })
+}
+
+fn meeting(a: u32, b: u32) -> (u32, u32) {
+    (a.min(b), a.max(b))
+}
+
+/// Pairs ranked teams, avoiding rematches if at all possible.
+fn pair_teams(ranked: &[u32], met: &HashSet<(u32, u32)>) -> Vec<(u32, u32)> {
+    let mut budget = SEARCH_LIMIT;
+    pair_without_rematches(ranked, met, &mut budget).unwrap_or_else(|| {
+        // Greedily, still preferring a new opponent for each team in turn.
+        let mut left = ranked.to_vec();
+        let mut pairs = Vec::new();
+        while left.len() >= 2 {
+            let home = left.remove(0);
+            let index = left
+                .iter()
+                .position(|away| !met.contains(&meeting(home, *away)))
+                .unwrap_or(0);
+            pairs.push((home, left.remove(index)));
+        }
+        pairs
+    })
+}
+
+/// The top remaining team plays the best-placed team it has not met, if the rest can
+/// still be paired after that.
+fn pair_without_rematches(
+    ranked: &[u32],
+    met: &HashSet<(u32, u32)>,
+    budget: &mut u32,
+) -> Option<Vec<(u32, u32)>> {
+    let Some((home, rest)) = ranked.split_first() else {
+        return Some(Vec::new());
+    };
+    for (index, away) in rest.iter().enumerate() {
+        if *budget == 0 {
+            return None;
+        }
+        *budget -= 1;
+        if met.contains(&meeting(*home, *away)) {
+            continue;
+        }
+        let others: Vec<u32> = rest
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != index)
+            .map(|(_, team)| *team)
+            .collect();
+        if let Some(mut pairs) = pair_without_rematches(&others, met, budget) {
+            pairs.insert(0, (*home, *away));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(teams: usize) -> TeamEvent {
+        let names = (1..=teams).map(|team| format!("Team {team}")).collect();
+        TeamEvent::new(TeamEventFormat::Swiss, names, 8).unwrap()
+    }
+
+    /// Pairs and plays the next round, the home team winning by `margin(home, away)`.
+    fn play_round(event: &mut TeamEvent, margin: impl Fn(u32, u32) -> i32) -> EventRound {
+        let round = pair_next_round(event).unwrap();
+        event.rounds.push(round.clone());
+        for matchup in &round.matches {
+            let margin = margin(matchup.home, matchup.away);
+            event
+                .set_result(round.round, matchup.table, margin.max(0), (-margin).max(0))
+                .unwrap();
+        }
+        round
+    }
+
+    fn pairs(round: &EventRound) -> Vec<(u32, u32)> {
+        round.matches.iter().map(|m| (m.home, m.away)).collect()
+    }
+
+    #[test]
+    fn first_round_pairs_in_team_order() {
+        let round = pair_next_round(&event(6)).unwrap();
+        assert_eq!(round.round, 1);
+        assert_eq!((round.first_board, round.last_board), (1, 8));
+        assert_eq!(pairs(&round), [(1, 2), (3, 4), (5, 6)]);
+        assert_eq!(round.bye, None);
+    }
+
+    #[test]
+    fn winners_meet_winners() {
+        let mut event = event(8);
+        play_round(&mut event, |_, _| 20);
+        let round = pair_next_round(&event).unwrap();
+        assert_eq!(round.first_board, 9);
+        assert_eq!(pairs(&round), [(1, 3), (5, 7), (2, 4), (6, 8)]);
+    }
+
+    #[test]
+    fn no_rematches_while_new_opponents_remain() {
+        for teams in [4, 6, 8, 10] {
+            let mut event = event(teams);
+            let mut met = HashSet::new();
+            for _ in 1..teams {
+                let round = play_round(&mut event, |home, away| {
+                    (home * 7 + away * 3) as i32 % 25 - 12
+                });
+                for (home, away) in pairs(&round) {
+                    assert!(
+                        met.insert(meeting(home, away)),
+                        "{teams} teams: {home} v {away}"
+                    );
+                }
+            }
+            // Everybody has played everybody, so the next round has to repeat.
+            let round = pair_next_round(&event).unwrap();
+            assert_eq!(round.matches.len(), teams / 2);
+        }
+    }
+
+    #[test]
+    fn backtracks_to_avoid_a_rematch() {
+        let met = HashSet::from([(1, 2), (2, 4)]);
+        assert_eq!(pair_teams(&[1, 2, 3, 4], &met), [(1, 4), (2, 3)]);
+        let everyone = HashSet::from([(1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)]);
+        assert_eq!(pair_teams(&[1, 2, 3, 4], &everyone), [(1, 2), (3, 4)]);
+    }
+
+    #[test]
+    fn byes_go_to_the_lowest_team_without_one() {
+        let mut event = event(5);
+        // Everyone level: the last team by number sits out.
+        let first = play_round(&mut event, |home, away| away as i32 - home as i32);
+        assert_eq!(first.bye, Some(5));
+        assert_eq!(pairs(&first), [(1, 2), (3, 4)]);
+        // Teams 1 and 3 won by an IMP, below team 5's bye; team 4 is last.
+        let second = pair_next_round(&event).unwrap();
+        assert_eq!(second.bye, Some(4));
+        assert_eq!(pairs(&second), [(5, 1), (3, 2)]);
+    }
+
+    #[test]
+    fn needs_a_finished_swiss() {
+        let mut event = event(4);
+        event.rounds.push(pair_next_round(&event).unwrap());
+        assert!(matches!(
+            pair_next_round(&event),
+            Err(SwissError::RoundNotFinished(1))
+        ));
+        event.format = TeamEventFormat::RoundRobin;
+        assert!(matches!(pair_next_round(&event), Err(SwissError::NotSwiss)));
+    }
+}
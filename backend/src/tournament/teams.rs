@@ -0,0 +1,305 @@
+//! Team events: the teams entered and the matches they play, round by round.
+//!
+//! Every match in a round plays the same boards. A match is scored from the session's
+//! board results, with team numbers standing in for pair numbers: the home team sits
+//! North-South in the open room and East-West in the closed room.
+use std::collections::HashMap;
+
+use async_graphql::{Enum, SimpleObject};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    board::BoardMongoDTO,
+    scoring::{imp::compare_results, matchpoints::round2, victory_points::victory_points},
+};
+
+/// What a team gets for a bye, on the 20-point scale.
+pub const BYE_VICTORY_POINTS: f64 = 12.0;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TeamEventError {
+    #[error("A team event needs at least {0} teams")]
+    TooFewTeams(usize),
+    #[error("Boards per match must be at least 1")]
+    InvalidBoardsPerMatch,
+    #[error("No match at table {table} in round {round}")]
+    MatchNotFound { round: u32, table: u32 },
+}
+
+#[derive(Debug, Serialize, Deserialize, Enum, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TeamEventFormat {
+    Swiss,
+    RoundRobin,
+    DoubleRoundRobin,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct Team {
+    pub number: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamEvent {
+    pub format: TeamEventFormat,
+    pub teams: Vec<Team>,
+    pub boards_per_match: u32,
+    pub rounds: Vec<EventRound>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct EventRound {
+    pub round: u32,
+    pub first_board: u32,
+    pub last_board: u32,
+    pub matches: Vec<TeamMatchup>,
+    /// Team sitting the round out, when the number of teams is odd.
+    pub bye: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamMatchup {
+    pub table: u32,
+    pub home: u32,
+    pub away: u32,
+    pub result: Option<MatchResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchResult {
+    pub home_imps: i32,
+    pub away_imps: i32,
+    pub home_vps: f64,
+    pub away_vps: f64,
+}
+
+impl MatchResult {
+    pub fn new(home_imps: i32, away_imps: i32, boards: u32) -> Self {
+        let (home_vps, away_vps) = victory_points(home_imps - away_imps, boards);
+        MatchResult {
+            home_imps,
+            away_imps,
+            home_vps,
+            away_vps,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamStanding {
+    pub team: u32,
+    pub name: String,
+    pub played: u32,
+    pub byes: u32,
+    pub imps_for: i32,
+    pub imps_against: i32,
+    pub victory_points: f64,
+}
+
+impl TeamEvent {
+    /// A new event with the teams numbered in the order given.
+    pub fn new(
+        format: TeamEventFormat,
+        names: Vec<String>,
+        boards_per_match: u32,
+    ) -> Result<Self, TeamEventError> {
+        if names.len() < 2 {
+            return Err(TeamEventError::TooFewTeams(2));
+        }
+        if boards_per_match == 0 {
+            return Err(TeamEventError::InvalidBoardsPerMatch);
+        }
+        let teams = names
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| Team {
+                number: index as u32 + 1,
+                name,
+            })
+            .collect();
+        Ok(TeamEvent {
+            format,
+            teams,
+            boards_per_match,
+            rounds: Vec::new(),
+        })
+    }
+
+    /// Boards of round `round`, counting from 1: every round has a fresh set.
+    pub fn board_range(&self, round: u32) -> (u32, u32) {
+        let first = (round - 1) * self.boards_per_match + 1;
+        (first, first + self.boards_per_match - 1)
+    }
+
+    pub fn team_name(&self, number: u32) -> Option<&str> {
+        self.teams
+            .iter()
+            .find(|team| team.number == number)
+            .map(|team| team.name.as_str())
+    }
+
+    /// The round and match a board result was played in, going by its pair numbers.
+    pub fn find_match(
+        &self,
+        board_number: u32,
+        ns_pair: u32,
+        ew_pair: u32,
+    ) -> Option<(&EventRound, &TeamMatchup)> {
+        let round = self
+            .rounds
+            .iter()
+            .find(|round| (round.first_board..=round.last_board).contains(&board_number))?;
+        let matchup = round.matches.iter().find(|m| {
+            (m.home, m.away) == (ns_pair, ew_pair) || (m.away, m.home) == (ns_pair, ew_pair)
+        })?;
+        Some((round, matchup))
+    }
+
+    pub fn board_count(&self) -> u32 {
+        self.rounds
+            .iter()
+            .map(|round| round.last_board)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Sets a match's score by hand, for matches whose boards were not entered.
+    pub fn set_result(
+        &mut self,
+        round: u32,
+        table: u32,
+        home_imps: i32,
+        away_imps: i32,
+    ) -> Result<(), TeamEventError> {
+        let boards = self.boards_per_match;
+        let matchup = self
+            .rounds
+            .iter_mut()
+            .find(|r| r.round == round)
+            .and_then(|r| r.matches.iter_mut().find(|m| m.table == table))
+            .ok_or(TeamEventError::MatchNotFound { round, table })?;
+        matchup.result = Some(MatchResult::new(home_imps, away_imps, boards));
+        Ok(())
+    }
+
+    /// Fills in the results of matches that have every board played at both tables.
+    /// Results already recorded are kept.
+    pub fn record_results(&mut self, boards: &[BoardMongoDTO]) {
+        for round in &mut self.rounds {
+            let in_range: Vec<&BoardMongoDTO> = boards
+                .iter()
+                .filter(|board| {
+                    (round.first_board..=round.last_board).contains(&board.board_number)
+                })
+                .collect();
+            let expected = (round.last_board - round.first_board + 1) as usize;
+            for matchup in round.matches.iter_mut().filter(|m| m.result.is_none()) {
+                matchup.result = score_match(&in_range, expected, matchup.home, matchup.away);
+            }
+        }
+    }
+
+    /// Teams in order: victory points, then IMP difference, then team number.
+    pub fn standings(&self) -> Vec<TeamStanding> {
+        let mut standings: HashMap<u32, TeamStanding> = self
+            .teams
+            .iter()
+            .map(|team| {
+                let standing = TeamStanding {
+                    team: team.number,
+                    name: team.name.clone(),
+                    played: 0,
+                    byes: 0,
+                    imps_for: 0,
+                    imps_against: 0,
+                    victory_points: 0.0,
+                };
+                (team.number, standing)
+            })
+            .collect();
+        for round in &self.rounds {
+            if let Some(standing) = round.bye.and_then(|bye| standings.get_mut(&bye)) {
+                standing.byes += 1;
+                standing.victory_points += BYE_VICTORY_POINTS;
+            }
+            for matchup in &round.matches {
+                let Some(result) = &matchup.result else {
+                    continue;
+                };
+                for (team, imps_for, imps_against, vps) in [
+                    (
+                        matchup.home,
+                        result.home_imps,
+                        result.away_imps,
+                        result.home_vps,
+                    ),
+                    (
+                        matchup.away,
+                        result.away_imps,
+                        result.home_imps,
+                        result.away_vps,
+                    ),
+                ] {
+                    if let Some(standing) = standings.get_mut(&team) {
+                        standing.played += 1;
+                        standing.imps_for += imps_for;
+                        standing.imps_against += imps_against;
+                        standing.victory_points += vps;
+                    }
+                }
+            }
+        }
+        let mut standings: Vec<TeamStanding> = standings
+            .into_values()
+            .map(|mut standing| {
+                standing.victory_points = round2(standing.victory_points);
+                standing
+            })
+            .collect();
+        standings.sort_by(|a, b| {
+            b.victory_points
+                .total_cmp(&a.victory_points)
+                .then((b.imps_for - b.imps_against).cmp(&(a.imps_for - a.imps_against)))
+                .then(a.team.cmp(&b.team))
+        });
+        standings
+    }
+}
+
+/// Scores one match from the boards of its round, or `None` until every board has a
+/// result at both tables.
+fn score_match(
+    boards: &[&BoardMongoDTO],
+    expected: usize,
+    home: u32,
+    away: u32,
+) -> Option<MatchResult> {
+    if boards.len() < expected {
+        return None;
+    }
+    let mut home_imps = 0;
+    let mut away_imps = 0;
+    for board in boards {
+        let result = |ns: u32, ew: u32| {
+            board
+                .results
+                .iter()
+                .find(|result| result.ns_pair == Some(ns) && result.ew_pair == Some(ew))
+        };
+        // Both scores are North-South's, so the closed room's counts for the away team.
+        let board_imps = compare_results(result(home, away)?, result(away, home)?);
+        if board_imps > 0 {
+            home_imps += board_imps;
+        } else {
+            away_imps -= board_imps;
+        }
+    }
+    Some(MatchResult::new(home_imps, away_imps, expected as u32))
+}
@@ -4,4 +4,10 @@ pub mod routes_logout;
 pub mod routes_graphql;
 pub mod routes_user;
 pub mod routes_user_session;
-pub mod routes_session;
\ No newline at end of file
+pub mod routes_session;
+pub mod routes_board;
+pub mod routes_import;
+pub mod routes_dealing;
+pub mod routes_tournament;
+pub mod routes_player;
+pub mod routes_ruling;
\ No newline at end of file
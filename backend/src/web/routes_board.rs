@@ -0,0 +1,254 @@
+use axum::{
+    body::Body,
+    debug_handler,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Json, Router,
+};
+use futures::{stream, StreamExt, TryStreamExt};
+use serde_json::{json, Value};
+
+use crate::{
+    analysis::{
+        double_dummy::{solve_deal, DoubleDummyTable},
+        par::{board_par, BoardPar},
+    },
+    middlewares::auth::{
+        lookup_user::lookup_user_from_token,
+        session_owner_guard::{owned_session_guard, unlocked_session_guard},
+        verify_jwt::get_claims_from_auth_token,
+    },
+    models::{
+        board::{
+            create_board, delete_board, get_board, get_boards_for_session, set_double_dummy_table,
+            update_board, BoardError, BoardJsonDTO, BoardMongoDTO, BoardUpdateDTO, NewBoardDTO,
+        },
+        session::SessionMongoDTO,
+    },
+    state::AppState,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum BoardWebError {
+    #[error("Data error")]
+    UnexpectedError(#[from] BoardError),
+}
+
+impl IntoResponse for BoardWebError {
+    fn into_response(self) -> Response<Body> {
+        let BoardWebError::UnexpectedError(e) = self;
+        let status = match e {
+            BoardError::BoardNotFound(_) => StatusCode::NOT_FOUND,
+            BoardError::MissingDeal(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            BoardError::BoardHasResults(_) => StatusCode::CONFLICT,
+            BoardError::InvalidBoardNumber(_)
+            | BoardError::DuplicateBoardNumber(_)
+            | BoardError::IncompleteResult
+            | BoardError::PassedOutWithResult
+            | BoardError::InvalidAdjustmentWeights
+            | BoardError::AuctionError(_)
+            | BoardError::AuctionMismatch
+            | BoardError::PlayError(_)
+            | BoardError::ScoringError(_)
+            | BoardError::InvalidObjectId(_) => StatusCode::BAD_REQUEST,
+            _ => {
+                tracing::error!("Board error: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        Response::builder()
+            .status(status)
+            .body(Json(json!({ "error": e.to_string() })).to_string().into())
+            .unwrap()
+    }
+}
+
+pub fn routes(state: &AppState) -> Router<AppState> {
+    let get_claims_layer =
+        middleware::from_fn_with_state(state.clone(), get_claims_from_auth_token);
+    let lookup_user_layer = middleware::from_fn_with_state(state.clone(), lookup_user_from_token);
+    let owned_session_guard_layer = middleware::from_fn_with_state(state.clone(), owned_session_guard);
+    let unlocked_session_guard_layer = middleware::from_fn(unlocked_session_guard);
+    Router::<AppState>::new()
+        .route(
+            "/api/user/{user_id}/session/{session_id}/boards",
+            get(list_boards_handler).post(create_board_handler),
+        )
+        .route(
+            "/api/user/{user_id}/session/{session_id}/boards/{board_id}",
+            get(get_board_handler)
+                .put(update_board_handler)
+                .delete(delete_board_handler),
+        )
+        .route(
+            "/api/user/{user_id}/session/{session_id}/boards/{board_id}/double-dummy",
+            get(double_dummy_handler),
+        )
+        .route(
+            "/api/user/{user_id}/session/{session_id}/boards/{board_id}/par",
+            get(board_par_handler),
+        )
+        .route(
+            "/api/user/{user_id}/session/{session_id}/par",
+            get(session_par_handler),
+        )
+        .route_layer(unlocked_session_guard_layer)
+        .route_layer(owned_session_guard_layer)
+        .route_layer(lookup_user_layer)
+        .route_layer(get_claims_layer)
+}
+
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn list_boards_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, BoardWebError> {
+    let boards: Vec<BoardJsonDTO> = get_boards_for_session(&db, &session.id)
+        .await?
+        .into_iter()
+        .map(BoardJsonDTO::from)
+        .collect();
+    Ok(Json(json!(boards)))
+}
+
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn create_board_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<NewBoardDTO>,
+) -> Result<Json<Value>, BoardWebError> {
+    let result = create_board(&db, &session.id, payload).await?;
+    Ok(Json(json!(result)))
+}
+
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn get_board_handler(
+    Path((_user_id, _session_id, board_id)): Path<(String, String, String)>,
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, BoardWebError> {
+    let board: BoardJsonDTO = get_board(&db, &session.id, &board_id).await?.into();
+    Ok(Json(json!(board)))
+}
+
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn update_board_handler(
+    Path((_user_id, _session_id, board_id)): Path<(String, String, String)>,
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<BoardUpdateDTO>,
+) -> Result<StatusCode, BoardWebError> {
+    update_board(&db, &session.id, &board_id, payload).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn delete_board_handler(
+    Path((_user_id, _session_id, board_id)): Path<(String, String, String)>,
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<StatusCode, BoardWebError> {
+    delete_board(&db, &session.id, &board_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The board's double-dummy table, solved on first request and cached on the board.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn double_dummy_handler(
+    Path((_user_id, _session_id, board_id)): Path<(String, String, String)>,
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, BoardWebError> {
+    let board = get_board(&db, &session.id, &board_id).await?;
+    let dd_table = double_dummy_table(&db, &board).await?;
+    Ok(Json(json!(dd_table)))
+}
+
+/// Par for the board, and how far each of its results was from it.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn board_par_handler(
+    Path((_user_id, _session_id, board_id)): Path<(String, String, String)>,
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, BoardWebError> {
+    let board = get_board(&db, &session.id, &board_id).await?;
+    let dd_table = double_dummy_table(&db, &board).await?;
+    Ok(Json(json!(board_par(&board, dd_table))))
+}
+
+/// Boards solved at once for a session's par. Each solve already runs its strains on
+/// separate threads.
+const CONCURRENT_SOLVES: usize = 4;
+
+/// Every result of the session against par. Boards without a deal are left out.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn session_par_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, BoardWebError> {
+    let db = &db;
+    let dealt = get_boards_for_session(db, &session.id)
+        .await?
+        .into_iter()
+        .filter(|board| board.deal.is_some());
+    let boards: Vec<BoardPar> = stream::iter(dealt)
+        .map(|board| async move {
+            let dd_table = double_dummy_table(db, &board).await?;
+            Ok::<_, BoardError>(board_par(&board, dd_table))
+        })
+        .buffered(CONCURRENT_SOLVES)
+        .try_collect()
+        .await?;
+    Ok(Json(json!(boards)))
+}
+
+/// The cached double-dummy table of a board, solving and storing it when there is none.
+async fn double_dummy_table(
+    db: &mongodb::Client,
+    board: &BoardMongoDTO,
+) -> Result<DoubleDummyTable, BoardError> {
+    if let Some(dd_table) = board.dd_table {
+        return Ok(dd_table);
+    }
+    let deal = board
+        .deal
+        .ok_or(BoardError::MissingDeal(board.board_number))?;
+    let dd_table = tokio::task::spawn_blocking(move || solve_deal(&deal)).await?;
+    set_double_dummy_table(db, &board.id, &deal, &dd_table).await?;
+    Ok(dd_table)
+}
\ No newline at end of file
@@ -0,0 +1,279 @@
+use axum::{
+    body::Body,
+    debug_handler,
+    extract::State,
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::post,
+    Extension, Json, Router,
+};
+use bson::{oid::ObjectId, DateTime};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::task::JoinError;
+
+use crate::{
+    dealing::{
+        constraints::{ConstraintError, DealConstraints},
+        generate::{deal_matching, hand_records, hand_records_from, new_seed, DealingError},
+    },
+    middlewares::auth::{
+        lookup_user::lookup_user_from_token,
+        session_owner_guard::{owned_session_guard, session_owner_guard, unlocked_session_guard},
+        verify_jwt::get_claims_from_auth_token,
+    },
+    models::{
+        board::{create_boards, set_board_deals, BoardError},
+        scoring::matchpoints::MatchpointConvention,
+        session::{
+            delete_session, insert_session, set_deal_seed, ScoringType, SessionError,
+            SessionMongoDTO, SessionStatus,
+        },
+        user::User,
+    },
+    state::AppState,
+};
+
+use super::routes_board::BoardWebError;
+
+/// Largest number of boards dealt in one request.
+const MAX_BOARDS: u32 = 128;
+const DEFAULT_TIMEOUT_SECONDS: u64 = 10;
+const MAX_TIMEOUT_SECONDS: u64 = 60;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateDealsPayload {
+    /// Reuse a seed to deal the same boards again; a new one is drawn if missing.
+    seed: Option<i64>,
+    /// Defaults to the session's board count.
+    board_count: Option<u32>,
+    /// Re-deal boards that already have results, discarding the results.
+    #[serde(default)]
+    clear_results: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PracticeSessionPayload {
+    name: String,
+    /// e.g. `North 15-17 balanced, South 4+ spades, 8+ HCP`.
+    constraints: String,
+    board_count: u32,
+    seed: Option<i64>,
+    /// How long to keep dealing before giving up, capped at a minute.
+    timeout_seconds: Option<u64>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DealingWebError {
+    #[error("Board count must be between 1 and {MAX_BOARDS}")]
+    InvalidBoardCount,
+    #[error("Invalid constraints: {0}")]
+    ConstraintError(#[from] ConstraintError),
+    #[error("{0}")]
+    DealingError(#[from] DealingError),
+    #[error("Session error")]
+    SessionError(#[from] SessionError),
+    #[error("Board error")]
+    BoardError(#[from] BoardError),
+    #[error("Dealing was interrupted")]
+    Interrupted(#[from] JoinError),
+}
+
+impl IntoResponse for DealingWebError {
+    fn into_response(self) -> Response<Body> {
+        match self {
+            DealingWebError::InvalidBoardCount | DealingWebError::ConstraintError(_) => {
+                Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(
+                        Json(json!({ "error": self.to_string() }))
+                            .to_string()
+                            .into(),
+                    )
+                    .unwrap()
+            }
+            DealingWebError::DealingError(e) => {
+                tracing::warn!("Gave up dealing practice boards: {}", e);
+                Response::builder()
+                    .status(StatusCode::UNPROCESSABLE_ENTITY)
+                    .body(Json(json!({ "error": e.to_string() })).to_string().into())
+                    .unwrap()
+            }
+            DealingWebError::SessionError(e @ SessionError::ConcurrentUpdate(_)) => {
+                Response::builder()
+                    .status(StatusCode::CONFLICT)
+                    .body(Json(json!({ "error": e.to_string() })).to_string().into())
+                    .unwrap()
+            }
+            DealingWebError::SessionError(e) => {
+                tracing::error!("Session error: {:?}", e);
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Json(json!({ "error": e.to_string() })).to_string().into())
+                    .unwrap()
+            }
+            DealingWebError::BoardError(e) => BoardWebError::from(e).into_response(),
+            DealingWebError::Interrupted(e) => {
+                tracing::error!("Dealing task failed: {:?}", e);
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(
+                        Json(json!({ "error": "Dealing was interrupted" }))
+                            .to_string()
+                            .into(),
+                    )
+                    .unwrap()
+            }
+        }
+    }
+}
+
+pub fn routes(state: &AppState) -> Router<AppState> {
+    let get_claims_layer =
+        middleware::from_fn_with_state(state.clone(), get_claims_from_auth_token);
+    let lookup_user_layer = middleware::from_fn_with_state(state.clone(), lookup_user_from_token);
+    let session_owner_guard_layer = middleware::from_fn(session_owner_guard);
+    let owned_session_guard_layer =
+        middleware::from_fn_with_state(state.clone(), owned_session_guard);
+    let unlocked_session_guard_layer = middleware::from_fn(unlocked_session_guard);
+    let owned_session_routes = Router::<AppState>::new()
+        .route(
+            "/api/user/{user_id}/session/{session_id}/deals",
+            post(generate_deals_handler),
+        )
+        .route_layer(unlocked_session_guard_layer)
+        .route_layer(owned_session_guard_layer);
+    Router::<AppState>::new()
+        .route(
+            "/api/user/{user_id}/practice",
+            post(practice_session_handler),
+        )
+        .route_layer(session_owner_guard_layer)
+        .merge(owned_session_routes)
+        .route_layer(lookup_user_layer)
+        .route_layer(get_claims_layer)
+}
+
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn generate_deals_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<GenerateDealsPayload>,
+) -> Result<Json<Value>, DealingWebError> {
+    let board_count = payload
+        .board_count
+        .or(session.board_count)
+        .filter(|count| (1..=MAX_BOARDS).contains(count))
+        .ok_or(DealingWebError::InvalidBoardCount)?;
+    let seed = payload.seed.unwrap_or_else(new_seed);
+    // The seed goes first, so the boards never hold deals the session cannot regenerate,
+    // and is put back if the deals are not written.
+    set_deal_seed(
+        &db,
+        &session.id,
+        session.deal_seed,
+        Some(seed),
+        Some(board_count),
+        None,
+    )
+    .await?;
+    let boards = match set_board_deals(
+        &db,
+        &session.id,
+        hand_records(seed, board_count),
+        payload.clear_results,
+    )
+    .await
+    {
+        Ok(boards) => boards,
+        Err(e) => {
+            if let Err(restore) = set_deal_seed(
+                &db,
+                &session.id,
+                Some(seed),
+                session.deal_seed,
+                session.board_count,
+                session.deal_constraints.as_deref(),
+            )
+            .await
+            {
+                tracing::error!("Could not restore the deal seed: {:?}", restore);
+            }
+            return Err(e.into());
+        }
+    };
+    Ok(Json(
+        json!({ "seed": seed, "boardCount": board_count, "boards": boards }),
+    ))
+}
+
+#[tracing::instrument(skip(db, user))]
+#[debug_handler]
+async fn practice_session_handler(
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<PracticeSessionPayload>,
+) -> Result<Json<Value>, DealingWebError> {
+    if !(1..=MAX_BOARDS).contains(&payload.board_count) {
+        return Err(DealingWebError::InvalidBoardCount);
+    }
+    let constraints: DealConstraints = payload.constraints.parse()?;
+    let seed = payload.seed.unwrap_or_else(new_seed);
+    let count = payload.board_count as usize;
+    let timeout = Duration::from_secs(
+        payload
+            .timeout_seconds
+            .unwrap_or(DEFAULT_TIMEOUT_SECONDS)
+            .min(MAX_TIMEOUT_SECONDS),
+    );
+    let deals =
+        tokio::task::spawn_blocking(move || deal_matching(&constraints, seed, count, timeout))
+            .await??;
+    let session = SessionMongoDTO {
+        id: ObjectId::new(),
+        name: payload.name,
+        location: "Practice".to_string(),
+        date: DateTime::now(),
+        owner: user.id,
+        scoring_type: ScoringType::Mp,
+        should_use_victory_points: false,
+        matchpoint_convention: MatchpointConvention::default(),
+        board_count: Some(payload.board_count),
+        deal_seed: Some(seed),
+        deal_constraints: Some(payload.constraints),
+        movement: None,
+        team_event: None,
+        participants: Vec::new(),
+        status: SessionStatus::default(),
+        reopenings: Vec::new(),
+    };
+    let session_id = insert_session(&db, &session).await?;
+    // Don't leave the user an empty session if its boards could not be saved.
+    let boards = match create_boards(&db, &session.id, hand_records_from(deals)).await {
+        Ok(boards) => boards,
+        Err(e) => {
+            if let Err(cleanup) = delete_session(&db, &session.id).await {
+                tracing::error!(
+                    "Could not remove practice session {}: {:?}",
+                    session.id,
+                    cleanup
+                );
+            }
+            return Err(e.into());
+        }
+    };
+    Ok(Json(
+        json!({ "sessionId": session_id, "seed": seed, "boards": boards }),
+    ))
+}
@@ -2,7 +2,7 @@ use async_graphql::{extensions::Tracing, http::GraphiQLSource, EmptySubscription
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::{body::Body, debug_handler, extract::{Request, State}, middleware::Next, response::{self, IntoResponse, Response}, routing::get, Extension, Router};
 use axum::middleware;
-use crate::{auth::{jwt::Claims, login::LoginError}, graphql::user::{Mutation, Query}, middlewares::auth::verify_jwt::{get_claims, BearerToken}, models::user::{find_user, User, UserError}, state::AppState};
+use crate::{auth::{jwt::Claims, login::LoginError}, graphql::{user::Mutation, QueryRoot}, middlewares::auth::verify_jwt::{get_claims, BearerToken}, models::user::{find_user, User, UserError}, state::AppState};
 
 
 
@@ -22,7 +22,7 @@ async fn graphql_handler(
     token: Option<BearerToken>, 
     req: GraphQLRequest) -> GraphQLResponse {
     let req = req.into_inner();
-    let schema = Schema::build(Query, Mutation, EmptySubscription)
+    let schema = Schema::build(QueryRoot::default(), Mutation, EmptySubscription)
         .data(db.clone())
         .data(keys.clone())
         .data(maybe_user.clone())
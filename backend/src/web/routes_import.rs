@@ -0,0 +1,114 @@
+use axum::{
+    body::Body,
+    debug_handler,
+    extract::State,
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::post,
+    Extension, Json, Router,
+};
+use serde_json::{json, Value};
+
+use crate::{
+    formats::{
+        lin::{boards_from_lin, LinError},
+        pbn::{boards_from_pbn, PbnError},
+    },
+    middlewares::auth::{
+        lookup_user::lookup_user_from_token,
+        session_owner_guard::{owned_session_guard, unlocked_session_guard},
+        verify_jwt::get_claims_from_auth_token,
+    },
+    models::{
+        board::{create_boards, BoardError},
+        session::SessionMongoDTO,
+    },
+    state::AppState,
+};
+
+use super::routes_board::BoardWebError;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ImportWebError {
+    #[error("PBN error")]
+    PbnError(#[from] PbnError),
+    #[error("LIN error")]
+    LinError(#[from] LinError),
+    #[error("Board error")]
+    BoardError(#[from] BoardError),
+}
+
+impl IntoResponse for ImportWebError {
+    fn into_response(self) -> Response<Body> {
+        match self {
+            ImportWebError::PbnError(e) => {
+                tracing::warn!("Rejected PBN import: {}", e);
+                Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Json(json!({ "error": e.to_string() })).to_string().into())
+                    .unwrap()
+            }
+            ImportWebError::LinError(e) => {
+                tracing::warn!("Rejected LIN import: {}", e);
+                Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(Json(json!({ "error": e.to_string() })).to_string().into())
+                    .unwrap()
+            }
+            ImportWebError::BoardError(e) => BoardWebError::from(e).into_response(),
+        }
+    }
+}
+
+pub fn routes(state: &AppState) -> Router<AppState> {
+    let get_claims_layer =
+        middleware::from_fn_with_state(state.clone(), get_claims_from_auth_token);
+    let lookup_user_layer = middleware::from_fn_with_state(state.clone(), lookup_user_from_token);
+    let owned_session_guard_layer =
+        middleware::from_fn_with_state(state.clone(), owned_session_guard);
+    let unlocked_session_guard_layer = middleware::from_fn(unlocked_session_guard);
+    Router::<AppState>::new()
+        .route(
+            "/api/user/{user_id}/session/{session_id}/import/pbn",
+            post(import_pbn_handler),
+        )
+        .route(
+            "/api/user/{user_id}/session/{session_id}/import/lin",
+            post(import_lin_handler),
+        )
+        .route_layer(unlocked_session_guard_layer)
+        .route_layer(owned_session_guard_layer)
+        .route_layer(lookup_user_layer)
+        .route_layer(get_claims_layer)
+}
+
+#[tracing::instrument(skip(db, session, body))]
+#[debug_handler]
+async fn import_pbn_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    body: String,
+) -> Result<Json<Value>, ImportWebError> {
+    let boards = boards_from_pbn(&body)?;
+    let result = create_boards(&db, &session.id, boards).await?;
+    Ok(Json(json!(result)))
+}
+
+#[tracing::instrument(skip(db, session, body))]
+#[debug_handler]
+async fn import_lin_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    body: String,
+) -> Result<Json<Value>, ImportWebError> {
+    let boards = boards_from_lin(&body)?;
+    let result = create_boards(&db, &session.id, boards).await?;
+    Ok(Json(json!(result)))
+}
@@ -0,0 +1,265 @@
+use axum::{
+    body::Body,
+    debug_handler,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+    Extension, Json, Router,
+};
+use serde_json::{json, Value};
+
+use crate::{
+    middlewares::auth::{
+        lookup_user::lookup_user_from_token,
+        session_owner_guard::{owned_session_guard, session_owner_guard, unlocked_session_guard},
+        verify_jwt::get_claims_from_auth_token,
+    },
+    models::{
+        player::{
+            build_participants, create_partnership, create_player, delete_partnership,
+            delete_player, get_partnerships, get_player, get_players, update_player,
+            NewParticipantDTO, NewPartnershipDTO, NewPlayerDTO, ParticipantJsonDTO,
+            PartnershipJsonDTO, PlayerError, PlayerJsonDTO, PlayerUpdateDTO,
+        },
+        session::{set_participants, SessionError, SessionMongoDTO},
+        user::User,
+    },
+    state::AppState,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum PlayerWebError {
+    #[error("{0}")]
+    PlayerError(#[from] PlayerError),
+    #[error("Session error")]
+    SessionError(#[from] SessionError),
+}
+
+impl IntoResponse for PlayerWebError {
+    fn into_response(self) -> Response<Body> {
+        let status = match self {
+            PlayerWebError::PlayerError(ref e) => match e {
+                PlayerError::PlayerNotFound(_)
+                | PlayerError::PartnershipNotFound(_)
+                | PlayerError::UserNotFound(_) => StatusCode::NOT_FOUND,
+                PlayerError::PlayerInPartnership(_) => StatusCode::CONFLICT,
+                PlayerError::SamePlayerTwice
+                | PlayerError::RepeatedParticipant(_)
+                | PlayerError::RepeatedNumber(_)
+                | PlayerError::SeatOutsideDirection { .. }
+                | PlayerError::NotInPartnership { .. }
+                | PlayerError::NoPlayers(_)
+                | PlayerError::InvalidObjectId(_) => StatusCode::BAD_REQUEST,
+                _ => {
+                    tracing::error!("Player error: {:?}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            },
+            PlayerWebError::SessionError(ref e) => {
+                tracing::error!("Session error: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        Response::builder()
+            .status(status)
+            .body(
+                Json(json!({ "error": self.to_string() }))
+                    .to_string()
+                    .into(),
+            )
+            .unwrap()
+    }
+}
+
+pub fn routes(state: &AppState) -> Router<AppState> {
+    let get_claims_layer =
+        middleware::from_fn_with_state(state.clone(), get_claims_from_auth_token);
+    let lookup_user_layer = middleware::from_fn_with_state(state.clone(), lookup_user_from_token);
+    let session_owner_guard_layer = middleware::from_fn(session_owner_guard);
+    let owned_session_guard_layer =
+        middleware::from_fn_with_state(state.clone(), owned_session_guard);
+    let unlocked_session_guard_layer = middleware::from_fn(unlocked_session_guard);
+    let owned_session_routes = Router::<AppState>::new()
+        .route(
+            "/api/user/{user_id}/session/{session_id}/participants",
+            get(list_participants_handler).put(set_participants_handler),
+        )
+        .route_layer(unlocked_session_guard_layer)
+        .route_layer(owned_session_guard_layer);
+    Router::<AppState>::new()
+        .route(
+            "/api/user/{user_id}/players",
+            get(list_players_handler).post(create_player_handler),
+        )
+        .route(
+            "/api/user/{user_id}/players/{player_id}",
+            get(get_player_handler)
+                .put(update_player_handler)
+                .delete(delete_player_handler),
+        )
+        .route(
+            "/api/user/{user_id}/partnerships",
+            get(list_partnerships_handler).post(create_partnership_handler),
+        )
+        .route(
+            "/api/user/{user_id}/partnerships/{partnership_id}",
+            delete(delete_partnership_handler),
+        )
+        .route_layer(session_owner_guard_layer)
+        .merge(owned_session_routes)
+        .route_layer(lookup_user_layer)
+        .route_layer(get_claims_layer)
+}
+
+#[tracing::instrument(skip(db, user))]
+#[debug_handler]
+async fn list_players_handler(
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, PlayerWebError> {
+    let players: Vec<PlayerJsonDTO> = get_players(&db, &user.id)
+        .await?
+        .into_iter()
+        .map(PlayerJsonDTO::from)
+        .collect();
+    Ok(Json(json!(players)))
+}
+
+#[tracing::instrument(skip(db, user))]
+#[debug_handler]
+async fn create_player_handler(
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<NewPlayerDTO>,
+) -> Result<Json<Value>, PlayerWebError> {
+    let result = create_player(&db, &user.id, payload).await?;
+    Ok(Json(json!(result)))
+}
+
+#[tracing::instrument(skip(db, user))]
+#[debug_handler]
+async fn get_player_handler(
+    Path((_user_id, player_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, PlayerWebError> {
+    let player: PlayerJsonDTO = get_player(&db, &user.id, &player_id).await?.into();
+    Ok(Json(json!(player)))
+}
+
+#[tracing::instrument(skip(db, user))]
+#[debug_handler]
+async fn update_player_handler(
+    Path((_user_id, player_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<PlayerUpdateDTO>,
+) -> Result<StatusCode, PlayerWebError> {
+    update_player(&db, &user.id, &player_id, payload).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[tracing::instrument(skip(db, user))]
+#[debug_handler]
+async fn delete_player_handler(
+    Path((_user_id, player_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<StatusCode, PlayerWebError> {
+    delete_player(&db, &user.id, &player_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[tracing::instrument(skip(db, user))]
+#[debug_handler]
+async fn list_partnerships_handler(
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, PlayerWebError> {
+    let partnerships: Vec<PartnershipJsonDTO> = get_partnerships(&db, &user.id)
+        .await?
+        .into_iter()
+        .map(PartnershipJsonDTO::from)
+        .collect();
+    Ok(Json(json!(partnerships)))
+}
+
+#[tracing::instrument(skip(db, user))]
+#[debug_handler]
+async fn create_partnership_handler(
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<NewPartnershipDTO>,
+) -> Result<Json<Value>, PlayerWebError> {
+    let result = create_partnership(&db, &user.id, payload).await?;
+    Ok(Json(json!(result)))
+}
+
+#[tracing::instrument(skip(db, user))]
+#[debug_handler]
+async fn delete_partnership_handler(
+    Path((_user_id, partnership_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<StatusCode, PlayerWebError> {
+    delete_partnership(&db, &user.id, &partnership_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[tracing::instrument(skip(session))]
+#[debug_handler]
+async fn list_participants_handler(Extension(session): Extension<SessionMongoDTO>) -> Json<Value> {
+    let participants: Vec<ParticipantJsonDTO> = session
+        .participants
+        .into_iter()
+        .map(ParticipantJsonDTO::from)
+        .collect();
+    Json(json!(participants))
+}
+
+/// Replaces the session's participants. Players and partnerships must belong to the
+/// session's owner.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn set_participants_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<Vec<NewParticipantDTO>>,
+) -> Result<Json<Value>, PlayerWebError> {
+    let participants = build_participants(&db, &session.owner, payload).await?;
+    set_participants(&db, &session.id, &participants).await?;
+    let participants: Vec<ParticipantJsonDTO> = participants
+        .into_iter()
+        .map(ParticipantJsonDTO::from)
+        .collect();
+    Ok(Json(json!(participants)))
+}
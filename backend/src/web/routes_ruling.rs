@@ -0,0 +1,145 @@
+use axum::{
+    body::Body,
+    debug_handler,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Json, Router,
+};
+use serde_json::{json, Value};
+
+use crate::{
+    middlewares::auth::{
+        authorization_guard::session_director_guard, lookup_user::lookup_user_from_token,
+        verify_jwt::get_claims_from_auth_token,
+    },
+    models::{
+        board::BoardError,
+        ruling::{
+            amend_ruling, create_ruling, get_ruling, get_rulings_for_session, NewRulingDTO,
+            RulingError, RulingJsonDTO, RulingUpdateDTO,
+        },
+        session::SessionMongoDTO,
+        user::User,
+    },
+    state::AppState,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum RulingWebError {
+    #[error("{0}")]
+    RulingError(#[from] RulingError),
+}
+
+impl IntoResponse for RulingWebError {
+    fn into_response(self) -> Response<Body> {
+        let RulingWebError::RulingError(ref e) = self;
+        let status = match e {
+            RulingError::RulingNotFound(_)
+            | RulingError::BoardError(BoardError::BoardNotFound(_)) => StatusCode::NOT_FOUND,
+            RulingError::ResultNotFound(_)
+            | RulingError::InvalidObjectId(_)
+            | RulingError::BoardError(BoardError::InvalidObjectId(_)) => StatusCode::BAD_REQUEST,
+            _ => {
+                tracing::error!("Ruling error: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        Response::builder()
+            .status(status)
+            .body(
+                Json(json!({ "error": self.to_string() }))
+                    .to_string()
+                    .into(),
+            )
+            .unwrap()
+    }
+}
+
+pub fn routes(state: &AppState) -> Router<AppState> {
+    let get_claims_layer =
+        middleware::from_fn_with_state(state.clone(), get_claims_from_auth_token);
+    let lookup_user_layer = middleware::from_fn_with_state(state.clone(), lookup_user_from_token);
+    let session_director_guard_layer =
+        middleware::from_fn_with_state(state.clone(), session_director_guard);
+    Router::<AppState>::new()
+        .route(
+            "/api/session/{session_id}/rulings",
+            get(list_rulings_handler).post(create_ruling_handler),
+        )
+        .route(
+            "/api/session/{session_id}/rulings/{ruling_id}",
+            get(get_ruling_handler).put(amend_ruling_handler),
+        )
+        .route_layer(session_director_guard_layer)
+        .route_layer(lookup_user_layer)
+        .route_layer(get_claims_layer)
+}
+
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn list_rulings_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, RulingWebError> {
+    let rulings: Vec<RulingJsonDTO> = get_rulings_for_session(&db, &session.id)
+        .await?
+        .into_iter()
+        .map(RulingJsonDTO::from)
+        .collect();
+    Ok(Json(json!(rulings)))
+}
+
+#[tracing::instrument(skip(db, session, user))]
+#[debug_handler]
+async fn create_ruling_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<NewRulingDTO>,
+) -> Result<Json<Value>, RulingWebError> {
+    let ruling: RulingJsonDTO = create_ruling(&db, &session.id, &user.id, payload)
+        .await?
+        .into();
+    Ok(Json(json!(ruling)))
+}
+
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn get_ruling_handler(
+    Path((_session_id, ruling_id)): Path<(String, String)>,
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, RulingWebError> {
+    let ruling: RulingJsonDTO = get_ruling(&db, &session.id, &ruling_id).await?.into();
+    Ok(Json(json!(ruling)))
+}
+
+#[tracing::instrument(skip(db, session, user))]
+#[debug_handler]
+async fn amend_ruling_handler(
+    Path((_session_id, ruling_id)): Path<(String, String)>,
+    Extension(session): Extension<SessionMongoDTO>,
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<RulingUpdateDTO>,
+) -> Result<Json<Value>, RulingWebError> {
+    let ruling: RulingJsonDTO = amend_ruling(&db, &session.id, &ruling_id, &user.id, payload)
+        .await?
+        .into();
+    Ok(Json(json!(ruling)))
+}
@@ -0,0 +1,288 @@
+use axum::{
+    body::Body,
+    debug_handler,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Extension, Json, Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+    middlewares::auth::{
+        lookup_user::lookup_user_from_token,
+        session_owner_guard::{owned_session_guard, unlocked_session_guard},
+        verify_jwt::get_claims_from_auth_token,
+    },
+    models::{
+        board::{get_boards_for_session, BoardError},
+        session::{set_movement, set_team_event, ScoringType, SessionError, SessionMongoDTO},
+    },
+    state::AppState,
+    tournament::{
+        movement::{howell, mitchell, EvenTables, MovementError, MovementKind},
+        round_robin::round_robin,
+        swiss::{pair_next_round, SwissError},
+        teams::{TeamEvent, TeamEventError, TeamEventFormat},
+    },
+};
+
+use super::routes_board::BoardWebError;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MovementPayload {
+    kind: MovementKind,
+    pairs: u32,
+    boards_per_round: u32,
+    /// Defaults to every round the movement has.
+    rounds: Option<u32>,
+    /// Mitchells only.
+    #[serde(default)]
+    even_tables: EvenTables,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTeamEventPayload {
+    /// Team names, numbered from 1 in this order.
+    teams: Vec<String>,
+    boards_per_match: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundRobinPayload {
+    teams: Vec<String>,
+    boards_per_match: u32,
+    /// Play every opponent twice, home and away.
+    #[serde(default)]
+    double: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchResultPayload {
+    home_imps: i32,
+    away_imps: i32,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TournamentWebError {
+    #[error("Movements are only for pairs sessions")]
+    NotPairsSession,
+    #[error("Team events are only for IMP sessions")]
+    NotTeamsSession,
+    #[error("The session has no team event")]
+    NoTeamEvent,
+    #[error("{0}")]
+    MovementError(#[from] MovementError),
+    #[error("{0}")]
+    TeamEventError(#[from] TeamEventError),
+    #[error("{0}")]
+    SwissError(#[from] SwissError),
+    #[error("Session error")]
+    SessionError(#[from] SessionError),
+    #[error("Board error")]
+    BoardError(#[from] BoardError),
+}
+
+impl IntoResponse for TournamentWebError {
+    fn into_response(self) -> Response<Body> {
+        let status = match self {
+            TournamentWebError::BoardError(e) => return BoardWebError::from(e).into_response(),
+            TournamentWebError::NotPairsSession
+            | TournamentWebError::NotTeamsSession
+            | TournamentWebError::SwissError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            TournamentWebError::NoTeamEvent
+            | TournamentWebError::TeamEventError(TeamEventError::MatchNotFound { .. }) => {
+                StatusCode::NOT_FOUND
+            }
+            TournamentWebError::MovementError(_) | TournamentWebError::TeamEventError(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            TournamentWebError::SessionError(ref e) => {
+                tracing::error!("Session error: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        Response::builder()
+            .status(status)
+            .body(
+                Json(json!({ "error": self.to_string() }))
+                    .to_string()
+                    .into(),
+            )
+            .unwrap()
+    }
+}
+
+pub fn routes(state: &AppState) -> Router<AppState> {
+    let get_claims_layer =
+        middleware::from_fn_with_state(state.clone(), get_claims_from_auth_token);
+    let lookup_user_layer = middleware::from_fn_with_state(state.clone(), lookup_user_from_token);
+    let owned_session_guard_layer =
+        middleware::from_fn_with_state(state.clone(), owned_session_guard);
+    let unlocked_session_guard_layer = middleware::from_fn(unlocked_session_guard);
+    Router::<AppState>::new()
+        .route(
+            "/api/user/{user_id}/session/{session_id}/movement",
+            post(movement_handler),
+        )
+        .route(
+            "/api/user/{user_id}/session/{session_id}/team-event",
+            get(team_event_handler),
+        )
+        .route(
+            "/api/user/{user_id}/session/{session_id}/round-robin",
+            post(round_robin_handler),
+        )
+        .route(
+            "/api/user/{user_id}/session/{session_id}/swiss",
+            post(new_swiss_handler),
+        )
+        .route(
+            "/api/user/{user_id}/session/{session_id}/swiss/rounds",
+            post(next_swiss_round_handler),
+        )
+        .route(
+            "/api/user/{user_id}/session/{session_id}/team-event/rounds/{round}/matches/{table}",
+            put(match_result_handler),
+        )
+        .route_layer(unlocked_session_guard_layer)
+        .route_layer(owned_session_guard_layer)
+        .route_layer(lookup_user_layer)
+        .route_layer(get_claims_layer)
+}
+
+/// Generates a movement for the session and stores it, replacing any earlier one.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn movement_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<MovementPayload>,
+) -> Result<Json<Value>, TournamentWebError> {
+    if !session.scoring_type.is_pairs_event() {
+        return Err(TournamentWebError::NotPairsSession);
+    }
+    let movement = match payload.kind {
+        MovementKind::Mitchell => mitchell(
+            payload.pairs,
+            payload.boards_per_round,
+            payload.rounds,
+            payload.even_tables,
+        )?,
+        MovementKind::Howell => howell(payload.pairs, payload.boards_per_round, payload.rounds)?,
+    };
+    set_movement(&db, &session.id, &movement).await?;
+    Ok(Json(json!(movement)))
+}
+
+/// The session's team event, with results filled in from any boards played so far.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn team_event_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, TournamentWebError> {
+    let mut event = session.team_event.ok_or(TournamentWebError::NoTeamEvent)?;
+    let boards = get_boards_for_session(&db, &session.id).await?;
+    event.record_results(&boards);
+    Ok(Json(json!(event)))
+}
+
+/// Schedules a round robin for the session. The same teams and settings always give the
+/// same schedule, so posting again regenerates it.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn round_robin_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<RoundRobinPayload>,
+) -> Result<Json<Value>, TournamentWebError> {
+    if session.scoring_type != ScoringType::Imp {
+        return Err(TournamentWebError::NotTeamsSession);
+    }
+    let event = round_robin(payload.teams, payload.boards_per_match, payload.double)?;
+    set_team_event(&db, &session.id, &event).await?;
+    Ok(Json(json!(event)))
+}
+
+/// Starts a Swiss event in the session and pairs its first round.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn new_swiss_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<NewTeamEventPayload>,
+) -> Result<Json<Value>, TournamentWebError> {
+    if session.scoring_type != ScoringType::Imp {
+        return Err(TournamentWebError::NotTeamsSession);
+    }
+    let mut event = TeamEvent::new(
+        TeamEventFormat::Swiss,
+        payload.teams,
+        payload.boards_per_match,
+    )?;
+    let round = pair_next_round(&event)?;
+    event.rounds.push(round);
+    set_team_event(&db, &session.id, &event).await?;
+    Ok(Json(json!(event)))
+}
+
+/// Records the results of the rounds played from the session's boards, then pairs the
+/// next round.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn next_swiss_round_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, TournamentWebError> {
+    let mut event = session.team_event.ok_or(TournamentWebError::NoTeamEvent)?;
+    let boards = get_boards_for_session(&db, &session.id).await?;
+    event.record_results(&boards);
+    let round = pair_next_round(&event);
+    // Keep whatever results could be recorded, even if the round cannot be paired yet.
+    if let Ok(round) = &round {
+        event.rounds.push(round.clone());
+    }
+    set_team_event(&db, &session.id, &event).await?;
+    Ok(Json(json!(round?)))
+}
+
+/// Enters a match's score directly, for matches whose boards are not in the session.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn match_result_handler(
+    Path((_user_id, _session_id, round, table)): Path<(String, String, u32, u32)>,
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<MatchResultPayload>,
+) -> Result<StatusCode, TournamentWebError> {
+    let mut event = session.team_event.ok_or(TournamentWebError::NoTeamEvent)?;
+    event.set_result(round, table, payload.home_imps, payload.away_imps)?;
+    set_team_event(&db, &session.id, &event).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -2,7 +2,7 @@ use axum::{
     body::Body,
     debug_handler,
     extract::{Path, State},
-    http::StatusCode,
+    http::{header, StatusCode},
     middleware,
     response::{IntoResponse, Response},
     routing::{get, post, put},
@@ -15,12 +15,20 @@ use std::str::FromStr;
 
 use crate::{
     auth::jwt::Claims,
+    formats::pbn::export_pbn,
     middlewares::auth::{
-        lookup_user::lookup_user_from_token, session_owner_guard::session_owner_guard,
+        lookup_user::lookup_user_from_token, session_owner_guard::{owned_session_guard, session_owner_guard},
         verify_jwt::get_claims_from_auth_token,
     },
-    models::session::{
-        create_session, get_sessions_for_user_id, update_session, NewSessionDTO, ScoringType, SessionError, SessionUpdateDTO
+    models::{
+        board::{get_boards_for_session, BoardError},
+        session::{
+            create_session, get_sessions_for_user_id, reopen_session, set_session_status, update_session, NewSessionDTO,
+            ReopeningJsonDTO, ScoringType, SessionError, SessionMongoDTO, SessionStatus, SessionUpdateDTO
+        },
+        standings::get_session_standings,
+        summary::{get_session_summary, SummaryError},
+        user::User,
     },
     state::AppState,
 };
@@ -30,6 +38,16 @@ pub struct SessionSearchPayload {
     scoring_type: Option<ScoringType>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SessionStatusPayload {
+    status: SessionStatus,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReopenSessionPayload {
+    reason: String,
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum SessionWebError {
     #[error("Cannot save session to different user")]
@@ -38,6 +56,10 @@ pub enum SessionWebError {
     BsonError(#[from] bson::oid::Error),
     #[error("Data error")]
     UnexpectedError(#[from] SessionError),
+    #[error("Summary error")]
+    SummaryError(#[from] SummaryError),
+    #[error("Board error")]
+    BoardError(#[from] BoardError),
 }
 
 impl IntoResponse for SessionWebError {
@@ -55,8 +77,16 @@ impl IntoResponse for SessionWebError {
                     .unwrap()
             }
             SessionWebError::UnexpectedError(e) => {
+                let status = match e {
+                    SessionError::SessionNotFound(_) => StatusCode::NOT_FOUND,
+                    SessionError::SessionFinal(_)
+                    | SessionError::InvalidStatusTransition { .. }
+                    | SessionError::ConcurrentUpdate(_) => StatusCode::CONFLICT,
+                    SessionError::MissingReopenReason => StatusCode::BAD_REQUEST,
+                    _ => StatusCode::INTERNAL_SERVER_ERROR,
+                };
                 Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .status(status)
                     .body(Json(json!({ "error": e.to_string() })).to_string().into())
                     .unwrap()
             }
@@ -66,6 +96,20 @@ impl IntoResponse for SessionWebError {
                     .body(Json(json!({ "error": e.to_string() })).to_string().into())
                     .unwrap()
             }
+            SessionWebError::BoardError(e) => {
+                tracing::error!("Board error: {:?}", e);
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Json(json!({ "error": e.to_string() })).to_string().into())
+                    .unwrap()
+            }
+            SessionWebError::SummaryError(e) => {
+                tracing::error!("Summary error: {:?}", e);
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Json(json!({ "error": e.to_string() })).to_string().into())
+                    .unwrap()
+            }
         }
     }
 }
@@ -75,11 +119,20 @@ pub fn routes(state: &AppState) -> Router<AppState> {
         middleware::from_fn_with_state(state.clone(), get_claims_from_auth_token);
     let lookup_user_layer = middleware::from_fn_with_state(state.clone(), lookup_user_from_token);
     let session_owner_guard_layer = middleware::from_fn(session_owner_guard);
+    let owned_session_guard_layer = middleware::from_fn_with_state(state.clone(), owned_session_guard);
+    let owned_session_routes = Router::<AppState>::new()
+        .route("/api/user/{user_id}/session/{session_id}/summary", get(session_summary_handler))
+        .route("/api/user/{user_id}/session/{session_id}/standings", get(session_standings_handler))
+        .route("/api/user/{user_id}/session/{session_id}/export/pbn", get(export_pbn_handler))
+        .route("/api/user/{user_id}/session/{session_id}/status", put(session_status_handler))
+        .route("/api/user/{user_id}/session/{session_id}/reopen", post(reopen_session_handler))
+        .route_layer(owned_session_guard_layer);
     Router::<AppState>::new()
         .route("/api/user/{user_id}/sessions", get(session_search))
         .route("/api/user/{user_id}/session", post(create_session_handler))
         .route_layer(session_owner_guard_layer)
         .route("/api/user/{user_id}/session/{session_id}", put(update_session_handler))
+        .merge(owned_session_routes)
         .route_layer(lookup_user_layer)
         .route_layer(get_claims_layer)
 }
@@ -142,4 +195,89 @@ async fn update_session_handler(
     // StatusCode::NO_CONTENT.into_response()
         //.body(Json(retval).to_string().into())
         //.unwrap()
+}
+
+/// Moves the session through draft, in progress, scoring and final.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn session_status_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<SessionStatusPayload>,
+) -> Result<StatusCode, SessionWebError> {
+    set_session_status(&db, &session, payload.status).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Reopens a final session. Only the owner gets past the session guard.
+#[tracing::instrument(skip(db, session, user))]
+#[debug_handler]
+async fn reopen_session_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    Extension(user): Extension<User>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+    Json(payload): Json<ReopenSessionPayload>,
+) -> Result<Json<Value>, SessionWebError> {
+    let reopening: ReopeningJsonDTO = reopen_session(&db, &session, &user.id, &payload.reason).await?.into();
+    Ok(Json(json!(reopening)))
+}
+
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn session_summary_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, SessionWebError> {
+    let summary = get_session_summary(&db, &session).await?;
+    Ok(Json(json!(summary)))
+}
+
+/// Pairs or teams ranked on the session's results so far.
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn session_standings_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Json<Value>, SessionWebError> {
+    let standings = get_session_standings(&db, &session).await?;
+    Ok(Json(json!(standings)))
+}
+
+#[tracing::instrument(skip(db, session))]
+#[debug_handler]
+async fn export_pbn_handler(
+    Extension(session): Extension<SessionMongoDTO>,
+    State(AppState {
+        mongodb_client: db,
+        keys: _,
+    }): State<AppState>,
+) -> Result<Response<Body>, SessionWebError> {
+    let boards = get_boards_for_session(&db, &session.id).await?;
+    let pbn = export_pbn(&session, &boards);
+    let file_name: String = session
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/x-pbn; charset=utf-8")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.pbn\"", file_name),
+        )
+        .body(pbn.into())
+        .unwrap())
 }
\ No newline at end of file